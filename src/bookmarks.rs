@@ -0,0 +1,143 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::settings::config_dir;
+
+// -------------------------------
+// Bookmarks
+// -------------------------------
+
+/// A bookmarked verse, identified by the module's book short name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bookmark {
+    pub book: String,
+    pub chapter: u32,
+    pub verse: u32,
+}
+
+impl fmt::Display for Bookmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}:{}", self.book, self.chapter, self.verse)
+    }
+}
+
+impl Bookmark {
+    /// Parse the `Book Chapter:Verse` form written by `Display`.
+    pub fn parse(line: &str) -> Option<Bookmark> {
+        let (book, rest) = line.trim().rsplit_once(char::is_whitespace)?;
+        let (chapter, verse) = rest.split_once(':')?;
+        let book = book.trim();
+        if book.is_empty() {
+            return None;
+        }
+        Some(Bookmark {
+            book: book.to_string(),
+            chapter: chapter.parse().ok()?,
+            verse: verse.parse().ok()?,
+        })
+    }
+}
+
+/// Where bookmarks are kept between runs.
+pub fn bookmarks_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("bookmarks.txt"))
+}
+
+/// Read bookmarks, one reference per line. A missing or unreadable file yields no bookmarks.
+pub fn load_bookmarks(path: &Path) -> Vec<Bookmark> {
+    fs::read_to_string(path)
+        .map(|contents| contents.lines().filter_map(Bookmark::parse).collect())
+        .unwrap_or_default()
+}
+
+/// Write bookmarks, one reference per line, creating the config directory if needed.
+pub fn save_bookmarks(path: &Path, bookmarks: &[Bookmark]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut contents = String::new();
+    for bookmark in bookmarks {
+        contents.push_str(&bookmark.to_string());
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+}
+
+/// Parse an imported list of references, one `Book Chapter:Verse` per line.
+/// Blank lines and `#` comments are skipped; lines that aren't references are returned
+/// as `line N: text` errors. Duplicates within the list are dropped.
+pub fn parse_reference_list(contents: &str) -> (Vec<Bookmark>, Vec<String>) {
+    let mut refs: Vec<Bookmark> = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match Bookmark::parse(line) {
+            Some(bookmark) if !refs.contains(&bookmark) => refs.push(bookmark),
+            Some(_) => {}
+            None => errors.push(format!("line {}: {}", idx + 1, line)),
+        }
+    }
+    (refs, errors)
+}
+
+/// Assemble a Markdown export of the bookmarks with their texts from the loaded module.
+/// `fetch` returns a bookmark's verse text, or `None` when the module lacks that verse.
+pub fn format_bookmark_export<F>(bookmarks: &[Bookmark], bible: &str, mut fetch: F) -> String
+where
+    F: FnMut(&Bookmark) -> Option<String>,
+{
+    let mut out = format!("# Bookmarks — {}\n\n", bible);
+    for bookmark in bookmarks {
+        match fetch(bookmark) {
+            Some(text) => out.push_str(&format!("- **{}** {}\n", bookmark, text)),
+            None => out.push_str(&format!("- **{}** _(not in this translation)_\n", bookmark)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(book: &str, chapter: u32, verse: u32) -> Bookmark {
+        Bookmark { book: book.to_string(), chapter, verse }
+    }
+
+    #[test]
+    fn reference_lists_skip_comments_and_report_bad_lines() {
+        let contents = "# Psalms\nPs 23:1\n\n  Song of Solomon 2:1  \nPs 23\nPs 23:1\nnot a reference\n";
+        let (refs, errors) = parse_reference_list(contents);
+        assert_eq!(refs, [bookmark("Ps", 23, 1), bookmark("Song of Solomon", 2, 1)]);
+        assert_eq!(errors, ["line 5: Ps 23", "line 7: not a reference"]);
+        assert_eq!(parse_reference_list(""), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn export_lists_each_bookmark_with_its_text() {
+        let bookmarks = [bookmark("Ps", 23, 1), bookmark("Song", 9, 9), bookmark("1Jn", 4, 9)];
+        let mut fetched = Vec::new();
+        let export = format_bookmark_export(&bookmarks, "KJV", |b| {
+            fetched.push(b.to_string());
+            match b.book.as_str() {
+                "Ps" => Some("The LORD is my shepherd; I shall not want.".to_string()),
+                "1Jn" => Some("In this was manifested the love of God".to_string()),
+                _ => None,
+            }
+        });
+        assert_eq!(fetched, ["Ps 23:1", "Song 9:9", "1Jn 4:9"]);
+        assert_eq!(
+            export,
+            "# Bookmarks — KJV\n\n\
+             - **Ps 23:1** The LORD is my shepherd; I shall not want.\n\
+             - **Song 9:9** _(not in this translation)_\n\
+             - **1Jn 4:9** In this was manifested the love of God\n"
+        );
+        assert_eq!(format_bookmark_export(&[], "KJV", |_| None), "# Bookmarks — KJV\n\n");
+    }
+}
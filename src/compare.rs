@@ -0,0 +1,523 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::markup::clean_text;
+use crate::Verse;
+
+// -------------------------------
+// Compare Module Discovery
+// -------------------------------
+
+/// How duplicate Bible files in the compare directory are collapsed into one column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupMode {
+    /// Every file gets its own column.
+    Off,
+    /// Files with the same `info` description share one column.
+    Description,
+    /// Files must share both the description and the file contents.
+    DescriptionAndHash,
+}
+
+impl DedupMode {
+    pub const ALL: [DedupMode; 3] = [
+        DedupMode::Off,
+        DedupMode::Description,
+        DedupMode::DescriptionAndHash,
+    ];
+}
+
+impl fmt::Display for DedupMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            DedupMode::Off => "Show duplicates",
+            DedupMode::Description => "Merge by description",
+            DedupMode::DescriptionAndHash => "Merge by description + file hash",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A Bible file found in the compare directory, plus any files merged into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredModule {
+    pub path: PathBuf,
+    pub description: String,
+    pub duplicates: Vec<PathBuf>,
+}
+
+impl DiscoveredModule {
+    /// Column label: the description, with a note naming any merged duplicate files.
+    pub fn label(&self) -> String {
+        if self.duplicates.is_empty() {
+            return self.description.clone();
+        }
+        let names: Vec<String> = self
+            .duplicates
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| p.display().to_string())
+            })
+            .collect();
+        format!("{} (duplicate: {})", self.description, names.join(", "))
+    }
+}
+
+impl fmt::Display for DiscoveredModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Collapse duplicate translations in a discovered `(path, description)` list.
+/// The first file of each group (in list order) is kept; the others are recorded as duplicates.
+/// `hash_of` is only consulted in `DescriptionAndHash` mode; files it can't hash are never merged.
+pub fn dedup_modules<F>(found: Vec<(PathBuf, String)>, mode: DedupMode, hash_of: F) -> Vec<DiscoveredModule>
+where
+    F: Fn(&Path) -> Option<u64>,
+{
+    let mut modules: Vec<DiscoveredModule> = Vec::new();
+    let mut keys: Vec<Option<(String, Option<u64>)>> = Vec::new();
+    for (path, description) in found {
+        let key = match mode {
+            DedupMode::Off => None,
+            DedupMode::Description => Some((description.clone(), None)),
+            DedupMode::DescriptionAndHash => hash_of(&path).map(|h| (description.clone(), Some(h))),
+        };
+        if let Some(k) = &key {
+            if let Some(idx) = keys.iter().position(|existing| existing.as_ref() == Some(k)) {
+                modules[idx].duplicates.push(path);
+                continue;
+            }
+        }
+        keys.push(key);
+        modules.push(DiscoveredModule {
+            path,
+            description,
+            duplicates: Vec::new(),
+        });
+    }
+    modules
+}
+
+/// Hash a file's contents, used to confirm that two same-description files really are identical.
+pub fn file_hash(path: &Path) -> Option<u64> {
+    let bytes = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Whether a file name looks like a module: `.SQLite3`, or zstd-compressed `.SQLite3.zst`
+/// when the build has zstd support.
+fn is_module_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_ascii_lowercase();
+    name.ends_with(".sqlite3") || (cfg!(feature = "zstd") && name.ends_with(".sqlite3.zst"))
+}
+
+/// How many directory levels below the compare directory a recursive scan descends.
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// List the module files in `dir`, sorted by path so column order is stable.
+/// With `recursive`, subdirectories are scanned too, down to `MAX_SCAN_DEPTH` levels;
+/// hidden directories (e.g. `.git`) are skipped.
+pub fn module_files(dir: &Path, recursive: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let depth = if recursive { MAX_SCAN_DEPTH } else { 0 };
+    collect_module_files(dir, depth, &mut HashSet::new(), &mut files);
+    files.sort();
+    files
+}
+
+/// Add the module files in `dir` to `files`, descending `depth` more levels.
+/// Directories are tracked by canonical path, so a symlink loop is walked only once.
+fn collect_module_files(dir: &Path, depth: usize, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.is_dir() {
+            let hidden = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'));
+            if depth > 0 && !hidden {
+                collect_module_files(&path, depth - 1, visited, files);
+            }
+        } else if is_module_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// Character and word counts over a translation's compared verses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextTotals {
+    pub chars: usize,
+    pub words: usize,
+}
+
+/// Sum the character and whitespace-separated word counts of a passage's verse texts.
+pub fn text_totals<'a, I>(texts: I) -> TextTotals
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    texts.into_iter().fold(TextTotals::default(), |acc, t| TextTotals {
+        chars: acc.chars + t.chars().count(),
+        words: acc.words + t.split_whitespace().count(),
+    })
+}
+
+/// How each translation's verses are laid out in the compare results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellLayout {
+    /// One line per verse, each clickable to pin it.
+    #[default]
+    VersePerLine,
+    /// The verses run together as one flowing paragraph.
+    Paragraph,
+}
+
+impl CellLayout {
+    pub const ALL: [CellLayout; 2] = [CellLayout::VersePerLine, CellLayout::Paragraph];
+}
+
+impl fmt::Display for CellLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            CellLayout::VersePerLine => "Verse per line",
+            CellLayout::Paragraph => "Paragraph",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A translation's verses as its compare cell shows them, one piece per verse. Verse per
+/// line gives `chapter:verse text` lines; a paragraph joins the verses with a space, leads
+/// with `chapter:` only where a new chapter starts, and lays each text out as the lookup
+/// list does (line breaks kept with `keep_line_breaks`, collapsed otherwise). Pinned verses
+/// are marked with `*`, and markup is removed unless `raw_markup` asks to see it.
+pub fn cell_pieces(
+    verses: &[Verse],
+    layout: CellLayout,
+    pinned: &BTreeSet<VerseKey>,
+    raw_markup: bool,
+    keep_line_breaks: bool,
+) -> Vec<(VerseKey, String)> {
+    let mut chapter = None;
+    verses
+        .iter()
+        .enumerate()
+        .map(|(idx, verse)| {
+            let key = (verse.chapter, verse.verse);
+            let marker = if pinned.contains(&key) { "* " } else { "" };
+            let text = crate::display_text(&verse.text, raw_markup);
+            let piece = match layout {
+                CellLayout::VersePerLine => format!("{}{}:{} {}", marker, verse.chapter, verse.verse_label(), text),
+                CellLayout::Paragraph => {
+                    let separator = if idx > 0 { " " } else { "" };
+                    let chapter_label = if chapter != Some(verse.chapter) { format!("{}:", verse.chapter) } else { String::new() };
+                    chapter = Some(verse.chapter);
+                    let text = crate::verse_layout(text.trim(), keep_line_breaks);
+                    format!("{}{}{}{} {}", separator, marker, chapter_label, verse.verse_label(), text)
+                }
+            };
+            (key, piece)
+        })
+        .collect()
+}
+
+/// A compared verse's position, `(chapter, verse)`.
+pub type VerseKey = (u32, u32);
+
+/// One translation's cell in a compare row: `(Bible label, verse text if present)`.
+pub type CompareCell<'a> = (&'a str, Option<&'a str>);
+
+/// Filter the compare results down to the pinned verses, one row per pinned verse
+/// (in canonical order) holding each translation's text, or None where it lacks the verse.
+pub fn pinned_rows<'a>(
+    compare_results: &'a [(String, Vec<Verse>)],
+    pinned: &BTreeSet<VerseKey>,
+) -> Vec<(VerseKey, Vec<CompareCell<'a>>)> {
+    pinned
+        .iter()
+        .map(|&key| {
+            let cells = compare_results
+                .iter()
+                .map(|(bible_name, verses)| {
+                    let text = verses
+                        .iter()
+                        .find(|v| (v.chapter, v.verse) == key)
+                        .map(|v| v.text.as_str());
+                    (bible_name.as_str(), text)
+                })
+                .collect();
+            (key, cells)
+        })
+        .collect()
+}
+
+/// The reference the comparison should switch to after a lookup of `lookup`, or None to
+/// leave it alone: a pinned comparison never moves, no comparison is started by a
+/// plain lookup, and re-looking-up the shown reference needs no new comparison.
+pub fn followed_compare_reference(pinned: bool, shown: Option<&str>, lookup: &str) -> Option<String> {
+    match shown {
+        Some(shown) if !pinned && shown.trim() != lookup.trim() => Some(lookup.to_string()),
+        _ => None,
+    }
+}
+
+/// Label for a translation: its `info` description, or the file stem when the module
+/// has no info table or description, so every module is at least identifiable by filename.
+pub fn translation_label(description: Option<String>, path: &Path) -> String {
+    description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .or_else(|| {
+            // "KJV.SQLite3.zst" is labelled "KJV", like "KJV.SQLite3".
+            let path = if crate::compressed::is_zstd(path) { Path::new(path.file_stem()?) } else { path };
+            path.file_stem().map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "Unknown Bible".to_string())
+}
+
+/// A verse's position shared across translations: `(book number, chapter, verse)`.
+pub type VerseRef = (u32, u32, u32);
+
+/// Whether two renderings of a verse read the same, ignoring whitespace differences.
+pub fn same_rendering(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// Whether every translation has the verse and all of them render it the same.
+/// A single translation has nothing to agree with, so it never counts as identical.
+pub fn all_identical(cells: &[CompareCell]) -> bool {
+    let mut texts = cells.iter().map(|(_, text)| *text);
+    match texts.next() {
+        Some(Some(first)) if cells.len() > 1 => texts.all(|t| t.is_some_and(|t| same_rendering(first, t))),
+        _ => false,
+    }
+}
+
+/// The compared verses that read identically across all translations, for dimming.
+pub fn identical_verses(compare_results: &[(String, Vec<Verse>)]) -> BTreeSet<VerseKey> {
+    let keys: BTreeSet<VerseKey> = compare_results
+        .iter()
+        .flat_map(|(_, verses)| verses.iter().map(|v| (v.chapter, v.verse)))
+        .collect();
+    pinned_rows(compare_results, &keys)
+        .into_iter()
+        .filter(|(_, cells)| all_identical(cells))
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// For each search hit, whether the baseline translation renders it identically
+/// (`Some(true)`), differently (`Some(false)`), or lacks the verse (`None`). Both sides are
+/// compared with their markup removed, so only the words can differ.
+pub fn baseline_flags<'a>(
+    hits: impl IntoIterator<Item = &'a Verse>,
+    baseline: &HashMap<VerseRef, String>,
+) -> HashMap<VerseRef, Option<bool>> {
+    hits.into_iter()
+        .map(|hit| {
+            let verse_ref = (hit.book_number, hit.chapter, hit.verse);
+            let flag = baseline
+                .get(&verse_ref)
+                .map(|text| same_rendering(&clean_text(&hit.text), &clean_text(text)));
+            (verse_ref, flag)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{temp_dir, verse};
+
+    #[test]
+    fn paragraph_pieces_remove_markup_unless_raw() {
+        let verses = [
+            verse("John", 3, 16, "<J>For God so loved the world</J><f>[1]</f>"),
+            verse("John", 4, 1, "When <i>therefore</i> the Lord knew"),
+        ];
+        let paragraph = |raw_markup| {
+            cell_pieces(&verses, CellLayout::Paragraph, &BTreeSet::new(), raw_markup, false)
+                .into_iter()
+                .map(|(_, piece)| piece)
+                .collect::<String>()
+        };
+        assert_eq!(paragraph(false), "3:16 For God so loved the world 4:1 When therefore the Lord knew");
+        assert_eq!(
+            paragraph(true),
+            "3:16 <J>For God so loved the world</J><f>[1]</f> 4:1 When <i>therefore</i> the Lord knew"
+        );
+    }
+
+    #[test]
+    fn cell_content_differs_between_layouts() {
+        let verses = [
+            verse("Ps", 23, 1, "The LORD is my shepherd;\n  I shall not want."),
+            verse("Ps", 23, 2, "He maketh me to lie down"),
+            verse("Ps", 24, 1, "The earth is the LORD's"),
+        ];
+        let pinned = BTreeSet::from([(23, 2)]);
+        let pieces = |layout, keep_line_breaks| {
+            cell_pieces(&verses, layout, &pinned, false, keep_line_breaks)
+                .into_iter()
+                .map(|(_, piece)| piece)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            pieces(CellLayout::VersePerLine, false),
+            ["23:1 The LORD is my shepherd;\n  I shall not want.", "* 23:2 He maketh me to lie down", "24:1 The earth is the LORD's"]
+        );
+        assert_eq!(
+            pieces(CellLayout::Paragraph, false),
+            ["23:1 The LORD is my shepherd; I shall not want.", " * 2 He maketh me to lie down", " 24:1 The earth is the LORD's"]
+        );
+        assert_eq!(pieces(CellLayout::Paragraph, true)[0], "23:1 The LORD is my shepherd;\n  I shall not want.");
+        let keys: Vec<_> = cell_pieces(&verses, CellLayout::Paragraph, &pinned, false, false).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, [(23, 1), (23, 2), (24, 1)]);
+    }
+
+    #[test]
+    fn baseline_flags_compare_the_words_without_markup() {
+        let hits = [
+            verse("John", 3, 16, "<J>For God so loved the world</J><f>[1]</f>"),
+            verse("John", 11, 35, "Jesus wept."),
+            verse("Ps", 23, 1, "The LORD is my shepherd"),
+        ];
+        let baseline = HashMap::from([
+            ((500, 3, 16), "For God so <i>loved</i> the  world".to_string()),
+            ((500, 11, 35), "Jesus cried.".to_string()),
+        ]);
+        let flags = baseline_flags(&hits, &baseline);
+        assert_eq!(flags[&(500, 3, 16)], Some(true));
+        assert_eq!(flags[&(500, 11, 35)], Some(false));
+        assert_eq!(flags[&(230, 23, 1)], None);
+    }
+
+    #[test]
+    fn text_totals_sum_over_the_passage() {
+        let verses = [
+            verse("Ps", 23, 1, "The LORD is my shepherd;"),
+            verse("Ps", 23, 2, "  He maketh me\tto lie down  "),
+            verse("Ps", 23, 3, ""),
+            verse("John", 1, 1, "Ἐν ἀρχῇ ἦν"),
+        ];
+        let totals = text_totals(verses.iter().map(|v| v.text.as_str()));
+        assert_eq!(totals, TextTotals { chars: 24 + 28 + 10, words: 5 + 6 + 3 });
+        assert_eq!(text_totals(std::iter::empty()), TextTotals::default());
+    }
+
+    #[test]
+    fn pinned_rows_hold_each_translations_text() {
+        let results = vec![
+            (
+                "KJV".to_string(),
+                vec![verse("Ps", 23, 1, "The LORD is my shepherd"), verse("Ps", 23, 2, "green pastures")],
+            ),
+            ("WEB".to_string(), vec![verse("Ps", 23, 2, "green pastures, still waters")]),
+        ];
+        let pinned = BTreeSet::from([(23, 2), (23, 1), (23, 9)]);
+        assert_eq!(
+            pinned_rows(&results, &pinned),
+            [
+                ((23, 1), vec![("KJV", Some("The LORD is my shepherd")), ("WEB", None)]),
+                ((23, 2), vec![("KJV", Some("green pastures")), ("WEB", Some("green pastures, still waters"))]),
+                ((23, 9), vec![("KJV", None), ("WEB", None)]),
+            ]
+        );
+        assert!(pinned_rows(&results, &BTreeSet::new()).is_empty());
+    }
+
+    #[test]
+    fn translation_label_falls_back_to_the_file_stem() {
+        let path = Path::new("modules/KJV.SQLite3");
+        assert_eq!(translation_label(Some(" King James Version ".to_string()), path), "King James Version");
+        assert_eq!(translation_label(Some("  ".to_string()), path), "KJV");
+        assert_eq!(translation_label(None, path), "KJV");
+        assert_eq!(translation_label(None, Path::new("modules/KJV.SQLite3.zst")), "KJV");
+        assert_eq!(translation_label(None, Path::new("")), "Unknown Bible");
+    }
+
+    #[test]
+    fn only_an_unpinned_comparison_follows_lookups() {
+        assert_eq!(followed_compare_reference(false, Some("Ps 23"), "Ps 24"), Some("Ps 24".to_string()));
+        assert_eq!(followed_compare_reference(true, Some("Ps 23"), "Ps 24"), None);
+        // Looking up the shown reference again, give or take whitespace, needs no new comparison.
+        assert_eq!(followed_compare_reference(false, Some("Ps 23"), " Ps 23 "), None);
+        assert_eq!(followed_compare_reference(false, None, "Ps 24"), None);
+        assert_eq!(followed_compare_reference(true, None, "Ps 24"), None);
+    }
+
+    #[test]
+    fn module_scan_stops_at_the_depth_cap_and_symlink_loops() {
+        let dir = temp_dir("module-scan");
+        let deepest = dir.join("1/2/3/4");
+        fs::create_dir_all(deepest.join("5")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        let files = ["KJV.SQLite3", "notes.txt", "1/WEB.sqlite3", "1/2/3/4/ASV.SQLite3", "1/2/3/4/5/YLT.SQLite3"];
+        for file in files.into_iter().chain([".git/OLD.SQLite3"]) {
+            fs::write(dir.join(file), b"").unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("1/loop")).unwrap();
+
+        assert_eq!(module_files(&dir, false), [dir.join("KJV.SQLite3")]);
+        assert_eq!(
+            module_files(&dir, true),
+            [dir.join("1/2/3/4/ASV.SQLite3"), dir.join("1/WEB.sqlite3"), dir.join("KJV.SQLite3")]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dedup_modules_merges_by_mode() {
+        let found = || {
+            vec![
+                (PathBuf::from("a/KJV.SQLite3"), "King James Version".to_string()),
+                (PathBuf::from("b/KJV.SQLite3"), "King James Version".to_string()),
+                (PathBuf::from("c/KJV-edited.SQLite3"), "King James Version".to_string()),
+                (PathBuf::from("WEB.SQLite3"), "World English Bible".to_string()),
+            ]
+        };
+        // a/ and b/ hold the same file; c/ was edited; WEB can't be read.
+        let hash_of = |path: &Path| match path.to_str() {
+            Some("a/KJV.SQLite3") | Some("b/KJV.SQLite3") => Some(1),
+            Some("c/KJV-edited.SQLite3") => Some(2),
+            _ => None,
+        };
+        let summary = |modules: Vec<DiscoveredModule>| -> Vec<(String, usize)> {
+            modules.iter().map(|m| (m.path.display().to_string(), m.duplicates.len())).collect()
+        };
+
+        let off = dedup_modules(found(), DedupMode::Off, hash_of);
+        assert_eq!(off.len(), 4);
+        assert!(off.iter().all(|m| m.duplicates.is_empty()));
+
+        let by_description = dedup_modules(found(), DedupMode::Description, hash_of);
+        assert_eq!(summary(by_description.clone()), [("a/KJV.SQLite3".to_string(), 2), ("WEB.SQLite3".to_string(), 0)]);
+        assert_eq!(by_description[0].label(), "King James Version (duplicate: KJV.SQLite3, KJV-edited.SQLite3)");
+
+        let by_hash = dedup_modules(found(), DedupMode::DescriptionAndHash, hash_of);
+        assert_eq!(
+            summary(by_hash),
+            [
+                ("a/KJV.SQLite3".to_string(), 1),
+                ("c/KJV-edited.SQLite3".to_string(), 0),
+                ("WEB.SQLite3".to_string(), 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::compare::file_hash;
+
+// -------------------------------
+// Compressed (.zst) Modules
+// -------------------------------
+
+/// Whether the module file is zstd-compressed (`.SQLite3.zst`).
+pub fn is_zstd(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("zst"))
+        .unwrap_or(false)
+}
+
+/// Where decompressed modules are kept: `$XDG_CACHE_HOME/biblical_gui` (falling back to `~/.cache`).
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("biblical_gui"))
+}
+
+/// The database file to open for a module: the file itself, or for a `.zst` module a
+/// decompressed copy in the cache directory. Copies are named after the compressed
+/// file's content hash, so each version is decompressed once, on first open, and then reused.
+pub fn module_db_path(path: &Path) -> io::Result<PathBuf> {
+    if !is_zstd(path) {
+        return Ok(path.to_path_buf());
+    }
+    let dir = cache_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+    decompressed_copy(path, &dir)
+}
+
+/// The decompressed copy of a `.zst` module if an earlier open already made one, so a
+/// directory scan can read its description without decompressing anything itself.
+pub fn cached_db_path(path: &Path) -> Option<PathBuf> {
+    let cached = cached_name(path, &cache_dir()?)?;
+    cached.exists().then_some(cached)
+}
+
+/// "KJV.SQLite3.zst" → "KJV.SQLite3" → `dir`/"KJV-<hash>.SQLite3"
+fn cached_name(path: &Path, dir: &Path) -> Option<PathBuf> {
+    let hash = file_hash(path)?;
+    let inner = Path::new(path.file_stem().unwrap_or_default());
+    let stem = inner.file_stem().unwrap_or_default().to_string_lossy();
+    Some(dir.join(format!("{}-{:016x}.SQLite3", stem, hash)))
+}
+
+/// Decompress `path` into `dir` unless a copy of this version is already there.
+fn decompressed_copy(path: &Path, dir: &Path) -> io::Result<PathBuf> {
+    let cached = cached_name(path, dir)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cannot read compressed module"))?;
+    if cached.exists() {
+        return Ok(cached);
+    }
+    fs::create_dir_all(dir)?;
+    // Decompress beside the final name and rename, so an interrupted run never leaves
+    // a truncated database that looks cached.
+    let partial = cached.with_extension("partial");
+    if let Err(e) = decompress(path, &partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &cached)?;
+    Ok(cached)
+}
+
+#[cfg(feature = "zstd")]
+fn decompress(path: &Path, to: &Path) -> io::Result<()> {
+    let input = fs::File::open(path)?;
+    let mut output = fs::File::create(to)?;
+    zstd::stream::copy_decode(input, &mut output)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress(path: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} is compressed, but this build has no zstd support", path.display()),
+    ))
+}
+
+#[cfg(all(test, feature = "zstd"))]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{temp_dir, write_fixture_module};
+    use rusqlite::Connection;
+
+    #[test]
+    fn compressed_module_is_decompressed_once() {
+        let dir = temp_dir("zstd");
+        let plain = dir.join("KJV.SQLite3");
+        write_fixture_module(&plain, "King James Version");
+        let compressed = dir.join("KJV.SQLite3.zst");
+        zstd::stream::copy_encode(fs::File::open(&plain).unwrap(), fs::File::create(&compressed).unwrap(), 3)
+            .unwrap();
+
+        let cache = dir.join("cache");
+        assert!(!cache.exists());
+        let copy = decompressed_copy(&compressed, &cache).unwrap();
+        assert_eq!(copy, cached_name(&compressed, &cache).unwrap());
+        assert_eq!(fs::read(&copy).unwrap(), fs::read(&plain).unwrap());
+        let conn = Connection::open(&copy).unwrap();
+        let verses: u32 = conn.query_row("SELECT COUNT(*) FROM verses", [], |row| row.get(0)).unwrap();
+        assert_eq!(verses, 21);
+
+        // The copy is reused rather than decompressed again.
+        let modified = fs::metadata(&copy).unwrap().modified().unwrap();
+        assert_eq!(decompressed_copy(&compressed, &cache).unwrap(), copy);
+        assert_eq!(fs::metadata(&copy).unwrap().modified().unwrap(), modified);
+        assert!(!copy.with_extension("partial").exists());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::markup::clean_text;
+use crate::Verse;
+
+// -------------------------------
+// Cross-Verse Phrase Matching
+// -------------------------------
+
+/// Verse text reduced for phrase matching: markup removed as on screen (see
+/// `markup::clean_text`), whitespace collapsed to single spaces, and lowercased.
+fn normalize(text: &str) -> String {
+    clean_text(text).split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Indices of the verses where `phrase` starts and runs on into the next verse of the
+/// same chapter. `verses` must be in canonical order. Matches wholly inside one verse
+/// are left to the per-verse search, and a phrase spanning three or more verses is
+/// not looked for.
+pub fn cross_verse_matches(verses: &[Verse], phrase: &str) -> Vec<usize> {
+    let phrase = normalize(phrase);
+    if phrase.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    let mut current = verses.first().map(|v| normalize(&v.text));
+    for (idx, pair) in verses.windows(2).enumerate() {
+        let next = normalize(&pair[1].text);
+        let same_chapter = pair[0].book_number == pair[1].book_number && pair[0].chapter == pair[1].chapter;
+        if let Some(first) = current.as_deref().filter(|_| same_chapter) {
+            let joined = format!("{} {}", first, next);
+            // A boundary match starts inside the first verse and ends past the joining space.
+            let crosses = joined
+                .match_indices(&phrase)
+                .any(|(start, _)| start < first.len() && start + phrase.len() > first.len() + 1);
+            if crosses {
+                matches.push(idx);
+            }
+        }
+        current = Some(next);
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::verse;
+
+    #[test]
+    fn phrases_cross_strongs_numbers_and_footnotes() {
+        let verses = [
+            verse("Gen", 1, 1, "In the beginning<S>7225</S> God created the heaven<f>[1]</f>"),
+            verse("Gen", 1, 2, "<S>8064</S>and the earth was without form"),
+            verse("Gen", 1, 3, "And God said"),
+        ];
+        assert_eq!(cross_verse_matches(&verses, "the heaven and the earth"), [0]);
+        assert_eq!(cross_verse_matches(&verses, "without form and God"), [1]);
+        assert!(cross_verse_matches(&verses, "beginning God").is_empty());
+        assert!(cross_verse_matches(&verses, "7225").is_empty());
+    }
+}
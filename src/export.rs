@@ -0,0 +1,112 @@
+use std::fmt;
+
+use crate::Verse;
+
+// -------------------------------
+// Result Export
+// -------------------------------
+
+/// How verses are written out when copied or exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// "Genesis 1:1 In the beginning…", one verse per line.
+    Plain,
+    /// A Markdown list with bold references, like the bookmark export.
+    Markdown,
+    /// Only the references, one per line.
+    References,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Plain, ExportFormat::Markdown, ExportFormat::References];
+
+    /// File written by "Export" when no path is given.
+    pub fn default_file(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "results.md",
+            ExportFormat::Plain | ExportFormat::References => "results.txt",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ExportFormat::Plain => "Plain text",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::References => "References only",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Serialize every verse in the given format, one line per verse, with references
+/// using the book's abbreviation when `abbreviate` is set. Markup is removed from the
+/// text unless `raw_markup` asks to keep it, as on screen.
+pub fn format_verses(verses: &[Verse], format: ExportFormat, abbreviate: bool, raw_markup: bool) -> String {
+    let mut out = String::new();
+    for verse in verses {
+        let reference = verse.reference(abbreviate);
+        let text = crate::display_text(&verse.text, raw_markup);
+        let line = match format {
+            ExportFormat::Plain => format!("{} {}", reference, text),
+            ExportFormat::Markdown => format!("- **{}** {}", reference, text),
+            ExportFormat::References => reference,
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// A results file: the translation `bible` as a header line, then one line per verse as
+/// `format_verses` writes them, except that plain text separates the reference from the
+/// verse with two spaces so the text lines up better.
+pub fn export_document(
+    verses: &[Verse],
+    format: ExportFormat,
+    abbreviate: bool,
+    raw_markup: bool,
+    bible: &str,
+) -> String {
+    let mut out = match format {
+        ExportFormat::Markdown => format!("# {}\n\n", bible),
+        ExportFormat::Plain | ExportFormat::References => format!("{}\n\n", bible),
+    };
+    match format {
+        ExportFormat::Plain => {
+            for verse in verses {
+                let text = crate::display_text(&verse.text, raw_markup);
+                out.push_str(&format!("{}  {}\n", verse.reference(abbreviate), text));
+            }
+        }
+        ExportFormat::Markdown | ExportFormat::References => {
+            out.push_str(&format_verses(verses, format, abbreviate, raw_markup))
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::verse;
+
+    #[test]
+    fn copies_and_exports_leave_markup_out() {
+        let verses = [verse("John", 3, 16, "<J>For God so loved the world</J><f>[1]</f>")];
+        assert_eq!(format_verses(&verses, ExportFormat::Plain, true, false), "John 3:16 For God so loved the world\n");
+        assert_eq!(
+            format_verses(&verses, ExportFormat::Markdown, true, false),
+            "- **John 3:16** For God so loved the world\n"
+        );
+        assert_eq!(
+            export_document(&verses, ExportFormat::Plain, true, false, "KJV"),
+            "KJV\n\nJohn 3:16  For God so loved the world\n"
+        );
+        assert_eq!(
+            export_document(&verses, ExportFormat::Markdown, true, true, "KJV"),
+            "# KJV\n\n- **John 3:16** <J>For God so loved the world</J><f>[1]</f>\n"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::settings::{config_dir, SearchMode};
+
+// -------------------------------
+// Search and Lookup History
+// -------------------------------
+
+/// Most queries remembered; the oldest are dropped beyond this.
+const MAX_HISTORY: usize = 50;
+
+/// Where the history is kept between runs: a line-based text file like the settings
+/// rather than JSON, which would need a serialization dependency for one list.
+pub fn history_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("history.txt"))
+}
+
+/// A past search or lookup, enough to run it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryEntry {
+    /// A search in a text-based mode; the phrases of a line-per-phrase search are joined
+    /// with newlines.
+    Search { mode: SearchMode, query: String },
+    Lookup(String),
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryEntry::Search { query, .. } => write!(f, "Search: {}", query.replace('\n', " / ")),
+            HistoryEntry::Lookup(reference) => write!(f, "Lookup: {}", reference),
+        }
+    }
+}
+
+/// Recent searches and lookups, most recent first, without duplicates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Put `entry` first, removing an earlier copy of it. Blank queries are not recorded.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if query_is_blank(&entry) {
+            return;
+        }
+        self.entries.retain(|e| *e != entry);
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_HISTORY);
+    }
+
+    /// Parse `lookup<TAB>reference` and `search:<mode><TAB>query` lines, most recent first.
+    /// Malformed lines are skipped so a damaged file never prevents startup.
+    pub fn parse(contents: &str) -> History {
+        let mut history = History::default();
+        for line in contents.lines() {
+            let Some((kind, query)) = line.split_once('\t') else {
+                continue;
+            };
+            let query = unescape(query);
+            let entry = match kind.split_once(':') {
+                None if kind == "lookup" => HistoryEntry::Lookup(query),
+                Some(("search", mode)) => match SearchMode::from_key(mode) {
+                    Some(mode) => HistoryEntry::Search { mode, query },
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if !query_is_blank(&entry) && !history.entries.contains(&entry) {
+                history.entries.push(entry);
+            }
+        }
+        history.entries.truncate(MAX_HISTORY);
+        history
+    }
+
+    /// Serialize as one tab-separated line per entry, most recent first.
+    pub fn to_file_contents(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                HistoryEntry::Search { mode, query } => format!("search:{}\t{}\n", mode.key(), escape(query)),
+                HistoryEntry::Lookup(reference) => format!("lookup\t{}\n", escape(reference)),
+            })
+            .collect()
+    }
+}
+
+fn query_is_blank(entry: &HistoryEntry) -> bool {
+    match entry {
+        HistoryEntry::Search { query, .. } => query.trim().is_empty(),
+        HistoryEntry::Lookup(reference) => reference.trim().is_empty(),
+    }
+}
+
+/// Keep a query on one line: backslashes and newlines are written as `\\` and `\n`.
+fn escape(query: &str) -> String {
+    query.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(query: &str) -> String {
+    let mut out = String::with_capacity(query.len());
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Read the history; a missing or unreadable file yields an empty one.
+pub fn load_history(path: &Path) -> History {
+    fs::read_to_string(path)
+        .map(|contents| History::parse(&contents))
+        .unwrap_or_default()
+}
+
+/// Write the history, creating the config directory if needed.
+pub fn save_history(path: &Path, history: &History) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, history.to_file_contents())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_round_trips_through_its_file_format() {
+        let mut history = History::default();
+        history.record(HistoryEntry::Lookup("Ps 23".to_string()));
+        history.record(HistoryEntry::Search { mode: SearchMode::Lines, query: "living water\nwell".to_string() });
+        history.record(HistoryEntry::Search { mode: SearchMode::Regex, query: r"\bLord\b\tGod".to_string() });
+        history.record(HistoryEntry::Lookup("Ps 23".to_string()));
+        assert_eq!(history.entries()[0], HistoryEntry::Lookup("Ps 23".to_string()));
+        assert_eq!(history.entries().len(), 3);
+        assert_eq!(History::parse(&history.to_file_contents()), history);
+    }
+
+    #[test]
+    fn damaged_lines_are_skipped() {
+        let history = History::parse("lookup\tJohn 3:16\ngarbage\nsearch:unknown\tfaith\nsearch:plain\t \nsearch:plain\tfaith\n");
+        assert_eq!(
+            history.entries(),
+            [
+                HistoryEntry::Lookup("John 3:16".to_string()),
+                HistoryEntry::Search { mode: SearchMode::Plain, query: "faith".to_string() },
+            ]
+        );
+        assert_eq!(History::parse("\u{0}\u{1}not a history file"), History::default());
+    }
+}
@@ -0,0 +1,82 @@
+use crate::bookmarks::Bookmark;
+use crate::Verse;
+
+// -------------------------------
+// Verse Links (biblical_gui:// URIs)
+// -------------------------------
+
+/// Scheme and path prefix of a verse link, e.g. `biblical_gui://ref/Gen.1.1`.
+pub const VERSE_URI_PREFIX: &str = "biblical_gui://ref/";
+
+/// The link that reopens a verse: `biblical_gui://ref/<book>.<chapter>.<verse>`, with the
+/// module's book short name. Spaces and percent signs in the name are percent-encoded.
+pub fn verse_uri(bookmark: &Bookmark) -> String {
+    let book = bookmark.book.replace('%', "%25").replace(' ', "%20");
+    format!("{}{}.{}.{}", VERSE_URI_PREFIX, book, bookmark.chapter, bookmark.verse)
+}
+
+/// Parse a link produced by `verse_uri` back into the verse it names.
+pub fn parse_verse_uri(uri: &str) -> Option<Bookmark> {
+    let rest = uri.trim().strip_prefix(VERSE_URI_PREFIX)?;
+    let (rest, verse) = rest.rsplit_once('.')?;
+    let (book, chapter) = rest.rsplit_once('.')?;
+    let book = book.replace("%20", " ").replace("%25", "%");
+    if book.is_empty() {
+        return None;
+    }
+    Some(Bookmark {
+        book,
+        chapter: chapter.parse().ok()?,
+        verse: verse.parse().ok()?,
+    })
+}
+
+/// A verse as copied by "Copy as link": its reference and text (without markup unless
+/// `raw_markup` is set), then the link that reopens it.
+pub fn format_verse_link(verse: &Verse, abbreviate: bool, raw_markup: bool) -> String {
+    let bookmark = Bookmark {
+        book: verse.short_name.clone(),
+        chapter: verse.chapter,
+        verse: verse.verse,
+    };
+    let text = crate::display_text(&verse.text, raw_markup);
+    format!("{} {}\n{}\n", verse.reference(abbreviate), text, verse_uri(&bookmark))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::verse;
+
+    fn bookmark(book: &str, chapter: u32, verse: u32) -> Bookmark {
+        Bookmark { book: book.to_string(), chapter, verse }
+    }
+
+    #[test]
+    fn verse_links_round_trip() {
+        for original in [bookmark("Gen", 1, 1), bookmark("1 Kgs", 8, 27), bookmark("A%20B.C", 150, 6)] {
+            assert_eq!(parse_verse_uri(&verse_uri(&original)), Some(original));
+        }
+        assert_eq!(verse_uri(&bookmark("Song of Songs", 2, 1)), "biblical_gui://ref/Song%20of%20Songs.2.1");
+        assert_eq!(parse_verse_uri("biblical_gui://ref/.1.1"), None);
+        assert_eq!(parse_verse_uri("biblical_gui://ref/Gen.1"), None);
+        assert_eq!(parse_verse_uri("https://example.com/Gen.1.1"), None);
+    }
+
+    #[test]
+    fn copied_link_reopens_the_verse() {
+        let copied = format_verse_link(&verse("John", 11, 35, "Jesus wept."), true, false);
+        assert_eq!(copied, "John 11:35 Jesus wept.\nbiblical_gui://ref/John.11.35\n");
+        let link = copied.lines().last().unwrap();
+        assert_eq!(parse_verse_uri(link), Some(bookmark("John", 11, 35)));
+    }
+
+    #[test]
+    fn copied_link_leaves_markup_out() {
+        let marked = verse("John", 3, 16, "<J>For God so loved the world</J><f>[1]</f>");
+        assert_eq!(
+            format_verse_link(&marked, true, false),
+            "John 3:16 For God so loved the world\nbiblical_gui://ref/John.3.16\n"
+        );
+    }
+}
@@ -1,42 +1,225 @@
 use iced::{
-    Alignment, Color, Element, Length, Sandbox, Settings,
-};
-use iced::widget::{
-    button, column, scrollable, text, text_input, Button, Column, Row, Scrollable, Text,
+    event, keyboard, executor, subscription, window, Alignment, Application, Color, Command, Element, Event, Length,
+    Settings, Subscription, Theme,
 };
+use iced::futures::channel::{mpsc, oneshot};
+use iced::futures::{future, StreamExt};
+use iced::widget::{button, checkbox, pick_list, text, text_input, tooltip, Column, Container, Row, Scrollable, Space};
 
-use rusqlite::{Connection, Error as RusqliteError};
+use rusqlite::{Connection, Error as RusqliteError, OpenFlags};
 use rusqlite::params;
 use rusqlite::params_from_iter;
 use regex::Regex;
-use std::error::Error as StdError;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod bookmarks;
+mod compare;
+mod compressed;
+mod cross_verse;
+mod export;
+mod history;
+mod link;
+mod markup;
+mod navigator;
+mod passage;
+mod review;
+mod schema;
+mod segment_cache;
+mod settings;
+mod term_frequency;
+#[cfg(test)]
+mod test_fixture;
+mod verse_numbers;
+mod versification;
+
+use bookmarks::{bookmarks_path, format_bookmark_export, load_bookmarks, parse_reference_list, save_bookmarks, Bookmark};
+use compressed::{cached_db_path, is_zstd, module_db_path};
+use cross_verse::cross_verse_matches;
+use export::{export_document, format_verses, ExportFormat};
+use history::{history_path, load_history, save_history, History, HistoryEntry};
+use link::{format_verse_link, parse_verse_uri};
+use markup::{clean_text, detect_red_letter, detect_strongs, segment_verse, red_letter_words, term_in_red_letter, RedLetterMarkup, VerseSegment};
+use navigator::{build_navigator, NavBook};
+use passage::{pick_passage, seeded_index, BookExtent, PassageWeighting, PASSAGE_LENGTH};
+use review::{diff_runs, pair_diff, review_sheet, DiffOp, ReviewFormat, VerseDiff};
+use segment_cache::SegmentCache;
+use settings::{
+    can_remember_dir, expand_home, load_settings, save_settings, settings_path, AppSettings, ContentWidth, DefaultSearchMode,
+    NotSemantics, SearchMode, CONTEXT_VERSE_CHOICES, COPY_LIMIT_CHOICES,
+};
+use term_frequency::{load_term_counts, save_term_counts, term_counts_path, TermFrequency};
+use versification::{to_canonical, Versification, VERSIFICATIONS};
+use verse_numbers::{detect_leading_numbers, strip_leading_number, LeadingNumbers};
+use schema::{validate_schema, BibleSchema, SchemaError};
+use compare::{
+    baseline_flags, cell_pieces, CellLayout, dedup_modules, followed_compare_reference, identical_verses, file_hash, module_files, pinned_rows, text_totals, translation_label,
+    DedupMode, DiscoveredModule, VerseKey, VerseRef,
+};
 
 /// -------------------------------
 /// Custom Text Styles
 /// -------------------------------
 
+/// Body text in the active theme's text color (dark on light, light on dark).
 #[derive(Debug, Clone, Copy)]
 struct NormalText;
 
 impl iced::widget::text::StyleSheet for NormalText {
     type Style = iced::Theme;
-    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+    fn appearance(&self, style: Self::Style) -> iced::widget::text::Appearance {
         iced::widget::text::Appearance {
-            color: Some(Color::BLACK),
+            color: Some(style.palette().text),
             ..Default::default()
         }
     }
 }
 
+/// Search matches and errors in red; a lighter red on the dark theme so it stays legible.
 #[derive(Debug, Clone, Copy)]
-struct HighlightText;
+struct HighlightText {
+    dark: bool,
+}
+
+impl HighlightText {
+    fn color(&self) -> Color {
+        if self.dark {
+            Color::from_rgb(1.0, 0.45, 0.4)
+        } else {
+            Color::from_rgb(1.0, 0.0, 0.0)
+        }
+    }
+}
 
 impl iced::widget::text::StyleSheet for HighlightText {
     type Style = iced::Theme;
     fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
         iced::widget::text::Appearance {
-            color: Some(Color::from_rgb(1.0, 0.0, 0.0)), // red highlight
+            color: Some(self.color()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PreviewText;
+
+impl iced::widget::text::StyleSheet for PreviewText {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+        iced::widget::text::Appearance {
+            color: Some(Color::from_rgb(0.6, 0.6, 0.6)), // faint preview
+            ..Default::default()
+        }
+    }
+}
+
+/// Words of Christ in crimson; a lighter crimson on the dark theme.
+#[derive(Debug, Clone, Copy)]
+struct RedLetterText {
+    dark: bool,
+}
+
+impl RedLetterText {
+    fn color(&self) -> Color {
+        if self.dark {
+            Color::from_rgb(0.95, 0.45, 0.5)
+        } else {
+            Color::from_rgb(0.7, 0.0, 0.1)
+        }
+    }
+}
+
+impl iced::widget::text::StyleSheet for RedLetterText {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+        iced::widget::text::Appearance {
+            color: Some(self.color()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Words only the second translation of a word diff has.
+#[derive(Debug, Clone, Copy)]
+struct AddedText {
+    dark: bool,
+}
+
+impl AddedText {
+    fn color(&self) -> Color {
+        if self.dark {
+            Color::from_rgb(0.45, 0.85, 0.5)
+        } else {
+            Color::from_rgb(0.1, 0.6, 0.2)
+        }
+    }
+}
+
+impl iced::widget::text::StyleSheet for AddedText {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+        iced::widget::text::Appearance {
+            color: Some(self.color()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Words only the first translation of a word diff has.
+#[derive(Debug, Clone, Copy)]
+struct RemovedText {
+    dark: bool,
+}
+
+impl RemovedText {
+    fn color(&self) -> Color {
+        if self.dark {
+            Color::from_rgb(1.0, 0.65, 0.3)
+        } else {
+            Color::from_rgb(0.85, 0.35, 0.0)
+        }
+    }
+}
+
+impl iced::widget::text::StyleSheet for RemovedText {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+        iced::widget::text::Appearance {
+            color: Some(self.color()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Translator-supplied (italic) words, muted against the theme's text color.
+#[derive(Debug, Clone, Copy)]
+struct SuppliedText {
+    dark: bool,
+}
+
+impl SuppliedText {
+    fn color(&self) -> Color {
+        if self.dark {
+            Color::from_rgb(0.65, 0.65, 0.65)
+        } else {
+            Color::from_rgb(0.35, 0.35, 0.35)
+        }
+    }
+}
+
+impl iced::widget::text::StyleSheet for SuppliedText {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: Self::Style) -> iced::widget::text::Appearance {
+        iced::widget::text::Appearance {
+            color: Some(self.color()),
             ..Default::default()
         }
     }
@@ -44,129 +227,1242 @@ impl iced::widget::text::StyleSheet for HighlightText {
 
 impl From<NormalText> for iced::theme::Text {
     fn from(_: NormalText) -> Self {
-        iced::theme::Text::Color(Color::BLACK)
+        // The default text style already follows the theme's palette.
+        iced::theme::Text::Default
     }
 }
 
 impl From<HighlightText> for iced::theme::Text {
-    fn from(_: HighlightText) -> Self {
-        iced::theme::Text::Color(Color::from_rgb(1.0, 0.0, 0.0))
+    fn from(style: HighlightText) -> Self {
+        iced::theme::Text::Color(style.color())
+    }
+}
+
+impl From<RedLetterText> for iced::theme::Text {
+    fn from(style: RedLetterText) -> Self {
+        iced::theme::Text::Color(style.color())
+    }
+}
+
+impl From<AddedText> for iced::theme::Text {
+    fn from(style: AddedText) -> Self {
+        iced::theme::Text::Color(style.color())
+    }
+}
+
+impl From<RemovedText> for iced::theme::Text {
+    fn from(style: RemovedText) -> Self {
+        iced::theme::Text::Color(style.color())
+    }
+}
+
+impl From<SuppliedText> for iced::theme::Text {
+    fn from(style: SuppliedText) -> Self {
+        iced::theme::Text::Color(style.color())
+    }
+}
+
+impl From<PreviewText> for iced::theme::Text {
+    fn from(_: PreviewText) -> Self {
+        iced::theme::Text::Color(Color::from_rgb(0.6, 0.6, 0.6))
+    }
+}
+
+/// -------------------------------
+/// Custom Container Styles
+/// -------------------------------
+
+/// Faint background behind every other result item (zebra striping).
+#[derive(Debug, Clone, Copy)]
+struct StripedRow;
+
+impl iced::widget::container::StyleSheet for StripedRow {
+    type Style = iced::Theme;
+    fn appearance(&self, style: &Self::Style) -> iced::widget::container::Appearance {
+        let shade = match style {
+            iced::Theme::Dark => Color::from_rgb(0.2, 0.21, 0.24),
+            _ => Color::from_rgb(0.94, 0.94, 0.96),
+        };
+        iced::widget::container::Appearance {
+            background: Some(shade.into()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<StripedRow> for iced::theme::Container {
+    fn from(style: StripedRow) -> Self {
+        iced::theme::Container::Custom(Box::new(style))
+    }
+}
+
+/// One book's cell in the search heatmap, filled with its match-count color.
+#[derive(Debug, Clone, Copy)]
+struct HeatCell(Color);
+
+impl iced::widget::container::StyleSheet for HeatCell {
+    type Style = iced::Theme;
+    fn appearance(&self, _style: &Self::Style) -> iced::widget::container::Appearance {
+        iced::widget::container::Appearance {
+            background: Some(self.0.into()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<HeatCell> for iced::theme::Container {
+    fn from(style: HeatCell) -> Self {
+        iced::theme::Container::Custom(Box::new(style))
+    }
+}
+
+/// Heatmap color for a book with `count` matches when the busiest book has `max`:
+/// light gray for none, then from pale yellow (few) to deep red (the most).
+fn heat_color(count: usize, max: usize) -> Color {
+    if count == 0 || max == 0 {
+        return Color::from_rgb(0.9, 0.9, 0.9);
     }
+    let t = (count.min(max) as f32) / (max as f32);
+    let (from, to) = ((1.0, 0.95, 0.6), (0.7, 0.0, 0.0));
+    Color::from_rgb(
+        from.0 + (to.0 - from.0) * t,
+        from.1 + (to.1 - from.1) * t,
+        from.2 + (to.2 - from.2) * t,
+    )
 }
 
 /// -------------------------------
 /// Data Structures and Database Setup
 /// -------------------------------
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Verse {
+    book_number: u32,
     long_name: String,
+    /// The book's abbreviation, e.g. "Gen".
+    short_name: String,
     chapter: u32,
     verse: u32,
+    /// The last verse of a row the module stores combined (e.g. 4 for "3-4").
+    verse_end: Option<u32>,
     text: String,
 }
 
+impl Verse {
+    /// Read a row selected with `BibleSchema::verse_columns`.
+    fn from_row(row: &rusqlite::Row) -> Result<Verse, RusqliteError> {
+        let verse: u32 = row.get(2)?;
+        let verse_end: u32 = row.get(6)?;
+        Ok(Verse {
+            book_number: row.get(4)?,
+            long_name: row.get(0)?,
+            short_name: row.get(5)?,
+            chapter: row.get(1)?,
+            verse,
+            verse_end: (verse_end > verse).then_some(verse_end),
+            text: row.get(3)?,
+        })
+    }
+
+    /// The verse number, or the span of a combined row ("3-4").
+    fn verse_label(&self) -> String {
+        match self.verse_end {
+            Some(end) => format!("{}-{}", self.verse, end),
+            None => self.verse.to_string(),
+        }
+    }
+
+    /// The verse's reference, with the book's full name or its abbreviation.
+    fn reference(&self, abbreviate: bool) -> String {
+        let book = if abbreviate { &self.short_name } else { &self.long_name };
+        format!("{} {}:{}", book, self.chapter, self.verse_label())
+    }
+}
+
+/// Each translation's label and verses for one compared reference.
+type CompareResults = Vec<(String, Vec<Verse>)>;
+
+/// A row of the module's `books` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Book {
+    book_number: u32,
+    short_name: String,
+    long_name: String,
+}
+
+impl fmt::Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.long_name)
+    }
+}
+
+/// The book `name` refers to, matching the short or long name case-insensitively and
+/// ignoring a trailing period, so "Genesis", "gen", "Gen." and "GEN" all find Genesis.
+fn find_book<'b>(books: &'b [Book], name: &str) -> Option<&'b Book> {
+    let name = name.trim().trim_end_matches('.').trim_end();
+    books
+        .iter()
+        .find(|b| b.short_name.eq_ignore_ascii_case(name) || b.long_name.eq_ignore_ascii_case(name))
+}
+
+/// Load the module's books in canonical order.
+fn load_books(bible: &Bible) -> Result<Vec<Book>, RusqliteError> {
+    let mut stmt = bible.conn.prepare(&format!(
+        "SELECT short_name, long_name, CAST({book} AS INTEGER) FROM {} ORDER BY {book}",
+        bible.schema.books_table,
+        book = bible.schema.books_book_column
+    ))?;
+    let books = stmt
+        .query_map([], |row| {
+            Ok(Book {
+                book_number: row.get(2)?,
+                short_name: row.get(0)?,
+                long_name: row.get(1)?,
+            })
+        })?
+        .filter_map(|b| b.ok())
+        .collect();
+    Ok(books)
+}
+
+/// Chapter extents of every book that has verses, for picking random passages.
+fn load_book_extents(bible: &Bible) -> Result<Vec<BookExtent>, RusqliteError> {
+    let book = bible.schema.verse_book();
+    let mut stmt = bible.conn.prepare(&format!(
+        "SELECT CAST({book} AS INTEGER), b.short_name, v.chapter, MAX(v.verse) \
+         FROM verses v {join} \
+         GROUP BY {book}, v.chapter \
+         ORDER BY {book}, v.chapter",
+        book = book,
+        join = bible.schema.books_join()
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, u32>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, u32>(2)?,
+            row.get::<_, u32>(3)?,
+        ))
+    })?;
+    let mut extents: Vec<BookExtent> = Vec::new();
+    for (book_number, short_name, chapter, last_verse) in rows.filter_map(|r| r.ok()) {
+        match extents.last_mut() {
+            Some(book) if book.book_number == book_number => book.chapters.push((chapter, last_verse)),
+            _ => extents.push(BookExtent {
+                book_number,
+                short_name,
+                chapters: vec![(chapter, last_verse)],
+            }),
+        }
+    }
+    Ok(extents)
+}
+
+/// The chapter after (`forward`) or before `chapter` of `book`, moving into the next or
+/// previous book at a book boundary. None past the last chapter of the last book or
+/// before the first chapter of the first.
+fn adjacent_chapter(extents: &[BookExtent], book: &str, chapter: u32, forward: bool) -> Option<(String, u32)> {
+    let chapters: Vec<(&str, u32)> = extents
+        .iter()
+        .flat_map(|extent| extent.chapters.iter().map(move |&(ch, _)| (extent.short_name.as_str(), ch)))
+        .collect();
+    let idx = chapters.iter().position(|&(b, ch)| b == book && ch == chapter)?;
+    let next = if forward { idx + 1 } else { idx.checked_sub(1)? };
+    chapters.get(next).map(|&(b, ch)| (b.to_string(), ch))
+}
+
+/// First verse of a chapter with its number, used for the lookup autocomplete preview.
+fn first_verse_text(bible: &Bible, book: &str, chapter: u32) -> Result<Option<(u32, String)>, RusqliteError> {
+    let mut stmt = bible.conn.prepare(&format!(
+        "SELECT CAST(v.verse AS INTEGER), v.text FROM verses v {} \
+         WHERE b.short_name = ? AND v.chapter = ? \
+         ORDER BY v.verse LIMIT 1",
+        bible.schema.books_join()
+    ))?;
+    let mut rows = stmt.query(params![book, chapter])?;
+    match rows.next()? {
+        Some(row) => Ok(Some((row.get(0)?, row.get(1)?))),
+        None => Ok(None),
+    }
+}
+
+/// Text of a single verse, or None when the module doesn't have it.
+fn verse_text(bible: &Bible, book: &str, chapter: u32, verse: u32) -> Result<Option<String>, RusqliteError> {
+    let mut stmt = bible.conn.prepare(&format!(
+        "SELECT v.text FROM verses v {} \
+         WHERE b.short_name = ? AND v.chapter = ? AND v.verse = ?",
+        bible.schema.books_join()
+    ))?;
+    let mut rows = stmt.query(params![book, chapter, verse])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+/// The module's description from its info table.
+fn bible_description(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT value FROM info WHERE name = 'description'",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// The main Bible opened from the module directory when present.
+const MAIN_BIBLE_FILE: &str = "KJ1769.SQLite3";
+
+/// Open the main Bible in `dir`: `MAIN_BIBLE_FILE` if it opens, otherwise the first
+/// module in the directory that does. The error names the default file's problem.
+fn open_main_bible(dir: &Path) -> Result<Bible, String> {
+    let default = dir.join(MAIN_BIBLE_FILE);
+    match open_bible(&default) {
+        Ok(bible) => Ok(bible),
+        Err(e) => {
+            println!("Failed to open {:?}: {}", default, e);
+            module_files(dir, false)
+                .iter()
+                .find_map(|path| open_bible(path).ok())
+                .ok_or_else(|| format!("Could not open {}: {}", default.display(), e))
+        }
+    }
+}
+
+/// File the "Export bookmarks" action writes to.
+const BOOKMARK_EXPORT_FILE: &str = "bookmarks.md";
+
+/// Status shown when a query is attempted without a main Bible connection.
+const NO_BIBLE_LOADED: &str = "No Bible loaded — open a module to search";
+
+/// An opened Bible module: its connection plus the table layout discovered for it.
+struct Bible {
+    conn: Connection,
+    schema: BibleSchema,
+    path: PathBuf,
+}
+
+/// Open a Bible module read-only, validate its schema and register the app's custom SQL functions on it.
+/// Unlike `Connection::open`, a missing file is an error rather than a new empty database.
+/// A `.zst` module is opened from its decompressed copy in the cache directory.
+fn open_bible(path: &Path) -> Result<Bible, SchemaError> {
+    let db_path = module_db_path(path).map_err(SchemaError::Decompress)?;
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let schema = validate_schema(&conn)?;
+    register_regex_function(&conn)?;
+    register_word_count_function(&conn)?;
+    register_plain_text_function(&conn)?;
+    Ok(Bible {
+        conn,
+        schema,
+        path: path.to_path_buf(),
+    })
+}
+
 /// (Optional) Register a custom SQL function "regexp" with SQLite.
+/// The pattern is compiled once per statement and kept as SQLite auxiliary data, rather
+/// than for every row the query scans.
 fn register_regex_function(conn: &Connection) -> Result<(), RusqliteError> {
     use rusqlite::functions::FunctionFlags;
+    type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
     conn.create_scalar_function(
         "regexp",
         2,
         FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
         |ctx| {
-            let pattern: String = ctx.get(0)?;
+            let re: Arc<Regex> =
+                ctx.get_or_create_aux(0, |pattern| -> Result<_, BoxError> { Ok(Regex::new(pattern.as_str()?)?) })?;
             let text: String = ctx.get(1)?;
-            let re = match Regex::new(&pattern) {
-                Ok(r) => r,
-                Err(e) => return Err(rusqlite::Error::UserFunctionError(Box::new(e))),
-            };
             Ok(re.is_match(&text) as i32)
         },
     )
 }
 
+/// Number of whitespace-separated words in a verse, ignoring markup tags.
+/// Tags are replaced by a space so a tag between two words never glues them together.
+fn word_count(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_tag = false;
+    let mut in_word = false;
+    for c in text.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                in_word = false;
+            }
+            '>' if in_tag => in_tag = false,
+            _ if in_tag => {}
+            c if c.is_whitespace() => in_word = false,
+            _ => {
+                if !in_word {
+                    count += 1;
+                }
+                in_word = true;
+            }
+        }
+    }
+    count
+}
+
+/// Register the SQL function "plain_text" with SQLite: a verse's text with its markup
+/// removed (see `markup::clean_text`), for matching terms anchored to the start or end.
+fn register_plain_text_function(conn: &Connection) -> Result<(), RusqliteError> {
+    use rusqlite::functions::FunctionFlags;
+    conn.create_scalar_function(
+        "plain_text",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text: String = ctx.get(0)?;
+            Ok(clean_text(&text).into_owned())
+        },
+    )
+}
+
+/// Register the SQL function "word_count" (see `word_count`) with SQLite.
+fn register_word_count_function(conn: &Connection) -> Result<(), RusqliteError> {
+    use rusqlite::functions::FunctionFlags;
+    conn.create_scalar_function(
+        "word_count",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text: String = ctx.get(0)?;
+            Ok(word_count(&text) as i64)
+        },
+    )
+}
+
 /// -------------------------------
 /// Helper Functions for Advanced Search & Lookup
 /// -------------------------------
 
-/// For advanced search: Build a dynamic WHERE clause from a query (e.g. "faith AND hope").
-fn build_where_clause(query: &str) -> (String, Vec<String>) {
-    let tokens: Vec<&str> = query.split_whitespace().collect();
-    let mut operator = "AND";
-    for token in &tokens {
-        let upper = token.to_uppercase();
-        if upper == "AND" {
-            operator = "AND";
-            break;
-        } else if upper == "OR" {
-            operator = "OR";
+/// Where in a verse a search term or phrase has to occur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchPosition {
+    /// Anywhere in the verse.
+    Anywhere,
+    /// At the very beginning of the verse.
+    Start,
+    /// At the very end of the verse.
+    End,
+}
+
+impl MatchPosition {
+    const ALL: [MatchPosition; 3] = [MatchPosition::Anywhere, MatchPosition::Start, MatchPosition::End];
+}
+
+impl fmt::Display for MatchPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            MatchPosition::Anywhere => "Match anywhere",
+            MatchPosition::Start => "Match at start of verse",
+            MatchPosition::End => "Match at end of verse",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// How a single search term is matched against verse text, shared by the SQL conditions
+/// and the highlighter so highlighted spans line up with what was searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TermMatching {
+    position: MatchPosition,
+    /// Distinguish "LORD" from "Lord"; off by default.
+    case_sensitive: bool,
+    /// Only match whole words, so "art" no longer finds "heart"; off by default.
+    whole_word: bool,
+}
+
+impl Default for TermMatching {
+    fn default() -> Self {
+        TermMatching {
+            position: MatchPosition::Anywhere,
+            case_sensitive: false,
+            whole_word: false,
         }
     }
-    let mut conditions = Vec::new();
-    let mut params = Vec::new();
-    for token in tokens {
-        let upper = token.to_uppercase();
-        if upper == "AND" || upper == "OR" {
-            continue;
+}
+
+impl TermMatching {
+    /// The condition for one bound term (see `param`), optionally negated.
+    /// LIKE ignores case, but SQLite only folds ASCII letters; GLOB is used when case matters. Whole-word matching needs
+    /// word boundaries, so it goes through the registered `regexp` function instead.
+    /// LIKE takes `\` as its escape character so `%` and `_` in a term match literally.
+    /// An anchored term is matched against the verse's plain text (see `plain_text`), so
+    /// leading or trailing markup such as `<J>` or a footnote doesn't hide where the verse
+    /// begins or ends, just as the highlighter skips it.
+    fn condition(&self, negated: bool) -> String {
+        let text = match self.position {
+            MatchPosition::Anywhere => "text",
+            MatchPosition::Start | MatchPosition::End => "trim(plain_text(text))",
+        };
+        if self.whole_word {
+            return format!("{} {}REGEXP ?", text, if negated { "NOT " } else { "" });
+        }
+        let (op, any, escape) =
+            if self.case_sensitive { ("GLOB", "'*'", "") } else { ("LIKE", "'%'", " ESCAPE '\\'") };
+        let pattern = match self.position {
+            MatchPosition::Anywhere => format!("{any} || ? || {any}", any = any),
+            MatchPosition::Start => format!("? || {}", any),
+            MatchPosition::End => format!("{} || ?", any),
+        };
+        format!("{} {}{} {}{}", text, if negated { "NOT " } else { "" }, op, pattern, escape)
+    }
+
+    /// The value bound for a term: as typed with the wildcards of LIKE (or of GLOB, when case
+    /// matters) escaped, or as a word-bounded pattern for whole-word matching.
+    fn param(&self, term: &str) -> String {
+        if self.whole_word {
+            return highlight_pattern(&[term], *self);
         }
-        if upper.starts_with("NOT") && token.len() > 3 {
-            let term = token[3..].trim();
-            if !term.is_empty() {
-                conditions.push("text NOT LIKE '%' || ? || '%'".to_string());
-                params.push(term.to_string());
+        let mut escaped = String::with_capacity(term.len());
+        for c in term.chars() {
+            match c {
+                '%' | '_' | '\\' if !self.case_sensitive => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '*' | '?' | '[' if self.case_sensitive => {
+                    escaped.push('[');
+                    escaped.push(c);
+                    escaped.push(']');
+                }
+                _ => escaped.push(c),
             }
-        } else {
-            conditions.push("text LIKE '%' || ? || '%'".to_string());
-            params.push(token.to_string());
         }
+        escaped
     }
-    let clause = if conditions.is_empty() {
-        "1".to_string()
-    } else {
-        conditions.join(&format!(" {} ", operator))
-    };
-    (clause, params)
 }
 
-/// For lookup: Parse a lookup reference.
-/// Accepts either "Gen 6:1-6" (end chapter omitted, so assume same as start) or "Gen 6:1-7:2".
-fn parse_lookup(query: &str) -> Option<(String, u32, u32, u32, u32)> {
-    let re = Regex::new(
-        r"^(?P<book>\S+)\s+(?P<start_ch>\d+):(?P<start_v>\d+)-(?:(?P<end_ch>\d+):)?(?P<end_v>\d+)$"
-    ).ok()?;
-    let caps = re.captures(query)?;
-    let book = caps.name("book")?.as_str().to_string();
-    let start_ch: u32 = caps.name("start_ch")?.as_str().parse().ok()?;
-    let start_v: u32 = caps.name("start_v")?.as_str().parse().ok()?;
-    let end_ch: u32 = if let Some(m) = caps.name("end_ch") {
-        m.as_str().parse().ok()?
-    } else {
-        start_ch
-    };
-    let end_v: u32 = caps.name("end_v")?.as_str().parse().ok()?;
-    Some((book, start_ch, start_v, end_ch, end_v))
+/// The operator negating the next search term ("NOT works"); also accepted glued to the
+/// term ("NOTworks") for compatibility with older queries.
+const NOT_PREFIX: &str = "NOT";
+
+/// Options that shape how an advanced search query is turned into SQL and highlighted.
+#[derive(Debug, Clone)]
+struct SearchOptions {
+    /// Unquoted terms shorter than this (in characters) are ignored.
+    min_term_len: usize,
+    /// Where in the verse terms (and whole quoted phrases) must occur, and whether case matters.
+    matching: TermMatching,
+    /// How NOT terms combine with OR.
+    not_semantics: NotSemantics,
+    /// Matches highlighted per verse; later ones are left plain and counted instead.
+    max_highlights: usize,
 }
 
-/// For highlighting: Split text into segments that match any search token (case‑insensitive).
-fn split_for_highlight<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
-    let tokens: Vec<&str> = query
-        .split_whitespace()
-        .filter(|&t| {
-            let upper = t.to_uppercase();
-            upper != "AND" && upper != "OR" && !upper.starts_with("NOT")
-        })
-        .collect();
-    if tokens.is_empty() {
-        return vec![(text, false)];
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            min_term_len: 2,
+            matching: TermMatching::default(),
+            not_semantics: NotSemantics::Exclude,
+            max_highlights: 50,
+        }
     }
-    let pattern = format!("(?i)({})", tokens.join("|"));
-    let re = match Regex::new(&pattern) {
-        Ok(r) => r,
-        Err(_) => return vec![(text, false)],
-    };
+}
+
+/// The WHERE clause built from an advanced search query.
+#[derive(Debug, Clone, PartialEq)]
+struct WhereClause {
+    sql: String,
+    params: Vec<String>,
+    /// Remarks about the query worth showing to the user (e.g. ignored terms).
+    notes: Vec<String>,
+}
+
+/// Strip surrounding double quotes from a term, reporting whether it was quoted.
+/// An unterminated quote (`"living water`) still counts as quoted.
+fn unquote(term: &str) -> (&str, bool) {
+    match term.strip_prefix('"') {
+        Some(inner) => (inner.strip_suffix('"').unwrap_or(inner), true),
+        None => (term, false),
+    }
+}
+
+/// Split a query into tokens on whitespace outside double quotes, so a quoted phrase
+/// stays one token (quotes included). Parentheses outside quotes are tokens of their own.
+/// An unterminated quote runs to the end of the query.
+fn tokenize_query(query: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+    for (idx, c) in query.char_indices() {
+        if (c.is_whitespace() || c == '(' || c == ')') && !in_quotes {
+            if let Some(s) = start.take() {
+                tokens.push(&query[s..idx]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&query[idx..idx + 1]);
+            }
+            continue;
+        }
+        if start.is_none() {
+            start = Some(idx);
+        }
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(query[s..].trim_end());
+    }
+    tokens
+}
+
+/// Whether an unquoted search term falls below the minimum length and should be dropped.
+fn is_short_term(term: &str, quoted: bool, options: &SearchOptions) -> bool {
+    !quoted && term.chars().count() < options.min_term_len
+}
+
+/// The operator joining the terms of one group of a query: AND if the group contains AND
+/// anywhere, otherwise OR if it contains OR, otherwise AND.
+fn query_operator(tokens: &[&str]) -> &'static str {
+    let mut operator = "AND";
+    for token in tokens {
+        let upper = token.to_uppercase();
+        if upper == "AND" {
+            operator = "AND";
+            break;
+        } else if upper == "OR" {
+            operator = "OR";
+        }
+    }
+    operator
+}
+
+/// A parenthesized group of an advanced search query, or the whole query, with the
+/// operator joining its members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryGroup<'q> {
+    operator: &'static str,
+    members: Vec<QueryNode<'q>>,
+}
+
+/// A member of a query group, possibly negated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QueryNode<'q> {
+    /// A term or quoted phrase, quotes still on.
+    Term { negated: bool, text: &'q str },
+    Group { negated: bool, group: QueryGroup<'q> },
+}
+
+/// Parse a query into its top-level group. Parentheses nest groups, each with its own
+/// operator (see `query_operator`). A standalone NOT negates the next term or group (a
+/// trailing NOT is ignored); a term glued to NOT ("NOTworks", see `glued_not_term`) is negated too. A group left
+/// open runs to the end of the query and a stray closing parenthesis is ignored.
+fn parse_query<'q>(tokens: &[&'q str]) -> QueryGroup<'q> {
+    parse_group(tokens, &mut 0, false)
+}
+
+/// The group starting at `tokens[*pos]`, up to its closing parenthesis when `nested` (which
+/// is left for the caller to consume) or the end of the query.
+fn parse_group<'q>(tokens: &[&'q str], pos: &mut usize, nested: bool) -> QueryGroup<'q> {
+    let mut members = Vec::new();
+    let mut operators = Vec::new();
+    let mut negate_next = false;
+    while let Some(&token) = tokens.get(*pos) {
+        let upper = token.to_uppercase();
+        match token {
+            ")" if nested => break,
+            ")" => {}
+            "(" => {
+                *pos += 1;
+                members.push(QueryNode::Group { negated: negate_next, group: parse_group(tokens, pos, true) });
+                negate_next = false;
+            }
+            _ if upper == "AND" || upper == "OR" => operators.push(token),
+            _ if upper == NOT_PREFIX => negate_next = true,
+            _ => {
+                let (negated, text) = match glued_not_term(token) {
+                    Some(term) => (true, term),
+                    None => (negate_next, token),
+                };
+                members.push(QueryNode::Term { negated, text });
+                negate_next = false;
+            }
+        }
+        *pos += 1;
+    }
+    QueryGroup { operator: query_operator(&operators), members }
+}
+
+/// The term of a token glued to NOT ("NOTworks" gives "works"). Only the uppercase operator
+/// counts and the term must not go on in uppercase, so words such as "nothing", "Note" or
+/// "NOTHING" stay ordinary terms.
+fn glued_not_term(token: &str) -> Option<&str> {
+    let rest = token.strip_prefix(NOT_PREFIX)?;
+    rest.chars().next().filter(|c| !c.is_uppercase())?;
+    Some(rest.trim())
+}
+
+/// Render one group as SQL, appending its bound terms to `params` in placeholder order.
+/// Returns None when nothing is left of the group once empty and short terms are dropped.
+/// Under `NotSemantics::Exclude`, negated members are pulled out of the group's operator
+/// and ANDed after it, as the whole query always did before groups existed; a `nested`
+/// group with such exclusions is parenthesized so it stays one condition.
+fn render_group(
+    group: &QueryGroup,
+    options: &SearchOptions,
+    nested: bool,
+    params: &mut Vec<String>,
+    notes: &mut Vec<String>,
+) -> Option<String> {
+    let mut conditions = Vec::new();
+    let mut exclusions = Vec::new();
+    let mut exclusion_params = Vec::new();
+    let mut anchored_terms = 0;
+    for member in &group.members {
+        let (negated, sql, member_params) = match member {
+            QueryNode::Term { negated, text } => {
+                let (term, quoted) = unquote(text);
+                if term.is_empty() {
+                    continue;
+                }
+                if is_short_term(term, quoted, options) {
+                    notes.push(format!("ignored short term: '{}'", term));
+                    continue;
+                }
+                anchored_terms += (!*negated) as usize;
+                (*negated, options.matching.condition(*negated), vec![options.matching.param(term)])
+            }
+            QueryNode::Group { negated, group } => {
+                let mut group_params = Vec::new();
+                let Some(sql) = render_group(group, options, true, &mut group_params, notes) else {
+                    continue;
+                };
+                (*negated, if *negated { format!("NOT {}", sql) } else { sql }, group_params)
+            }
+        };
+        if negated && options.not_semantics == NotSemantics::Exclude {
+            exclusions.push(sql);
+            exclusion_params.extend(member_params);
+        } else {
+            conditions.push(sql);
+            params.extend(member_params);
+        }
+    }
+    if conditions.is_empty() && exclusions.is_empty() {
+        return None;
+    }
+    if group.operator == "AND" && anchored_terms > 1 && options.matching.position != MatchPosition::Anywhere {
+        let end = if options.matching.position == MatchPosition::Start { "begin" } else { "end" };
+        notes.push(format!(
+            "{} terms joined by AND must all {} the verse; that only matches where each is part of the longest",
+            anchored_terms, end
+        ));
+    }
+    let mut sql = if conditions.is_empty() {
+        "1".to_string()
+    } else {
+        format!("({})", conditions.join(&format!(" {} ", group.operator)))
+    };
+    let excluding = !exclusions.is_empty();
+    for exclusion in exclusions {
+        sql = format!("{} AND {}", sql, exclusion);
+    }
+    params.extend(exclusion_params);
+    Some(if nested && excluding { format!("({})", sql) } else { sql })
+}
+
+/// The terms of a query that verses must contain, quotes still on: terms under a NOT, or
+/// inside a negated group, are left out.
+fn positive_terms<'q>(group: &QueryGroup<'q>, terms: &mut Vec<&'q str>) {
+    for member in &group.members {
+        match member {
+            QueryNode::Term { negated: false, text } => terms.push(text),
+            QueryNode::Group { negated: false, group } => positive_terms(group, terms),
+            _ => {}
+        }
+    }
+}
+
+/// For advanced search: Build a dynamic WHERE clause from a query (e.g. "faith AND hope",
+/// or "(faith OR hope) AND love"). See `search_help` for the rules, and `NotSemantics` for
+/// how NOT terms combine with OR. Parameters are in the order of their placeholders.
+fn build_where_clause(query: &str, options: &SearchOptions) -> WhereClause {
+    let tokens = tokenize_query(query);
+    let mut params = Vec::new();
+    let mut notes = Vec::new();
+    let sql = render_group(&parse_query(&tokens), options, false, &mut params, &mut notes);
+    WhereClause { sql: sql.unwrap_or_else(|| "1".to_string()), params, notes }
+}
+
+/// How the plain query syntax works under the current options, for the in-app help.
+/// Written from the same constants and rules `build_where_clause` uses.
+fn search_help(options: &SearchOptions) -> Vec<String> {
+    let not_rule = match options.not_semantics {
+        NotSemantics::Exclude => format!(
+            "{p} before a term removes verses containing it from the results, whatever the operator: \
+             \"faith OR hope {p} works\" finds (faith OR hope) AND NOT works.",
+            p = NOT_PREFIX
+        ),
+        NotSemantics::Operand => format!(
+            "{p} before a term matches verses without it, joined by the operator like any other term: \
+             \"faith OR {p} works\" finds verses with faith, or without works.",
+            p = NOT_PREFIX
+        ),
+    };
+    let position = match options.matching.position {
+        MatchPosition::Anywhere if options.matching.whole_word => "Terms match whole words anywhere in a verse.",
+        MatchPosition::Anywhere => "Terms match anywhere in a verse, including inside longer words.",
+        MatchPosition::Start => "Terms must begin the verse.",
+        MatchPosition::End => "Terms must end the verse.",
+    };
+    vec![
+        format!(
+            "Terms are separated by spaces and matched {}.",
+            if options.matching.case_sensitive {
+                "case-sensitively"
+            } else {
+                "ignoring case, though only for unaccented Latin letters (\"É\" and \"é\" differ)"
+            }
+        ),
+        "One operator joins the terms of each group, and of the query outside any group: AND if \
+         the group contains AND anywhere, otherwise OR if it contains OR, otherwise AND."
+            .to_string(),
+        format!(
+            "Parentheses group terms, each group with its own operator: \"(faith OR hope) AND love\". \
+             {p} before a group negates all of it; an unclosed group runs to the end of the query.",
+            p = NOT_PREFIX
+        ),
+        not_rule,
+        "A \"quoted phrase\" is matched as one contiguous piece of text, as typed, and is never \
+         dropped for being short; an unclosed quote runs to the end of the query."
+            .to_string(),
+        format!("Unquoted terms shorter than {} characters are ignored.", options.min_term_len),
+        position.to_string(),
+    ]
+}
+
+/// The window size until one is remembered in the settings.
+const DEFAULT_WINDOW_SIZE: (u32, u32) = (800, 600);
+
+/// The smallest window size remembered, so the next launch never opens a sliver of a window.
+const MIN_WINDOW_SIZE: (u32, u32) = (400, 300);
+
+/// Window positions at or beyond this are off-screen parking spots (Windows moves a
+/// minimized window to -32000,-32000) rather than where the user left the window.
+const OFFSCREEN_POSITION: i32 = -10_000;
+
+/// A reported window size worth remembering, raised to `MIN_WINDOW_SIZE`; None for the
+/// 0x0 a minimized window reports.
+fn remembered_window_size((width, height): (u32, u32)) -> Option<(u32, u32)> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width.max(MIN_WINDOW_SIZE.0), height.max(MIN_WINDOW_SIZE.1)))
+}
+
+/// A reported window position worth remembering; None for an off-screen parking spot.
+fn remembered_window_position((x, y): (i32, i32)) -> Option<(i32, i32)> {
+    (x > OFFSCREEN_POSITION && y > OFFSCREEN_POSITION).then_some((x, y))
+}
+
+/// Share of the window height the search, lookup and compare panels split between them
+/// by weight; each panel gets at least `MIN_PANEL_HEIGHT` pixels.
+const RESULTS_HEIGHT_SHARE: f32 = 0.9;
+const MIN_PANEL_HEIGHT: f32 = 120.0;
+const MAX_PANEL_WEIGHT: u16 = 4;
+
+/// A scrollable results panel whose height the user can change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultsPanel {
+    Search,
+    Lookup,
+    Compare,
+}
+
+impl ResultsPanel {
+    fn index(&self) -> usize {
+        match self {
+            ResultsPanel::Search => 0,
+            ResultsPanel::Lookup => 1,
+            ResultsPanel::Compare => 2,
+        }
+    }
+}
+
+/// Text size of verses and result headers, adjusted with the "A-"/"A+" buttons.
+const DEFAULT_FONT_SIZE: u16 = 16;
+const MIN_FONT_SIZE: u16 = 10;
+const MAX_FONT_SIZE: u16 = 40;
+const FONT_SIZE_STEP: u16 = 2;
+
+/// End verse standing for "to the end of the chapter" in a parsed reference.
+const CHAPTER_END: u32 = u32::MAX;
+
+/// For lookup: Parse a lookup reference.
+/// Accepts either "Gen 6:1-6" (end chapter omitted, so assume same as start), "Gen 6:1-7:2",
+/// a single verse, "Gen 6:1", a whole chapter, "Gen 3", which runs from verse 1 to
+/// `CHAPTER_END`, or a whole book, "Jude", which runs from 1:1 to chapter and verse `CHAPTER_END`.
+/// The book is everything before the trailing chapter part, so names with spaces and
+/// numbers work: "1 John 4:9", "2 Kings 2:11", "Song of Solomon 2:1".
+fn parse_lookup(query: &str) -> Option<(String, u32, u32, u32, u32)> {
+    let re = Regex::new(
+        r"^(?P<book>[^\s:][^:]*?)(?:\s+(?P<start_ch>\d+)(?::(?P<start_v>\d+)(?:-(?:(?P<end_ch>\d+):)?(?P<end_v>\d+))?)?)?$"
+    ).ok()?;
+    let caps = re.captures(query)?;
+    let book = caps.name("book")?.as_str().trim().to_string();
+    let Some(start_ch) = caps.name("start_ch") else {
+        return Some((book, 1, 1, CHAPTER_END, CHAPTER_END));
+    };
+    let start_ch: u32 = start_ch.as_str().parse().ok()?;
+    let Some(start_v) = caps.name("start_v") else {
+        return Some((book, start_ch, 1, start_ch, CHAPTER_END));
+    };
+    let start_v: u32 = start_v.as_str().parse().ok()?;
+    let Some(end_v) = caps.name("end_v") else {
+        return Some((book, start_ch, start_v, start_ch, start_v));
+    };
+    let end_v: u32 = end_v.as_str().parse().ok()?;
+    let end_ch: u32 = if let Some(m) = caps.name("end_ch") {
+        m.as_str().parse().ok()?
+    } else {
+        start_ch
+    };
+    Some((book, start_ch, start_v, end_ch, end_v))
+}
+
+/// Whether a parsed range ends before it starts. Chapter and verse are compared as a pair,
+/// which orders like `chapter * 1000 + verse` without overflowing on `CHAPTER_END`, so
+/// "Gen 6:10-7:2" is fine while "Gen 6:10-6:2" and "Gen 7:1-6:5" are reversed.
+fn is_reversed_range(start_ch: u32, start_v: u32, end_ch: u32, end_v: u32) -> bool {
+    (start_ch, start_v) > (end_ch, end_v)
+}
+
+/// The references of a lookup input, which may hold several separated by semicolons
+/// ("John 3:16; Rom 5:8"), trimmed and in input order; empty pieces are dropped and
+/// comma lists of verses are split up (see `expand_verse_list`).
+fn lookup_references(input: &str) -> Vec<Cow<'_, str>> {
+    input
+        .split(';')
+        .map(str::trim)
+        .filter(|piece| !piece.is_empty())
+        .flat_map(expand_verse_list)
+        .collect()
+}
+
+/// A reference listing verses of one chapter with commas, "Ps 23:1,3,5" or "Ps 23:1-3,6",
+/// as one reference per listed verse or range ("Ps 23:1-3", "Ps 23:6"). Anything else is
+/// returned unchanged.
+fn expand_verse_list(reference: &str) -> Vec<Cow<'_, str>> {
+    let Ok(re) = Regex::new(r"^(?P<chapter>[^:]+\s\d+):(?P<list>\d+(?:-\d+)?(?:\s*,\s*\d+(?:-\d+)?)+)$") else {
+        return vec![Cow::Borrowed(reference)];
+    };
+    let Some(caps) = re.captures(reference) else {
+        return vec![Cow::Borrowed(reference)];
+    };
+    let chapter = &caps["chapter"];
+    caps["list"]
+        .split(',')
+        .map(|item| Cow::Owned(format!("{}:{}", chapter, item.trim())))
+        .collect()
+}
+
+/// Whether a query follows the lookup reference grammar (see `parse_lookup`), so the
+/// quick bar runs it as a lookup rather than a search. Plain phrases fit the grammar too
+/// ("love", "seventy 7"), so a reference only counts when it names one of `books`.
+fn looks_like_reference(query: &str, books: &[Book]) -> bool {
+    expand_verse_list(query.trim())
+        .iter()
+        .all(|piece| parse_lookup(piece).is_some_and(|(book, ..)| find_book(books, &book).is_some()))
+}
+
+/// Where the quick bar sends its query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryRoute {
+    /// Lookup if the query looks like a reference, otherwise search.
+    Auto,
+    Search,
+    Lookup,
+}
+
+impl QueryRoute {
+    const ALL: [QueryRoute; 3] = [QueryRoute::Auto, QueryRoute::Search, QueryRoute::Lookup];
+
+    /// Resolve `Auto` for a query: `Lookup` for references to the module's `books`, else `Search`.
+    fn resolve(&self, query: &str, books: &[Book]) -> QueryRoute {
+        match self {
+            QueryRoute::Auto if looks_like_reference(query, books) => QueryRoute::Lookup,
+            QueryRoute::Auto => QueryRoute::Search,
+            route => *route,
+        }
+    }
+}
+
+impl fmt::Display for QueryRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            QueryRoute::Auto => "Auto-detect",
+            QueryRoute::Search => "Search",
+            QueryRoute::Lookup => "Browse (lookup)",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// The condition leaving the globally excluded books out of a search, or None when no book
+/// is excluded so the query is left as it was. Book numbers are integers, so they are inlined.
+fn excluded_books_clause(excluded: &BTreeSet<u32>, book_column: &str) -> Option<String> {
+    if excluded.is_empty() {
+        return None;
+    }
+    let numbers: Vec<String> = excluded.iter().map(u32::to_string).collect();
+    Some(format!("CAST({} AS INTEGER) NOT IN ({})", book_column, numbers.join(", ")))
+}
+
+/// Book numbers bounding each testament: Genesis..Malachi and Matthew..Revelation.
+const OLD_TESTAMENT: (u32, u32) = (10, 460);
+const NEW_TESTAMENT: (u32, u32) = (470, 730);
+
+/// Where an advanced search looks: the whole Bible, one testament or a single book.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+enum SearchScope {
+    #[default]
+    All,
+    OldTestament,
+    NewTestament,
+    Book(Book),
+}
+
+impl SearchScope {
+    /// The choices offered for the module's `books`: the whole Bible, each testament, then
+    /// every book in canonical order.
+    fn choices(books: &[Book]) -> Vec<SearchScope> {
+        let mut choices = vec![SearchScope::All, SearchScope::OldTestament, SearchScope::NewTestament];
+        choices.extend(books.iter().cloned().map(SearchScope::Book));
+        choices
+    }
+
+    /// The condition keeping a search within the scope, or None for the whole Bible.
+    /// Book numbers are integers, so they are inlined like the excluded books.
+    fn clause(&self, book_column: &str) -> Option<String> {
+        let (first, last) = match self {
+            SearchScope::All => return None,
+            SearchScope::OldTestament => OLD_TESTAMENT,
+            SearchScope::NewTestament => NEW_TESTAMENT,
+            SearchScope::Book(book) => {
+                return Some(format!("CAST({} AS INTEGER) = {}", book_column, book.book_number));
+            }
+        };
+        Some(format!("CAST({} AS INTEGER) BETWEEN {} AND {}", book_column, first, last))
+    }
+}
+
+impl fmt::Display for SearchScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchScope::All => write!(f, "All books"),
+            SearchScope::OldTestament => write!(f, "Old Testament"),
+            SearchScope::NewTestament => write!(f, "New Testament"),
+            SearchScope::Book(book) => write!(f, "{}", book.long_name),
+        }
+    }
+}
+
+/// A search's WHERE clause narrowed to the search scope and with the excluded books left out.
+/// The clause is parenthesized before each condition is added, since it may be an OR of
+/// phrases (Lines mode) that AND would otherwise bind to the last of.
+fn narrowed_where_clause(where_clause: String, excluded: &BTreeSet<u32>, scope: &SearchScope, book_column: &str) -> String {
+    let where_clause = match excluded_books_clause(excluded, book_column) {
+        Some(exclusion) => format!("({}) AND {}", where_clause, exclusion),
+        None => where_clause,
+    };
+    match scope.clause(book_column) {
+        Some(scope) => format!("({}) AND {}", where_clause, scope),
+        None => where_clause,
+    }
+}
+
+/// For multi-line search: each non-empty line is a phrase, and the phrases are ORed together.
+/// Lines are matched as typed, so the minimum term length does not apply to them.
+fn build_lines_clause(lines: &[String], matching: TermMatching) -> WhereClause {
+    let phrases = search_line_phrases(lines);
+    let sql = if phrases.is_empty() {
+        "1".to_string()
+    } else {
+        vec![matching.condition(false); phrases.len()].join(" OR ")
+    };
+    WhereClause {
+        sql,
+        params: phrases.iter().map(|p| matching.param(p)).collect(),
+        notes: Vec::new(),
+    }
+}
+
+/// How a verse's word count is compared with the requested count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordCountOp {
+    Exactly,
+    FewerThan,
+    MoreThan,
+}
+
+impl WordCountOp {
+    const ALL: [WordCountOp; 3] = [WordCountOp::Exactly, WordCountOp::FewerThan, WordCountOp::MoreThan];
+
+    fn sql(&self) -> &'static str {
+        match self {
+            WordCountOp::Exactly => "=",
+            WordCountOp::FewerThan => "<",
+            WordCountOp::MoreThan => ">",
+        }
+    }
+}
+
+impl fmt::Display for WordCountOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            WordCountOp::Exactly => "Exactly",
+            WordCountOp::FewerThan => "Fewer than",
+            WordCountOp::MoreThan => "More than",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// For word-count search: verses whose word count compares with `count` as `op` says.
+/// A count that isn't a whole number matches nothing and says why.
+fn build_word_count_clause(op: WordCountOp, count: &str) -> WhereClause {
+    match count.trim().parse::<u32>() {
+        Ok(n) => WhereClause {
+            // The parameter is bound as text, so cast it to compare numerically.
+            sql: format!("word_count(v.text) {} CAST(? AS INTEGER)", op.sql()),
+            params: vec![n.to_string()],
+            notes: Vec::new(),
+        },
+        Err(_) => WhereClause {
+            sql: "0".to_string(),
+            params: Vec::new(),
+            notes: vec![format!("not a number of words: '{}'", count.trim())],
+        },
+    }
+}
+
+/// For regex search: verses whose text matches `pattern` through the registered `regexp`
+/// function. The pattern is checked first, so an invalid one is reported instead of
+/// failing inside SQLite.
+fn build_regex_clause(pattern: &str) -> Result<WhereClause, regex::Error> {
+    Regex::new(pattern)?;
+    Ok(WhereClause {
+        sql: "v.text REGEXP ?".to_string(),
+        params: vec![pattern.to_string()],
+        notes: Vec::new(),
+    })
+}
+
+/// The trimmed, non-empty lines of a multi-line search.
+fn search_line_phrases(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).collect()
+}
+
+/// Whether a query group holds in a verse's words of Christ (see `red_letter_words`): its
+/// terms and groups are joined by the group's operator, negated members are left to the
+/// SQL query, and a group with nothing else left holds.
+fn query_in_red_letter(group: &QueryGroup, words: &[String], options: &SearchOptions) -> bool {
+    let held: Vec<bool> = group
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            QueryNode::Term { negated: false, text } => {
+                let (term, quoted) = unquote(text);
+                if term.is_empty() || is_short_term(term, quoted, options) {
+                    return None;
+                }
+                Some(term_in_red_letter(words, term))
+            }
+            QueryNode::Group { negated: false, group } => Some(query_in_red_letter(group, words, options)),
+            _ => None,
+        })
+        .collect();
+    match group.operator {
+        "OR" if !held.is_empty() => held.contains(&true),
+        _ => !held.contains(&false),
+    }
+}
+
+/// The terms from an advanced search query that should be highlighted in results.
+/// Negated terms are left out, since matching verses never contain them.
+fn highlight_terms<'q>(query: &'q str, options: &SearchOptions) -> Vec<&'q str> {
+    let tokens = tokenize_query(query);
+    let mut terms = Vec::new();
+    positive_terms(&parse_query(&tokens), &mut terms);
+    terms
+        .into_iter()
+        .map(unquote)
+        .filter(|&(t, quoted)| !t.is_empty() && !is_short_term(t, quoted, options))
+        .map(|(t, _)| t)
+        .collect()
+}
+
+/// For lookup autocomplete: books whose short or long name starts with the typed text.
+/// Only offered while the book is still being typed ("1 Jo", "Song of"), not once a chapter is.
+fn book_suggestions<'b>(books: &'b [Book], input: &str, limit: usize) -> Vec<&'b Book> {
+    let typed = input.trim_start();
+    if typed.is_empty() || typed.contains(':') || chapter_prefix(typed).is_some() {
+        return Vec::new();
+    }
+    let typed = typed.to_lowercase();
+    books
+        .iter()
+        .filter(|b| {
+            b.short_name.to_lowercase().starts_with(&typed) || b.long_name.to_lowercase().starts_with(&typed)
+        })
+        .take(limit)
+        .collect()
+}
+
+/// How long the lookup input must stay unchanged before the preview queries the module.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// For the lookup preview: the "Book Chapter" prefix of a reference being typed, if complete.
+/// The part before any verse is read with `parse_lookup`'s grammar, so numbered and
+/// multi-word books ("1 John 4", "Song of Solomon 2:1") work as they do in a lookup.
+fn chapter_prefix(input: &str) -> Option<(String, u32)> {
+    let chapter_part = input.split(':').next()?.trim();
+    match parse_lookup(chapter_part)? {
+        (book, chapter, 1, end_ch, CHAPTER_END) if end_ch == chapter => Some((book, chapter)),
+        _ => None,
+    }
+}
+
+/// The alternation matching any of the literal terms, ignoring case unless `matching` says otherwise.
+/// Longer terms come first so "faithful" wins over its prefix "faith" (the regex
+/// alternation takes the first branch that matches, not the longest).
+/// An anchored position only lets the alternation match at the start or end of the text
+/// (give or take whitespace), and whole-word matching puts word boundaries around it.
+/// The alternation is capture group 1, which is what gets highlighted.
+fn highlight_pattern(terms: &[&str], matching: TermMatching) -> String {
+    let mut ordered: Vec<&str> = terms.to_vec();
+    ordered.sort_by_key(|t| std::cmp::Reverse(t.chars().count()));
+    let escaped: Vec<String> = ordered.iter().map(|t| regex::escape(t)).collect();
+    let (start, end) = match matching.position {
+        MatchPosition::Anywhere => ("", ""),
+        MatchPosition::Start => (r"^\s*", ""),
+        MatchPosition::End => ("", r"\s*$"),
+    };
+    let flags = if matching.case_sensitive { "" } else { "(?i)" };
+    let boundary = if matching.whole_word { r"\b" } else { "" };
+    format!("{}{}{b}({}){b}{}", flags, start, escaped.join("|"), end, b = boundary)
+}
+
+/// For highlighting: Split text into segments that match any of the given literal terms.
+fn split_for_highlight<'a>(text: &'a str, terms: &[&str], matching: TermMatching) -> Vec<(&'a str, bool)> {
+    if terms.is_empty() {
+        return vec![(text, false)];
+    }
+    let re = match Regex::new(&highlight_pattern(terms, matching)) {
+        Ok(r) => r,
+        Err(_) => return vec![(text, false)],
+    };
     let mut segments = Vec::new();
     let mut last_end = 0;
-    for mat in re.find_iter(text) {
+    for caps in re.captures_iter(text) {
+        let Some(mat) = caps.get(1) else {
+            continue;
+        };
         let start = mat.start();
         let end = mat.end();
         if start > last_end {
@@ -181,303 +1477,5047 @@ fn split_for_highlight<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
     segments
 }
 
+/// Split items into a left and right column, filling the left column first so results
+/// still read top-to-bottom; the left column gets the extra item of an odd count.
+fn split_columns<T>(mut items: Vec<T>) -> (Vec<T>, Vec<T>) {
+    let right = items.split_off(items.len().div_ceil(2));
+    (items, right)
+}
+
+/// Number of verses per book, keyed by the book's short name.
+fn count_by_book(verses: &[Verse]) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for verse in verses {
+        *counts.entry(verse.short_name.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Group canonically sorted verses into runs sharing a `(book short name, chapter)`.
+/// Verses are grouped by consecutive runs, so unsorted input may repeat a chapter.
+fn group_by_chapter(verses: &[Verse]) -> Vec<((String, u32), Vec<&Verse>)> {
+    let mut groups: Vec<((String, u32), Vec<&Verse>)> = Vec::new();
+    for verse in verses {
+        match groups.last_mut() {
+            Some(((book, chapter), members)) if *book == verse.short_name && *chapter == verse.chapter => {
+                members.push(verse)
+            }
+            _ => groups.push(((verse.short_name.clone(), verse.chapter), vec![verse])),
+        }
+    }
+    groups
+}
+
+/// The verses to show around a result spanning `first..=last`: `context` verses on each
+/// side, clamped to the chapter's first verse and to `chapter_end`, its last.
+fn context_range(first: u32, last: u32, context: u32, chapter_end: u32) -> (u32, u32) {
+    let start = first.saturating_sub(context).max(1);
+    let end = last.saturating_add(context).min(chapter_end.max(last));
+    (start, end)
+}
+
+/// A chapter containing search matches, for the chapters-only result view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChapterCount {
+    short_name: String,
+    long_name: String,
+    chapter: u32,
+    matches: usize,
+}
+
+/// Collapse canonically sorted search results to their distinct chapters, each with
+/// the number of matching verses in it.
+fn chapter_counts(verses: &[Verse]) -> Vec<ChapterCount> {
+    group_by_chapter(verses)
+        .into_iter()
+        .map(|((short_name, chapter), members)| ChapterCount {
+            short_name,
+            long_name: members[0].long_name.clone(),
+            chapter,
+            matches: members.len(),
+        })
+        .collect()
+}
+
+/// Lay out result items in one column, or in two side-by-side columns for wide screens.
+fn flow_columns(items: Vec<Element<'_, Message>>, two_columns: bool) -> Element<'_, Message> {
+    if !two_columns {
+        return Column::with_children(items).spacing(10).into();
+    }
+    let (left, right) = split_columns(items);
+    Row::new()
+        .spacing(20)
+        .push(Column::with_children(left).spacing(10).width(Length::FillPortion(1)))
+        .push(Column::with_children(right).spacing(10).width(Length::FillPortion(1)))
+        .into()
+}
+
+/// Wrap a result item so odd-numbered items get the striped background when striping is on.
+fn striped<'a>(item: impl Into<Element<'a, Message>>, idx: usize, enabled: bool) -> Element<'a, Message> {
+    let container = Container::new(item).width(Length::Fill);
+    if enabled && idx % 2 == 1 {
+        container.style(StripedRow).into()
+    } else {
+        container.into()
+    }
+}
+
+/// A verse's raw text, markup tags and all, in a small monospace font for inspecting modules.
+fn raw_text_line(raw: &str) -> Element<'_, Message> {
+    text(raw).size(12).font(iced::Font::MONOSPACE).style(PreviewText).into()
+}
+
+/// Verse text as displayed outside the styled segments: with its markup removed (see
+/// `clean_text`) unless the raw markup toggle is on.
+fn display_text(text: &str, raw_markup: bool) -> Cow<'_, str> {
+    if raw_markup {
+        Cow::Borrowed(text)
+    } else {
+        clean_text(text)
+    }
+}
+
+/// Copies of `verses` with their text as displayed, for diffs that should see only the words.
+fn displayed_verses(verses: &[Verse], raw_markup: bool) -> Vec<Verse> {
+    verses
+        .iter()
+        .map(|v| Verse { text: display_text(&v.text, raw_markup).into_owned(), ..v.clone() })
+        .collect()
+}
+
+/// Verse text for single-paragraph rendering: each line break, with the spaces around it,
+/// becomes one space. Text without line breaks is returned as is.
+fn single_line(text: &str) -> Cow<'_, str> {
+    if !text.contains(['\n', '\r']) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut after_break = false;
+    for c in text.chars() {
+        if c == '\n' || c == '\r' {
+            while out.ends_with([' ', '\t']) {
+                out.pop();
+            }
+            out.push(' ');
+            after_break = true;
+        } else if !(after_break && (c == ' ' || c == '\t')) {
+            after_break = false;
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Verse text as laid out in the result lists: kept as is in poetry layout
+/// (`keep_line_breaks`), collapsed to one paragraph by [`single_line`] otherwise.
+fn verse_layout(text: &str, keep_line_breaks: bool) -> Cow<'_, str> {
+    if keep_line_breaks {
+        Cow::Borrowed(text)
+    } else {
+        single_line(text)
+    }
+}
+
+/// Render styled verse segments as a row of text widgets, followed by a note when
+/// `hidden` matches went unhighlighted because of the per-verse cap. Line breaks in the
+/// text are collapsed to spaces unless `keep_line_breaks` (poetry layout) is set.
+/// Search highlights take precedence over red letters, which take precedence over supplied words.
+/// Strong's numbers are shown small and muted after their word when `strongs` is set, and
+/// left out otherwise.
+fn segments_row<'a>(
+    (segments, hidden): (Vec<VerseSegment<'a>>, usize),
+    red_letter: bool,
+    keep_line_breaks: bool,
+    strongs: bool,
+    highlight: HighlightText,
+    size: u16,
+) -> Row<'a, Message> {
+    let mut row = Row::new().spacing(0);
+    for segment in segments {
+        if segment.strongs {
+            if strongs && !segment.text.trim().is_empty() {
+                row = row.push(text(segment.text.trim()).size(size * 2 / 3).style(PreviewText));
+            }
+            continue;
+        }
+        let seg_text = text(verse_layout(segment.text, keep_line_breaks)).size(size);
+        let seg_text = if segment.highlight {
+            seg_text.style(highlight)
+        } else if red_letter && segment.red_letter {
+            seg_text.style(RedLetterText { dark: highlight.dark })
+        } else if segment.italic {
+            seg_text.style(SuppliedText { dark: highlight.dark })
+        } else {
+            seg_text.style(NormalText)
+        };
+        row = row.push(seg_text);
+    }
+    if hidden > 0 {
+        row = row.push(text(format!(" (+{} more matches)", hidden)).style(PreviewText));
+    }
+    row
+}
+
+/// Find the Bible modules in `dir` (and its subdirectories, if `recursive`) for comparison,
+/// reading each one's description from its info table and merging duplicates according to `dedup`.
+/// Modules are opened into `cache`, so the comparison that follows doesn't open them again;
+/// files that aren't Bible modules are left out.
+fn discover_modules(
+    dir: &Path,
+    recursive: bool,
+    dedup: DedupMode,
+    cache: &mut HashMap<PathBuf, Bible>,
+) -> Vec<DiscoveredModule> {
+    let modules = dedup_modules(scan_modules(dir, recursive, cache), dedup, file_hash);
+    forget_duplicates(&modules, cache);
+    modules
+}
+
+/// Scan the compare directory once for both lists built from it: the modules offered as a
+/// search baseline (merged by description) and the compare modules (merged by `dedup`).
+fn discover_module_lists(
+    dir: &Path,
+    recursive: bool,
+    dedup: DedupMode,
+    cache: &mut HashMap<PathBuf, Bible>,
+) -> (Vec<DiscoveredModule>, Vec<DiscoveredModule>) {
+    let found = scan_modules(dir, recursive, cache);
+    let baselines = dedup_modules(found.clone(), DedupMode::Description, file_hash);
+    let modules = dedup_modules(found, dedup, file_hash);
+    forget_duplicates(&modules, cache);
+    (baselines, modules)
+}
+
+/// The module files in `dir` with their labels, opened into `cache` (see `discover_modules`).
+fn scan_modules(dir: &Path, recursive: bool, cache: &mut HashMap<PathBuf, Bible>) -> Vec<(PathBuf, String)> {
+    module_files(dir, recursive)
+        .into_iter()
+        .map(|path| canonical_path(&path))
+        .filter_map(|path| {
+            // A compressed module isn't decompressed just to be listed: it is labelled by
+            // its file name until its first comparison leaves a decompressed copy.
+            if is_zstd(&path) && cached_db_path(&path).is_none() {
+                let label = translation_label(None, &path);
+                return Some((path, label));
+            }
+            if !cache.contains_key(&path) {
+                match open_bible(&path) {
+                    Ok(bible) => {
+                        cache.insert(path.clone(), bible);
+                    }
+                    Err(e) => {
+                        println!("Skipping {:?}: {}", path, e);
+                        return None;
+                    }
+                }
+            }
+            let label = translation_label(bible_description(&cache[&path].conn), &path);
+            Some((path, label))
+        })
+        .collect()
+}
+
+/// Close the merged duplicates of `modules`, which are never queried.
+fn forget_duplicates(modules: &[DiscoveredModule], cache: &mut HashMap<PathBuf, Bible>) {
+    for duplicate in modules.iter().flat_map(|module| &module.duplicates) {
+        cache.remove(duplicate);
+    }
+}
+
+/// Modules opened so far, keyed by path, so repeated comparisons (or search pages) reuse
+/// their connections. Shared with the background work; the compare modules' cache is
+/// replaced by an empty one whenever the compare directory is rescanned.
+type BibleCache = Arc<Mutex<HashMap<PathBuf, Bible>>>;
+
+/// The path with links and `.`/`..` resolved, or as given when it doesn't resolve.
+/// Discovered modules are canonicalized once, when the directory is scanned, so a
+/// comparison finds the main Bible among them with a plain `==`.
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The discovered modules other than the main Bible at `main_path` (canonicalized), which
+/// a comparison queries on its own connection instead.
+fn modules_besides(modules: &[DiscoveredModule], main_path: Option<&Path>) -> Vec<DiscoveredModule> {
+    modules
+        .iter()
+        .filter(|module| main_path != Some(module.path.as_path()))
+        .cloned()
+        .collect()
+}
+
+/// Open discovered modules for comparison, paired with their column labels, reusing the
+/// connections in `cache` and adding the newly opened ones to it.
+/// Files that fail to open or aren't Bible modules are skipped.
+fn open_modules<'c>(modules: &[DiscoveredModule], cache: &'c mut HashMap<PathBuf, Bible>) -> Vec<(String, &'c Bible)> {
+    for module in modules {
+        if cache.contains_key(&module.path) {
+            continue;
+        }
+        match open_bible(&module.path) {
+            Ok(bible) => {
+                cache.insert(module.path.clone(), bible);
+            }
+            Err(e) => println!("Skipping {:?} for compare: {}", module.path, e),
+        }
+    }
+    let cache: &'c HashMap<PathBuf, Bible> = cache;
+    modules
+        .iter()
+        .filter_map(|module| Some((module.label(), cache.get(&module.path)?)))
+        .collect()
+}
+
+/// Put the main Bible's column, queried on its own connection, at the main module's place
+/// among the compared `modules`; the other columns came from the background comparison.
+/// `main_path` is canonicalized, like the discovered module paths.
+fn insert_main_column(results: &mut CompareResults, modules: &[DiscoveredModule], main_path: &Path, main_verses: Vec<Verse>) {
+    let Some(idx) = modules.iter().position(|module| module.path == main_path) else {
+        return;
+    };
+    let label = modules[idx].label();
+    let at = modules[..idx]
+        .iter()
+        .filter(|module| results.iter().any(|(name, _)| *name == module.label()))
+        .count();
+    let verses = main_verses.into_iter().map(|v| Verse { long_name: label.clone(), ..v }).collect();
+    results.insert(at.min(results.len()), (label, verses));
+}
+
+/// One translation's verses for a reference range, labelled with `bible_name`.
+fn query_passage(
+    bible: &Bible,
+    bible_name: &str,
+    book: &str,
+    start_ch: u32,
+    start_v: u32,
+    end_ch: u32,
+    end_v: u32,
+) -> Result<Vec<Verse>, RusqliteError> {
+    let verses = query_range(&bible.conn, &bible.schema, book, start_ch, start_v, end_ch, end_v)?;
+    // Compare columns are labelled by translation rather than by book.
+    Ok(verses
+        .into_iter()
+        .map(|v| Verse {
+            long_name: bible_name.to_string(),
+            ..v
+        })
+        .collect())
+}
+
+/// Why a lookup reference gave no verses.
+#[derive(Debug)]
+enum LookupError {
+    /// The reference doesn't follow the lookup grammar (see `parse_lookup`).
+    Unparsable(String),
+    /// The module has no book by this name.
+    NoSuchBook(String),
+    /// The range ends before it starts, as in "Gen 6:10-6:2".
+    ReversedRange(String),
+    /// The book exists but has no verses in the range, as in "Gen 60".
+    EmptyRange(String),
+    Database(RusqliteError),
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupError::Unparsable(reference) => write!(f, "skipped unrecognized reference: '{}'", reference),
+            LookupError::NoSuchBook(book) => write!(f, "No book named '{}' in this module", book),
+            LookupError::ReversedRange(reference) => write!(f, "end reference is before start: '{}'", reference),
+            LookupError::EmptyRange(reference) => write!(f, "no verses in that range: '{}'", reference),
+            LookupError::Database(e) => write!(f, "Lookup failed: {}", e),
+        }
+    }
+}
+
+impl From<RusqliteError> for LookupError {
+    fn from(e: RusqliteError) -> Self {
+        LookupError::Database(e)
+    }
+}
+
+/// The verses of one lookup reference, with its book given by any name `find_book` knows.
+/// The book is checked against the module's `books` first, so an unknown book and an empty
+/// range are told apart; when the book list couldn't be read, the name is queried as typed.
+fn run_lookup(
+    conn: &Connection,
+    schema: &BibleSchema,
+    books: &[Book],
+    reference: &str,
+) -> Result<Vec<Verse>, LookupError> {
+    let (book, start_ch, start_v, end_ch, end_v) =
+        parse_lookup(reference).ok_or_else(|| LookupError::Unparsable(reference.to_string()))?;
+    if is_reversed_range(start_ch, start_v, end_ch, end_v) {
+        return Err(LookupError::ReversedRange(reference.to_string()));
+    }
+    let known = find_book(books, &book);
+    if known.is_none() && !books.is_empty() {
+        return Err(LookupError::NoSuchBook(book));
+    }
+    let book = known.map(|b| b.short_name.clone()).unwrap_or(book);
+    println!("Lookup Parameters: [book: {}, start: {}:{}, end: {}:{}]", book, start_ch, start_v, end_ch, end_v);
+    let verses = query_range(conn, schema, &book, start_ch, start_v, end_ch, end_v)?;
+    match (verses.is_empty(), known) {
+        (true, Some(_)) => Err(LookupError::EmptyRange(reference.to_string())),
+        (true, None) => Err(LookupError::NoSuchBook(book)),
+        (false, _) => Ok(verses),
+    }
+}
+
+/// The verses of `book` overlapping a reference range, in order. Rows come back in the
+/// stored order and are sorted here, so sorting doesn't cast every verse number either.
+fn query_range(
+    conn: &Connection,
+    schema: &BibleSchema,
+    book: &str,
+    start_ch: u32,
+    start_v: u32,
+    end_ch: u32,
+    end_v: u32,
+) -> Result<Vec<Verse>, RusqliteError> {
+    let sql = format!("
+        SELECT {}
+        FROM verses v
+        {}
+        WHERE b.short_name = ?
+          AND {}
+        ORDER BY v.chapter, v.verse
+    ", schema.verse_columns(), schema.books_join(), schema.range_overlap());
+    let mut stmt = conn.prepare(&sql)?;
+    let verse_iter = stmt.query_map(
+        params![book, start_ch, end_ch, start_ch, start_v, end_ch, end_v],
+        Verse::from_row,
+    )?;
+    let mut verses: Vec<Verse> = verse_iter.filter_map(|v| v.ok()).collect();
+    // A combined "3-4" row is stored as text, which SQLite sorts after every number.
+    verses.sort_by_key(|v| (v.chapter, v.verse));
+    Ok(verses)
+}
+
+/// Whether a query failed because the connection has no `regexp` function registered.
+fn is_missing_regexp(e: &RusqliteError) -> bool {
+    matches!(e, RusqliteError::SqliteFailure(_, Some(msg)) if msg.starts_with("no such function: regexp"))
+}
+
+/// Prepare a search query. A connection that wasn't opened through `open_bible` may lack
+/// the `regexp` function: it is registered on the spot and the query prepared again,
+/// rather than failing the search.
+fn prepare_search_query<'c>(conn: &'c Connection, sql: &str) -> Result<rusqlite::Statement<'c>, RusqliteError> {
+    match conn.prepare(sql) {
+        Err(e) if is_missing_regexp(&e) => {
+            println!("Registering missing regexp function");
+            register_regex_function(conn)?;
+            conn.prepare(sql)
+        }
+        result => result,
+    }
+}
+
+/// Run a verse query with positional text parameters, passing each verse to `f` until it
+/// returns false.
+fn for_each_verse(
+    conn: &Connection,
+    sql: &str,
+    params: &[String],
+    mut f: impl FnMut(Verse) -> bool,
+) -> Result<(), RusqliteError> {
+    let mut stmt = prepare_search_query(conn, sql)?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), Verse::from_row)?;
+    for verse in rows.filter_map(|v| v.ok()) {
+        if !f(verse) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The query selecting the verses a search's WHERE clause matches, in canonical order.
+fn search_sql(schema: &BibleSchema, where_clause: &str) -> String {
+    format!(
+        "SELECT {} FROM verses v {} WHERE {} ORDER BY CAST({} AS INTEGER), v.chapter, {}",
+        schema.verse_columns(),
+        schema.books_join(),
+        where_clause,
+        schema.verse_book(),
+        schema.verse_start()
+    )
+}
+
+/// Run a search: pass the verses matching `where_clause`, bound to `params`, to `f` in
+/// canonical order until it returns false. Only the page of `limit` rows starting at
+/// `offset` is read.
+fn run_search(
+    conn: &Connection,
+    schema: &BibleSchema,
+    where_clause: &str,
+    params: &[String],
+    limit: usize,
+    offset: usize,
+    f: impl FnMut(Verse) -> bool,
+) -> Result<(), RusqliteError> {
+    let sql = format!("{} LIMIT {} OFFSET {}", search_sql(schema, where_clause), limit, offset);
+    for_each_verse(conn, &sql, params, f)
+}
+
+/// Verses delivered per message by a streaming search.
+const SEARCH_BATCH_SIZE: usize = 200;
+
+/// Matching verses fetched per page of advanced search results; "Load more" fetches the next page.
+const DEFAULT_SEARCH_PAGE_SIZE: usize = 100;
+const SEARCH_PAGE_SIZES: [usize; 4] = [50, 100, 200, 500];
+
+/// A search to run in the background on its own connection to the main module,
+/// tagged with the search it belongs to so batches of a replaced search are ignored.
+/// Only the page of `limit` rows starting at `offset` is fetched.
+#[derive(Debug, Clone)]
+struct StreamingSearch {
+    id: u64,
+    path: PathBuf,
+    where_clause: String,
+    params: Vec<String>,
+    limit: usize,
+    offset: usize,
+    /// Also count all the verses the query matches once the page is in (the first page only).
+    count: bool,
+}
+
+/// One step of a streaming search.
+enum SearchEvent {
+    Batch(Vec<Verse>),
+    /// The number of verses the query matches over all pages.
+    Total(usize),
+    /// Why the query failed.
+    Failed(String),
+}
+
+/// Progress of a streaming search subscription.
+enum StreamState {
+    Starting(StreamingSearch, BibleCache),
+    Streaming(u64, mpsc::UnboundedReceiver<SearchEvent>),
+    Finished,
+}
+
+/// Deliver a search's results in batches as `SearchBatch` messages, its total as
+/// `SearchCounted` when asked for, then `SearchDone`.
+/// The query runs on a thread of its own; when a newer search replaces the subscription,
+/// the channel is disconnected and the thread stops at its next batch.
+fn streaming_search(search: StreamingSearch, connections: BibleCache) -> Subscription<Message> {
+    subscription::unfold(search.id, StreamState::Starting(search, connections), |state| async move {
+        let (id, mut batches) = match state {
+            StreamState::Starting(search, connections) => {
+                let (sender, receiver) = mpsc::unbounded();
+                let id = search.id;
+                std::thread::spawn(move || stream_verses(search, &connections, sender));
+                (id, receiver)
+            }
+            StreamState::Streaming(id, receiver) => (id, receiver),
+            StreamState::Finished => return future::pending().await,
+        };
+        match batches.next().await {
+            Some(SearchEvent::Batch(batch)) => (Message::SearchBatch(id, batch), StreamState::Streaming(id, batches)),
+            Some(SearchEvent::Total(total)) => (Message::SearchCounted(id, total), StreamState::Streaming(id, batches)),
+            Some(SearchEvent::Failed(e)) => (Message::SearchDone(id, Some(e)), StreamState::Finished),
+            None => (Message::SearchDone(id, None), StreamState::Finished),
+        }
+    })
+}
+
+/// Run a streaming search's query on a connection from `connections`, opening one only
+/// when none is free, and put the connection back for the next page or search.
+fn stream_verses(search: StreamingSearch, connections: &BibleCache, sender: mpsc::UnboundedSender<SearchEvent>) {
+    let idle = connections.lock().unwrap_or_else(PoisonError::into_inner).remove(&search.path);
+    let bible = match idle.map_or_else(|| open_bible(&search.path), Ok) {
+        Ok(bible) => bible,
+        Err(e) => {
+            let _ = sender.unbounded_send(SearchEvent::Failed(e.to_string()));
+            return;
+        }
+    };
+    stream_page(&bible, &search, &sender);
+    connections.lock().unwrap_or_else(PoisonError::into_inner).insert(search.path, bible);
+}
+
+/// Send the verses of one search page in batches of `SEARCH_BATCH_SIZE`, then its total
+/// when asked for. The full count scans the whole module, so it runs here after the page
+/// rather than holding up the window or the first results.
+fn stream_page(bible: &Bible, search: &StreamingSearch, sender: &mpsc::UnboundedSender<SearchEvent>) {
+    let mut batch = Vec::with_capacity(SEARCH_BATCH_SIZE);
+    let (conn, schema) = (&bible.conn, &bible.schema);
+    let result = run_search(conn, schema, &search.where_clause, &search.params, search.limit, search.offset, |verse| {
+        batch.push(verse);
+        batch.len() < SEARCH_BATCH_SIZE || sender.unbounded_send(SearchEvent::Batch(std::mem::take(&mut batch))).is_ok()
+    });
+    let sent = match result {
+        Ok(()) if batch.is_empty() => Ok(()),
+        Ok(()) => sender.unbounded_send(SearchEvent::Batch(batch)),
+        Err(e) => {
+            let _ = sender.unbounded_send(SearchEvent::Failed(e.to_string()));
+            return;
+        }
+    };
+    if sent.is_err() || !search.count || sender.is_closed() {
+        return;
+    }
+    match count_verses(conn, schema, &search.where_clause, &search.params) {
+        Ok(total) => {
+            let _ = sender.unbounded_send(SearchEvent::Total(total));
+        }
+        Err(e) => println!("Counting search matches failed: {}", e),
+    }
+}
+
+/// Days since the Unix epoch (UTC), the seed of the verse of the day.
+fn days_since_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default()
+}
+
+/// The verse of the day: a verse picked with `day` as the seed, so it stays the same all
+/// day and changes daily. None for a module without verses.
+fn verse_of_the_day(bible: &Bible, day: u64) -> Result<Option<Verse>, RusqliteError> {
+    let count: i64 = bible.conn.query_row("SELECT COUNT(*) FROM verses", [], |row| row.get(0))?;
+    if count <= 0 {
+        return Ok(None);
+    }
+    let offset = seeded_index(day, count as u64);
+    let sql = format!(
+        "SELECT {} FROM verses v {} ORDER BY CAST({} AS INTEGER), v.chapter, {} LIMIT 1 OFFSET ?",
+        bible.schema.verse_columns(),
+        bible.schema.books_join(),
+        bible.schema.verse_book(),
+        bible.schema.verse_start()
+    );
+    let mut stmt = bible.conn.prepare(&sql)?;
+    let mut rows = stmt.query_map([offset as i64], Verse::from_row)?;
+    rows.next().transpose()
+}
+
+/// How many verses a search's WHERE clause matches, bound to the same parameters as the
+/// search query itself so the total agrees with the pages fetched.
+fn count_verses(
+    conn: &Connection,
+    schema: &BibleSchema,
+    where_clause: &str,
+    params: &[String],
+) -> Result<usize, RusqliteError> {
+    let sql = format!("SELECT COUNT(*) FROM verses v {} WHERE {}", schema.books_join(), where_clause);
+    let count: i64 = prepare_search_query(conn, &sql)?.query_row(params_from_iter(params.iter()), |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+/// A count with thousands separators, e.g. "4,312".
+fn group_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Every verse of the module in canonical order, for matching phrases across verse boundaries.
+fn load_all_verses(bible: &Bible) -> Result<Vec<Verse>, RusqliteError> {
+    let sql = format!(
+        "SELECT {columns} \
+         FROM verses v {join} \
+         ORDER BY CAST({book} AS INTEGER), v.chapter, {verse}",
+        columns = bible.schema.verse_columns(),
+        book = bible.schema.verse_book(),
+        join = bible.schema.books_join(),
+        verse = bible.schema.verse_start()
+    );
+    let mut stmt = bible.conn.prepare(&sql)?;
+    let verses = stmt
+        .query_map([], Verse::from_row)?
+        .filter_map(|v| v.ok())
+        .collect();
+    Ok(verses)
+}
+
+/// The verses where one of `phrases` starts and runs on into the next verse (see
+/// `cross_verse_matches`), read on a connection from `connections` like a search page.
+fn find_cross_verse_matches(path: &Path, connections: &BibleCache, phrases: &[String]) -> Result<Vec<Verse>, String> {
+    let idle = connections.lock().unwrap_or_else(PoisonError::into_inner).remove(path);
+    let bible = idle.map_or_else(|| open_bible(path), Ok).map_err(|e| e.to_string())?;
+    let all = load_all_verses(&bible);
+    connections.lock().unwrap_or_else(PoisonError::into_inner).insert(path.to_path_buf(), bible);
+    let all = all.map_err(|e| e.to_string())?;
+    let starts: BTreeSet<usize> = phrases.iter().flat_map(|phrase| cross_verse_matches(&all, phrase)).collect();
+    Ok(all
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| starts.contains(idx))
+        .map(|(_, verse)| verse)
+        .collect())
+}
+
+/// Drop the verse number some modules store at the start of each verse's text.
+fn strip_verse_numbers(verses: &mut [Verse]) {
+    for verse in verses {
+        let stripped = strip_leading_number(verse.verse, &verse.text);
+        if stripped.len() != verse.text.len() {
+            verse.text = stripped.to_string();
+        }
+    }
+}
+
+/// The known numbering schemes a module follows, recognized by their marker verses.
+fn detect_versifications(bible: &Bible) -> Vec<Versification> {
+    let sql = format!(
+        "SELECT EXISTS(SELECT 1 FROM verses v WHERE CAST({} AS INTEGER) = ? AND v.chapter = ? AND {} = ?)",
+        bible.schema.verse_book(),
+        bible.schema.verse_start()
+    );
+    VERSIFICATIONS
+        .into_iter()
+        .filter(|scheme| {
+            let (book, chapter, verse) = scheme.marker;
+            bible
+                .conn
+                .query_row(&sql, params![book, chapter, verse], |row| row.get(0))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Renumber verses from `schemes` to canonical numbering and keep those in `start..=end`,
+/// in canonical order. Verses a scheme splits where the canonical one doesn't (3 John 1:15
+/// is part of 1:14) land on the same number; their texts are joined into one verse.
+fn canonical_verses(verses: Vec<Verse>, schemes: &[Versification], start: VerseKey, end: VerseKey) -> Vec<Verse> {
+    let mut mapped: Vec<Verse> = verses
+        .into_iter()
+        .map(|v| {
+            let (chapter, verse) = to_canonical(schemes, v.book_number, v.chapter, v.verse);
+            let verse_end = v.verse_end.map(|last| last - v.verse + verse);
+            Verse { chapter, verse, verse_end, ..v }
+        })
+        .filter(|v| (start..=end).contains(&(v.chapter, v.verse)))
+        .collect();
+    mapped.sort_by_key(|v| (v.chapter, v.verse));
+    mapped.dedup_by(|later, earlier| {
+        if (later.chapter, later.verse) != (earlier.chapter, earlier.verse) {
+            return false;
+        }
+        earlier.text.push(' ');
+        earlier.text.push_str(&later.text);
+        earlier.verse_end = earlier.verse_end.max(later.verse_end);
+        true
+    });
+    mapped
+}
+
+/// How compare columns are read from each module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColumnOptions {
+    /// Renumber verses of modules following another versification (see `compare_passage`).
+    normalize: bool,
+    /// Whether leading verse numbers are stripped; `Auto` checks each module on its own.
+    leading: LeadingNumbers,
+}
+
+/// Run the compare query for one reference range across the opened modules.
+/// With `normalize`, modules following a known different numbering scheme are read a
+/// chapter wider on each side (a shifted verse is never further from its canonical place)
+/// and renumbered, so equivalent verses line up in the compare grid.
+fn compare_passage(
+    bibles: &[(String, &Bible)],
+    book: &str,
+    start_ch: u32,
+    start_v: u32,
+    end_ch: u32,
+    end_v: u32,
+    options: ColumnOptions,
+) -> CompareResults {
+    let ColumnOptions { normalize, leading } = options;
+    bibles
+        .iter()
+        .filter_map(|(bible_name, bible)| {
+            let schemes = if normalize { detect_versifications(bible) } else { Vec::new() };
+            let mut verses = if schemes.is_empty() {
+                query_passage(bible, bible_name, book, start_ch, start_v, end_ch, end_v).ok()?
+            } else {
+                let wide_start = start_ch.saturating_sub(1).max(1);
+                let wide = query_passage(bible, bible_name, book, wide_start, 1, end_ch.saturating_add(1), CHAPTER_END).ok()?;
+                canonical_verses(wide, &schemes, (start_ch, start_v), (end_ch, end_v))
+            };
+            // Each module is checked for leading verse numbers on its own.
+            let detected = leading == LeadingNumbers::Auto && detect_leading_numbers(&bible.conn).unwrap_or(false);
+            if leading.strips(detected) {
+                strip_verse_numbers(&mut verses);
+            }
+            println!("Bible '{}' (file {:?}) returned {} verses", bible_name, bible.path, verses.len());
+            Some((bible_name.clone(), verses))
+        })
+        .collect()
+}
+
+/// References per batched baseline query (three parameters each, within SQLite's 999 limit).
+const BASELINE_BATCH_SIZE: usize = 300;
+
+/// Fetch many verses from a module in a few batched queries, keyed by reference.
+/// References the module doesn't have are simply absent from the map.
+fn fetch_verses_batched(bible: &Bible, refs: &[VerseRef]) -> Result<HashMap<VerseRef, String>, RusqliteError> {
+    let mut texts = HashMap::new();
+    for chunk in refs.chunks(BASELINE_BATCH_SIZE) {
+        let sql = format!(
+            "SELECT CAST({book} AS INTEGER), v.chapter, v.verse, v.text FROM verses v \
+             WHERE (CAST({book} AS INTEGER), v.chapter, v.verse) IN (VALUES {values})",
+            book = bible.schema.verse_book(),
+            values = vec!["(?, ?, ?)"; chunk.len()].join(", ")
+        );
+        let params: Vec<u32> = chunk.iter().flat_map(|&(b, c, v)| [b, c, v]).collect();
+        let mut stmt = bible.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+            Ok(((row.get(0)?, row.get(1)?, row.get(2)?), row.get::<_, String>(3)?))
+        })?;
+        texts.extend(rows.filter_map(|r| r.ok()));
+    }
+    Ok(texts)
+}
+
+/// The baseline module's renderings of `refs`, read through the module cache so a module
+/// is opened (and a compressed one unpacked) only once. Leading verse numbers are stripped
+/// when `leading` says so for that module, as they are in the compare columns.
+fn fetch_baseline_texts(
+    cache: &BibleCache,
+    module: &DiscoveredModule,
+    refs: &[VerseRef],
+    leading: LeadingNumbers,
+) -> Result<HashMap<VerseRef, String>, String> {
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+    let bible = match cache.entry(module.path.clone()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(open_bible(&module.path).map_err(|e| e.to_string())?),
+    };
+    let mut texts = fetch_verses_batched(bible, refs).map_err(|e| e.to_string())?;
+    let detected = leading == LeadingNumbers::Auto && detect_leading_numbers(&bible.conn).unwrap_or(false);
+    if leading.strips(detected) {
+        for (&(_, _, verse), text) in texts.iter_mut() {
+            let stripped = strip_leading_number(verse, text);
+            if stripped.len() != text.len() {
+                *text = stripped.to_string();
+            }
+        }
+    }
+    Ok(texts)
+}
+
+/// Bookmarks per page of the bookmark comparison report.
+const BOOKMARK_REPORT_PAGE_SIZE: usize = 10;
+
 /// -------------------------------
 /// Application State and Combined UI
 /// -------------------------------
 
+/// The last submitted search, kept apart from the (possibly edited) inputs so it can be repeated.
+#[derive(Debug, Clone, PartialEq)]
+struct LastSearch {
+    mode: SearchMode,
+    input: String,
+    lines: Vec<String>,
+    word_count: (WordCountOp, String),
+}
+
+/// The last search or lookup, as summarized in the window title.
+#[derive(Debug, Clone, PartialEq)]
+enum RecentActivity {
+    Search { query: String, matches: usize },
+    Lookup { reference: String, verses: usize },
+}
+
+/// Fallback window title when no translation is loaded and nothing has been looked up.
+const DEFAULT_TITLE: &str = "Bible Verse Lookup – Search, Lookup & Compare";
+
+/// The window title: the loaded translation and the last action, e.g.
+/// "KJV — 'faith' (142 matches)", or the default title when there is neither.
+fn window_title(translation: Option<&str>, activity: Option<&RecentActivity>) -> String {
+    let activity = activity.map(|activity| match activity {
+        RecentActivity::Search { query, matches } => {
+            format!("'{}' ({} {})", query, matches, if *matches == 1 { "match" } else { "matches" })
+        }
+        RecentActivity::Lookup { reference, verses } => {
+            format!("{} ({} {})", reference, verses, if *verses == 1 { "verse" } else { "verses" })
+        }
+    });
+    match (translation, activity) {
+        (Some(translation), Some(activity)) => format!("{} — {}", translation, activity),
+        (None, Some(activity)) => activity,
+        (Some(translation), None) => format!("{} — {}", translation, DEFAULT_TITLE),
+        (None, None) => DEFAULT_TITLE.to_string(),
+    }
+}
+
+/// "Taller"/"Shorter" buttons giving a results panel more or less of the window.
+fn panel_size_buttons<'a>(panel: ResultsPanel) -> Row<'a, Message> {
+    Row::new()
+        .spacing(5)
+        .push(button(text("Taller").size(12)).on_press(Message::PanelResized(panel, true)).padding(3))
+        .push(button(text("Shorter").size(12)).on_press(Message::PanelResized(panel, false)).padding(3))
+}
+
+/// Keyboard shortcuts: Ctrl+R repeats the last search, Ctrl+L the last lookup,
+/// Ctrl+M switches to the next search mode (those three only while no input has taken the
+/// key, so they stay inert while typing), Ctrl+F focuses the search input and
+/// Escape clears the input being typed in (a focused input captures the key, so an
+/// uncaptured Escape has nothing to clear). Window resizes and moves are passed on to size
+/// the results panels and to be remembered when the window is closed.
+fn hotkey(event: Event, status: event::Status) -> Option<Message> {
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) if modifiers.control() => {
+            let typing = status == event::Status::Captured;
+            match key_code {
+                keyboard::KeyCode::R if !typing => Some(Message::RepeatLastSearch),
+                keyboard::KeyCode::L if !typing => Some(Message::RepeatLastLookup),
+                keyboard::KeyCode::M if !typing => Some(Message::CycleSearchMode),
+                keyboard::KeyCode::F => Some(Message::FocusSearch),
+                _ => None,
+            }
+        }
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code: keyboard::KeyCode::Escape, .. })
+            if status == event::Status::Captured =>
+        {
+            Some(Message::EscapePressed)
+        }
+        Event::Window(window::Event::Resized { width, height }) => Some(Message::WindowResized(width, height)),
+        Event::Window(window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+        Event::Window(window::Event::CloseRequested) => Some(Message::WindowCloseRequested),
+        _ => None,
+    }
+}
+
+/// Shortcut summary shown under the quick bar.
+const SHORTCUT_HINT: &str =
+    "Ctrl+F search box · Ctrl+R repeat search · Ctrl+L repeat lookup · Ctrl+M next search mode · Esc clear input";
+
+/// A text input Escape can clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClearableInput {
+    /// The advanced search input of the current mode (the first line in line-per-phrase mode).
+    Search,
+    SearchLine(usize),
+    Lookup,
+    Quick,
+}
+
+/// The advanced search input (the first line in line-per-phrase mode), for Ctrl+F.
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("search_input")
+}
+
+fn lookup_input_id() -> text_input::Id {
+    text_input::Id::new("lookup_input")
+}
+
+fn quick_input_id() -> text_input::Id {
+    text_input::Id::new("quick_input")
+}
+
+/// Choices offered for the minimum search term length.
+const MIN_TERM_LEN_CHOICES: [usize; 5] = [1, 2, 3, 4, 5];
+
+/// Most-searched terms offered as quick-access buttons.
+const FREQUENT_TERMS_SHOWN: usize = 8;
+
+/// Choices offered for the number of highlighted matches per verse.
+const MAX_HIGHLIGHT_CHOICES: [usize; 4] = [10, 50, 100, 500];
+
 struct App {
+    // Quick bar: one input routed to search or lookup, the override, and where the last query went
+    quick_input: String,
+    quick_route: QueryRoute,
+    last_route: Option<QueryRoute>,
+    // The input last typed in (or focused with Ctrl+F), for Escape to clear: iced unfocuses
+    // a text input on Escape before the app sees the key, so it can't ask which one had focus
+    editing: Option<ClearableInput>,
     // Advanced search state
     search_input: String,
     search_results: Vec<Verse>,
+    // Highlight segments of the search results, reused across view() calls
+    search_segments: SegmentCache,
+    // Highlight segments of the compare results
+    compare_segments: SegmentCache,
+    search_options: SearchOptions,
+    // Lines mode: each line is a phrase, ORed with the others
+    search_mode: SearchMode,
+    search_lines: Vec<String>,
+    // Lines mode: also match phrases that run across a verse boundary (reads the whole module)
+    cross_verse_phrases: bool,
+    // Word-count mode: the comparison and the typed number of words
+    word_count_op: WordCountOp,
+    word_count_input: String,
+    // Words-of-Christ markup used by the main module, if any
+    red_letter_markup: Option<RedLetterMarkup>,
+    // Whether the main module stores verse numbers at the start of its verse texts
+    leading_numbers_detected: bool,
+    // Restrict search matches to the words of Christ (only offered when markup is present)
+    red_letter_only: bool,
+    // Render the words of Christ in red (only offered when markup is present)
+    red_letter_render: bool,
+    // Whether the main module tags words with Strong's numbers, and whether they are shown
+    has_strongs: bool,
+    show_strongs: bool,
+    // Show verse text exactly as stored, markup tags included, instead of cleaned and styled
+    raw_markup: bool,
+    // Modules found in the compare directory at startup, offered as baselines
+    available_modules: Vec<DiscoveredModule>,
+    // Translation each search hit is checked against, and the same/different flags of the hits checked so far
+    baseline: Option<DiscoveredModule>,
+    baseline_flags: HashMap<VerseRef, Option<bool>>,
+    // Bumped when the flags are cleared, so a fetch for earlier results or another baseline is dropped
+    baseline_generation: u64,
+    // List only the chapters containing matches, with their match counts
+    chapters_only: bool,
+    // Show search results under per-chapter headers, and which chapters are collapsed
+    group_by_chapter: bool,
+    collapsed_chapters: BTreeSet<(String, u32)>,
+    // Remarks from building the last search query (e.g. ignored short terms)
+    search_notes: Vec<String>,
+    // Why the last search or lookup query failed, shown in place of its results
+    search_error: Option<String>,
+    // The search whose results are still streaming in, and the id given to the latest search
+    streaming_search: Option<StreamingSearch>,
+    search_generation: u64,
+    // Background connections to the main module, kept between search pages
+    search_connections: BibleCache,
+    // The part of the Bible advanced search looks in
+    scope: SearchScope,
+    // Rows per page of search results; the last page requested, for "Load more"
+    search_page_size: usize,
+    search_page: Option<StreamingSearch>,
+    // Rows the page being fetched returned so far (before red-letter filtering), and
+    // whether the last page came back full, so more matches may follow
+    search_page_rows: usize,
+    search_has_more: bool,
+    // Total verses the last search matches, counted before fetching its first page
+    search_total: Option<usize>,
+    lookup_error: Option<String>,
+    // Format used by "Copy all results"
+    export_format: ExportFormat,
+    // Last submitted search, for "Repeat last search"
+    last_search: Option<LastSearch>,
+    // How often each term has been searched, persisted to the config directory
+    term_frequency: TermFrequency,
+    // Recent searches and lookups, persisted to the config directory
+    history: History,
     // Lookup state
     lookup_input: String,
+    // Last submitted lookup reference, for "Repeat last lookup"
+    last_lookup: Option<String>,
     lookup_results: Vec<Verse>,
+    // Today's verse from the main module, shown above the search section
+    verse_of_day: Option<Verse>,
+    // Remarks from the last lookup (e.g. skipped references)
+    lookup_notes: Vec<String>,
+    // Size of verse text and result headers, kept for the session
+    font_size: u16,
+    // Keep the line breaks in lookup and search verse texts instead of collapsing them (poetry layout)
+    poetry_layout: bool,
+    // Books of the main module, for autocomplete
+    books: Vec<Book>,
+    // First-verse previews keyed by (book short name, chapter); None when the chapter doesn't exist
+    preview_cache: HashMap<(String, u32), Option<(u32, String)>>,
+    // The (book, chapter) the preview currently shows, so unchanged prefixes don't re-query
+    preview_key: Option<(String, u32)>,
+    // Bumped on every lookup keystroke; only the latest pending preview refresh runs
+    preview_generation: u64,
+    // Chapter extents for "Surprise me", loaded on first use
+    book_extents: Option<Vec<BookExtent>>,
+    passage_weighting: PassageWeighting,
+    // Book/chapter navigator sidebar, built on first open; whether it is open lives in settings
+    navigator: Option<Vec<NavBook>>,
+    expanded_books: BTreeSet<String>,
+    // Bookmarked verses, persisted to the config directory
+    bookmarks: Vec<Bookmark>,
+    // File the "Import references" action reads, and the lines its last run skipped with why
+    import_path: String,
+    import_skipped: Vec<String>,
+    // Size of the window and how the results panels share its height (search, lookup,
+    // compare); the weights are saved as they change, the size and position to reopen with on close
+    window_size: (u32, u32),
+    panel_weights: [u16; 3],
+    // File "Export" writes results to; empty for the export format's default file
+    export_path: String,
+    // An existing file the user was warned about; exporting to it again overwrites it
+    export_overwrite: Option<String>,
+    // Bookmark comparison report: the current page's bookmarks, each across the compare set
+    bookmark_report: Option<Vec<(Bookmark, CompareResults)>>,
+    bookmark_report_page: usize,
     // Compare state: vector of (Bible description, verses) from each Bible database file.
-    compare_results: Vec<(String, Vec<Verse>)>,
-    // Shared database connection (for advanced search and lookup)
-    db: Connection,
+    compare_results: CompareResults,
+    // Verses pinned from the compare results into the focused grid
+    pinned_verses: BTreeSet<VerseKey>,
+    // How duplicate Bible files found during the compare scan are merged.
+    compare_dedup: DedupMode,
+    // Modules found by the last compare directory scan; None until scanned or after a rescan request
+    discovered_modules: Option<Vec<DiscoveredModule>>,
+    // Bumped whenever the scan is discarded, so a comparison started before that doesn't cache its stale scan
+    module_scan_generation: u64,
+    // Connections to the compare modules, opened on first use
+    bible_cache: BibleCache,
+    // Reference the comparison shows, and whether it is pinned against lookup navigation
+    compare_reference: Option<String>,
+    compare_pinned: bool,
+    // A comparison is running in the background; a reference requested meanwhile waits here
+    comparing: bool,
+    queued_compare: Option<String>,
+    // Gray out compared verses that read the same in every translation
+    dim_identical: bool,
+    // Align compared verses on canonical (KJV) numbering across versification schemes
+    normalize_versification: bool,
+    // Whether compared translations are shown verse per line or as paragraphs
+    compare_layout: CellLayout,
+    // Format of the review sheet generated from the comparison
+    review_format: ReviewFormat,
+    // The two compared translations (by column label) shown as a word diff
+    diff_first: Option<String>,
+    diff_second: Option<String>,
+    // Shared database connection (for advanced search and lookup); None if it failed to open
+    db: Option<Bible>,
+    // Directory holding the main Bible and the compare modules, and its input box
+    db_dir: PathBuf,
+    db_dir_input: String,
+    // The main translation's label, and the last search or lookup, for the window title
+    main_label: Option<String>,
+    recent_activity: Option<RecentActivity>,
+    // Settings remembered between runs
+    settings: AppSettings,
+    // Status line shown above the panels (e.g. why no Bible is loaded)
+    status: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-enum Message {
-    // Advanced search messages
-    SearchChanged(String),
-    SearchSubmitted,
-    // Lookup messages
-    LookupChanged(String),
-    LookupSubmitted,
-    // Compare messages
-    CompareSubmitted,
-}
+#[derive(Debug, Clone)]
+enum Message {
+    // Quick bar messages
+    QuickInputChanged(String),
+    QuickRouteChanged(QueryRoute),
+    QuickSubmitted,
+    UnifiedResultsToggled(bool),
+    // With one result area, show the search (Search) or lookup (Lookup) panel
+    ResultsTabChosen(QueryRoute),
+    ContentWidthChanged(ContentWidth),
+    ContextVersesChanged(u32),
+    ScopeChanged(SearchScope),
+    // Books left out of every search
+    BookExcluded(Book),
+    BookIncluded(u32),
+    // Open a search result with its surrounding verses in the lookup panel
+    ShowContext(usize),
+    // Advanced search messages
+    SearchChanged(String),
+    FrequentTermChosen(String),
+    SearchSubmitted,
+    SearchBatch(u64, Vec<Verse>),
+    LoadMore,
+    SearchPageSizeChanged(usize),
+    SearchCounted(u64, usize),
+    SearchDone(u64, Option<String>),
+    // Verses where a phrase of the search runs on into the next verse, or why they couldn't be looked for
+    CrossVerseMatched(u64, Result<Vec<Verse>, String>),
+    // The baseline's renderings of the requested hits (generation, refs), or why they couldn't be read
+    BaselineFetched(u64, Vec<VerseRef>, Result<HashMap<VerseRef, String>, String>),
+    RepeatLastSearch,
+    MinTermLenChanged(usize),
+    MatchPositionChanged(MatchPosition),
+    CaseSensitivityToggled(bool),
+    WholeWordToggled(bool),
+    MaxHighlightsChanged(usize),
+    NotSemanticsChanged(NotSemantics),
+    SearchHelpToggled,
+    SearchModeChanged(SearchMode),
+    CycleSearchMode,
+    DefaultSearchModeChanged(DefaultSearchMode),
+    RedLetterOnlyToggled(bool),
+    RedLetterRenderToggled(bool),
+    StrongsToggled(bool),
+    RawMarkupToggled(bool),
+    ExportFormatChanged(ExportFormat),
+    // Copy the search (false) or lookup (true) results, up to the copy limit
+    CopyAllResults(bool),
+    CopyLimitChanged(usize),
+    ExportPathChanged(String),
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    // Remember the window's size and position, then close it
+    WindowCloseRequested,
+    // Make a results panel taller (true) or shorter (false)
+    PanelResized(ResultsPanel, bool),
+    // Write the search (false) or lookup (true) results to the export file
+    ExportResults(bool),
+    TwoColumnResultsToggled(bool),
+    ZebraStripesToggled(bool),
+    ThemeToggled,
+    FontSizeChanged(u16),
+    AbbreviateReferencesToggled(bool),
+    ShowRawTextToggled(bool),
+    GroupByChapterToggled(bool),
+    ChaptersOnlyToggled(bool),
+    // Open a chapter from the chapters-only view in the lookup panel
+    MatchingChapterChosen(String, u32),
+    ChapterGroupToggled((String, u32)),
+    // Collapse (true) or expand (false) every chapter group
+    AllChapterGroupsCollapsed(bool),
+    BaselineChanged(DiscoveredModule),
+    BaselineCleared,
+    SearchLineChanged(usize, String),
+    CrossVersePhrasesToggled(bool),
+    WordCountOpChanged(WordCountOp),
+    WordCountChanged(String),
+    // Insert an empty line after the given line (Enter in a multi-line search line)
+    SearchLineAdded(usize),
+    SearchLineRemoved(usize),
+    // Lookup messages
+    LookupChanged(String),
+    // The lookup input has been still for `PREVIEW_DEBOUNCE` since this keystroke
+    PreviewDue(u64),
+    LookupSubmitted,
+    RepeatLastLookup,
+    FocusSearch,
+    EscapePressed,
+    HistoryChosen(HistoryEntry),
+    NextChapter,
+    PrevChapter,
+    PoetryLayoutToggled(bool),
+    LeadingNumbersChanged(LeadingNumbers),
+    BookSuggestionChosen(String),
+    SurpriseMe,
+    PassageWeightingChanged(PassageWeighting),
+    // Navigator messages
+    NavigatorToggled,
+    NavigatorBookToggled(String),
+    NavigateChapter(String, u32, u32),
+    // Bookmark messages
+    BookmarkToggled(Bookmark),
+    // Copy a lookup result (by index) with a biblical_gui:// link that reopens it
+    CopyVerseLink(usize),
+    ExportBookmarks,
+    ImportPathChanged(String),
+    ImportReferences,
+    CompareBookmarks,
+    BookmarkReportPage(usize),
+    // A report page, with the compare directory scan it used (see `CompareCompleted`)
+    BookmarkReportLoaded(usize, u64, Vec<DiscoveredModule>, Vec<(Bookmark, CompareResults)>),
+    CloseBookmarkReport,
+    // Compare messages
+    CompareSubmitted,
+    CompareCompleted(u64, Vec<DiscoveredModule>, CompareResults),
+    CompareReferencePinToggled(bool),
+    CompareDedupChanged(DedupMode),
+    CompareSubdirectoriesToggled(bool),
+    // Re-read the compare directory without running a comparison
+    RescanModules,
+    DbDirChanged(String),
+    DbDirSubmitted,
+    CompareVersePinToggled(VerseKey),
+    DimIdenticalToggled(bool),
+    NormalizeVersificationToggled(bool),
+    CompareLayoutChanged(CellLayout),
+    ReviewFormatChanged(ReviewFormat),
+    GenerateReviewSheet,
+    DiffFirstChosen(String),
+    DiffSecondChosen(String),
+    ClearPinnedVerses,
+}
+
+impl App {
+    /// Put a search or lookup at the top of the history and save it.
+    fn record_history(&mut self, entry: HistoryEntry) {
+        self.history.record(entry);
+        if let Some(path) = history_path() {
+            if let Err(e) = save_history(&path, &self.history) {
+                self.status = Some(format!("Could not save history: {}", e));
+            }
+        }
+    }
+
+    /// Write the bookmarks to the config directory, reporting a failure in the status line.
+    fn save_bookmarks(&mut self) {
+        if let Some(path) = bookmarks_path() {
+            if let Err(e) = save_bookmarks(&path, &self.bookmarks) {
+                self.status = Some(format!("Could not save bookmarks: {}", e));
+            }
+        }
+    }
+
+    /// The highlight style for the active theme.
+    fn highlight_style(&self) -> HighlightText {
+        HighlightText { dark: self.settings.dark_theme }
+    }
+
+    /// The module's short name for a book given by short or long name (see `find_book`).
+    fn canonical_book(&self, name: &str) -> Option<String> {
+        find_book(&self.books, name).map(|b| b.short_name.clone())
+    }
+
+    /// Fetch the baseline badges of the search results that have none yet, in the
+    /// background; `BaselineFetched` brings the baseline's renderings back.
+    fn refresh_baseline_flags(&self) -> Command<Message> {
+        let Some(module) = self.baseline.clone() else {
+            return Command::none();
+        };
+        let refs: Vec<VerseRef> = self
+            .search_results
+            .iter()
+            .map(|v| (v.book_number, v.chapter, v.verse))
+            .filter(|verse_ref| !self.baseline_flags.contains_key(verse_ref))
+            .collect();
+        if refs.is_empty() {
+            return Command::none();
+        }
+        let cache = Arc::clone(&self.bible_cache);
+        let leading = self.settings.leading_numbers;
+        let generation = self.baseline_generation;
+        Command::perform(
+            async move {
+                let texts = fetch_baseline_texts(&cache, &module, &refs, leading);
+                (refs, texts)
+            },
+            move |(refs, texts)| Message::BaselineFetched(generation, refs, texts),
+        )
+    }
+
+    /// Forget the baseline badges, dropping any fetch still under way.
+    fn clear_baseline_flags(&mut self) {
+        self.baseline_flags.clear();
+        self.baseline_generation += 1;
+    }
+
+    /// Persist the settings, reporting a failure in the status line.
+    fn save_settings(&mut self) {
+        if let Some(path) = settings_path() {
+            if let Err(e) = save_settings(&path, &self.settings) {
+                self.status = Some(format!("Could not save settings: {}", e));
+            }
+        }
+    }
+
+    /// The phrases whose matches across a verse boundary are looked for: the lines of a
+    /// Lines search, or the quoted phrases of a plain query made of nothing else (several
+    /// joined by OR), where a verse found across a boundary can't be missing another term.
+    fn cross_verse_candidates(&self) -> Vec<String> {
+        match self.search_mode {
+            SearchMode::Lines => search_line_phrases(&self.search_lines).into_iter().map(str::to_string).collect(),
+            SearchMode::Plain => {
+                let tokens = tokenize_query(&self.search_input);
+                let query = parse_query(&tokens);
+                let mut phrases = Vec::new();
+                for member in &query.members {
+                    match member {
+                        QueryNode::Term { negated: false, text } => match unquote(text) {
+                            (phrase, true) if !phrase.trim().is_empty() => phrases.push(phrase.to_string()),
+                            _ => return Vec::new(),
+                        },
+                        _ => return Vec::new(),
+                    }
+                }
+                if phrases.len() > 1 && query.operator != "OR" {
+                    return Vec::new();
+                }
+                phrases
+            }
+            SearchMode::WordCount | SearchMode::Regex => Vec::new(),
+        }
+    }
+
+    /// Add the verses a search's phrases run on from into the next verse, once the
+    /// background pass finds them, unless a newer search has started meanwhile.
+    fn add_cross_verse_matches(&mut self, id: u64, matches: Result<Vec<Verse>, String>) -> Command<Message> {
+        if id != self.search_generation {
+            return Command::none();
+        }
+        let matches = match matches {
+            Ok(matches) => matches,
+            Err(e) => {
+                self.search_notes.push(format!("Could not check across verse boundaries: {}", e));
+                return Command::none();
+            }
+        };
+        let found: BTreeSet<VerseRef> = self
+            .search_results
+            .iter()
+            .map(|v| (v.book_number, v.chapter, v.verse))
+            .collect();
+        let before = self.search_results.len();
+        self.accept_search_batch(
+            matches
+                .into_iter()
+                .filter(|v| !found.contains(&(v.book_number, v.chapter, v.verse)))
+                .collect(),
+        );
+        let added = self.search_results.len() - before;
+        if added > 0 {
+            self.search_results.sort_by_key(|v| (v.book_number, v.chapter, v.verse));
+            self.search_notes.push(format!(
+                "{} phrase matches run across a verse boundary (listed at the verse where they start)",
+                added
+            ));
+            if let Some(RecentActivity::Search { matches, .. }) = &mut self.recent_activity {
+                *matches = self.search_results.len();
+            }
+            return self.refresh_baseline_flags();
+        }
+        Command::none()
+    }
+
+    /// Compare `reference` across the modules in the compare directory, replacing the
+    /// current comparison.
+    ///
+    /// The directory scan and the per-module queries run off the UI thread; the results
+    /// arrive as `CompareCompleted`. A request made while a comparison is running waits
+    /// for it, and only the latest such request is run next.
+    fn run_compare(&mut self, reference: String) -> Command<Message> {
+        if self.comparing {
+            self.queued_compare = Some(reference);
+            return Command::none();
+        }
+        self.pinned_verses.clear();
+        let Some((book, start_ch, start_v, end_ch, end_v)) = parse_lookup(&reference) else {
+            println!("Failed to parse lookup input for compare: {}", reference);
+            self.compare_results.clear();
+            self.compare_reference = Some(reference);
+            return Command::none();
+        };
+        if is_reversed_range(start_ch, start_v, end_ch, end_v) {
+            self.status = Some(LookupError::ReversedRange(reference.clone()).to_string());
+            self.compare_results.clear();
+            self.compare_reference = Some(reference);
+            return Command::none();
+        }
+        let book = self.canonical_book(&book).unwrap_or(book);
+        self.compare_reference = Some(reference);
+        if !self.db_dir.is_dir() {
+            self.status = Some(format!("Bible directory {} does not exist", self.db_dir.display()));
+            self.compare_results.clear();
+            return Command::none();
+        }
+        self.comparing = true;
+        let dir = self.db_dir.clone();
+        let discovered = self.discovered_modules.clone();
+        let generation = self.module_scan_generation;
+        let recursive = self.settings.compare_subdirectories;
+        let dedup = self.compare_dedup;
+        let cache = Arc::clone(&self.bible_cache);
+        let column_options = self.column_options();
+        // The main Bible is already open here; query it now instead of opening it again in the background.
+        let main = self.db.as_ref().map(|db| {
+            let column = compare_passage(&[(String::new(), db)], &book, start_ch, start_v, end_ch, end_v, column_options);
+            (canonical_path(&db.path), column.into_iter().next().map(|(_, verses)| verses).unwrap_or_default())
+        });
+        Command::perform(
+            async move {
+                let started = Instant::now();
+                let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+                let modules = discovered.unwrap_or_else(|| discover_modules(&dir, recursive, dedup, &mut cache));
+                let others = modules_besides(&modules, main.as_ref().map(|(path, _)| path.as_path()));
+                let bibles = open_modules(&others, &mut cache);
+                let mut results = compare_passage(&bibles, &book, start_ch, start_v, end_ch, end_v, column_options);
+                if let Some((path, verses)) = main {
+                    insert_main_column(&mut results, &modules, &path, verses);
+                }
+                println!("Comparison of {} Bibles took {:?}", results.len(), started.elapsed());
+                (modules, results)
+            },
+            move |(modules, results)| Message::CompareCompleted(generation, modules, results),
+        )
+    }
+
+    /// Keep a compare directory scan made in the background for later comparisons, unless
+    /// a rescan requested meanwhile may have changed the options (`generation` is stale).
+    fn keep_module_scan(&mut self, generation: u64, modules: Vec<DiscoveredModule>) {
+        if generation == self.module_scan_generation && self.discovered_modules.is_none() {
+            self.discovered_modules = Some(modules);
+        }
+    }
+
+    /// Forget the compare directory scan; the next comparison scans again.
+    fn discard_module_scan(&mut self) {
+        self.discovered_modules = None;
+        self.module_scan_generation += 1;
+    }
+
+    /// Switch the main Bible, reloading what was read from the previous one.
+    fn set_main_bible(&mut self, bible: Bible) {
+        self.books = load_books(&bible).unwrap_or_default();
+        // A book chosen from the previous module may not exist in this one.
+        self.scope = SearchScope::All;
+        self.main_label = Some(translation_label(bible_description(&bible.conn), &bible.path));
+        self.red_letter_markup = detect_red_letter(&bible.conn).ok().flatten();
+        self.has_strongs = detect_strongs(&bible.conn).unwrap_or(false);
+        self.leading_numbers_detected = detect_leading_numbers(&bible.conn).unwrap_or(false);
+        self.book_extents = None;
+        self.preview_cache.clear();
+        self.preview_key = None;
+        // A status saying no Bible is loaded no longer holds.
+        if self.db.is_none() {
+            self.status = None;
+        }
+        self.db = Some(bible);
+        if self.navigator.take().is_some() || self.settings.navigator_open {
+            self.load_navigator();
+        }
+        self.pick_verse_of_day();
+    }
+
+    /// Pick today's verse from the main module; none when it has no verses.
+    fn pick_verse_of_day(&mut self) {
+        let mut verse = self.db.as_ref().and_then(|db| verse_of_the_day(db, days_since_epoch()).ok().flatten());
+        if self.strips_verse_numbers() {
+            strip_verse_numbers(verse.as_mut_slice());
+        }
+        self.verse_of_day = verse;
+    }
+
+    /// The height of a results panel: its weighted share of the window, counting only the
+    /// panels on screen, and never below `MIN_PANEL_HEIGHT`.
+    fn panel_height(&self, panel: ResultsPanel) -> Length {
+        let shown: u16 = [ResultsPanel::Search, ResultsPanel::Lookup, ResultsPanel::Compare]
+            .into_iter()
+            .filter(|p| match (self.settings.unified_results, self.last_route, *p) {
+                (true, Some(QueryRoute::Lookup), ResultsPanel::Search) => false,
+                (true, route, ResultsPanel::Lookup) => route == Some(QueryRoute::Lookup),
+                _ => true,
+            })
+            .map(|p| self.panel_weights[p.index()])
+            .sum();
+        let share = self.window_size.1 as f32 * RESULTS_HEIGHT_SHARE * f32::from(self.panel_weights[panel.index()])
+            / f32::from(shown.max(1));
+        Length::Fixed(share.max(MIN_PANEL_HEIGHT))
+    }
+
+    /// Build the navigator tree on first use; it is kept for the rest of the session.
+    fn load_navigator(&mut self) {
+        if self.navigator.is_some() {
+            return;
+        }
+        if let Some(db) = self.db.as_ref() {
+            if self.book_extents.is_none() {
+                self.book_extents = load_book_extents(db).ok();
+            }
+            let extents = self.book_extents.as_deref().unwrap_or_default();
+            self.navigator = Some(build_navigator(&self.books, extents));
+        }
+    }
+
+    /// The literal terms of the current search (query terms, or one phrase per line), for
+    /// highlighting and red-letter filtering.
+    fn search_terms(&self) -> Vec<&str> {
+        match self.search_mode {
+            SearchMode::Plain => highlight_terms(&self.search_input, &self.search_options),
+            SearchMode::Lines => search_line_phrases(&self.search_lines),
+            SearchMode::WordCount | SearchMode::Regex => Vec::new(),
+        }
+    }
+
+    /// The last verse of a chapter, loading the book extents on first use.
+    fn chapter_last_verse(&mut self, book: &str, chapter: u32) -> Option<u32> {
+        if let (None, Some(db)) = (&self.book_extents, self.db.as_ref()) {
+            self.book_extents = load_book_extents(db).ok();
+        }
+        self.book_extents
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .find(|extent| extent.short_name == book)
+            .and_then(|extent| extent.chapters.iter().find(|&&(ch, _)| ch == chapter))
+            .map(|&(_, last)| last)
+    }
+
+    /// The current search's input in a few words, for the window title.
+    fn search_description(&self) -> String {
+        match self.search_mode {
+            SearchMode::Plain | SearchMode::Regex => self.search_input.trim().to_string(),
+            SearchMode::Lines => search_line_phrases(&self.search_lines).join(" | "),
+            SearchMode::WordCount => {
+                format!("{} {} words", self.word_count_op, self.word_count_input.trim()).to_lowercase()
+            }
+        }
+    }
+
+    /// Red-letter search filter: whether the verse has words of Christ and the search's terms
+    /// are found in them. A plain query combines its terms there with its own operators
+    /// (negated terms were already ruled out over the whole verse); a Lines search needs
+    /// one of its phrases; a search without terms only needs words of Christ.
+    fn matches_in_red_letter(&self, text: &str, markup: &RedLetterMarkup) -> bool {
+        let Some(words) = red_letter_words(text, markup) else {
+            return false;
+        };
+        match self.search_mode {
+            SearchMode::Plain => {
+                let tokens = tokenize_query(&self.search_input);
+                query_in_red_letter(&parse_query(&tokens), &words, &self.search_options)
+            }
+            SearchMode::Lines => {
+                let phrases = search_line_phrases(&self.search_lines);
+                phrases.is_empty() || phrases.iter().any(|phrase| term_in_red_letter(&words, phrase))
+            }
+            SearchMode::WordCount | SearchMode::Regex => true,
+        }
+    }
+
+    /// Append a batch of a streaming search's verses to the results, applying the
+    /// filters and clean-up the query itself can't do.
+    fn accept_search_batch(&mut self, batch: Vec<Verse>) {
+        // Red-letter mode: the query must match, and its terms must also match in the words of Christ.
+        let mut batch: Vec<Verse> = match self.red_letter_markup.filter(|_| self.red_letter_only) {
+            Some(markup) => batch.into_iter().filter(|v| self.matches_in_red_letter(&v.text, &markup)).collect(),
+            None => batch,
+        };
+        if self.strips_verse_numbers() {
+            strip_verse_numbers(&mut batch);
+        }
+        self.search_results.extend(batch);
+    }
+
+    /// How the current settings have compare columns read.
+    fn column_options(&self) -> ColumnOptions {
+        ColumnOptions {
+            normalize: self.normalize_versification,
+            leading: self.settings.leading_numbers,
+        }
+    }
+
+    /// With one result area, show the panel `route` (Search or Lookup) goes to, remembering
+    /// it for the next launch.
+    fn show_results_tab(&mut self, route: QueryRoute) {
+        self.last_route = Some(route);
+        if self.settings.lookup_tab_active != (route == QueryRoute::Lookup) {
+            self.settings.lookup_tab_active = route == QueryRoute::Lookup;
+            self.save_settings();
+        }
+    }
+
+    /// The compared verses to dim: those all translations render identically, when dimming is on.
+    fn dimmed_verses(&self) -> BTreeSet<VerseKey> {
+        if self.dim_identical {
+            identical_verses(&self.compare_results)
+        } else {
+            BTreeSet::new()
+        }
+    }
+
+    /// Whether verse numbers at the start of the main module's texts are stripped.
+    fn strips_verse_numbers(&self) -> bool {
+        self.settings.leading_numbers.strips(self.leading_numbers_detected)
+    }
 
-impl Sandbox for App {
-    type Message = Message;
+    /// Whether the current search mode's input holds something to search for.
+    fn has_search_query(&self) -> bool {
+        match self.search_mode {
+            SearchMode::Plain | SearchMode::Regex => !self.search_input.trim().is_empty(),
+            SearchMode::Lines => !search_line_phrases(&self.search_lines).is_empty(),
+            SearchMode::WordCount => !self.word_count_input.trim().is_empty(),
+        }
+    }
+
+    /// Update the lookup preview for the "Book Chapter" being typed.
+    /// Queries only when the prefix changes and caches each chapter, so typing
+    /// the verse part of a reference never touches the database. The book is resolved to
+    /// the module's short name as a lookup resolves it, so "Genesis 1" and "gen 1" share a preview.
+    fn refresh_preview(&mut self) {
+        let key = chapter_prefix(&self.lookup_input)
+            .map(|(book, chapter)| (self.canonical_book(&book).unwrap_or(book), chapter));
+        if key == self.preview_key {
+            return;
+        }
+        if let (Some(k), Some(db)) = (&key, self.db.as_ref()) {
+            if !self.preview_cache.contains_key(k) {
+                let preview = first_verse_text(db, &k.0, k.1).ok().flatten();
+                self.preview_cache.insert(k.clone(), preview);
+            }
+        }
+        self.preview_key = key;
+    }
 
-    fn new() -> Self {
-        // UPDATE: Replace with the actual path to your main Bible database.
-        let db_path = "KJ1769.SQLite3";
-        let conn = Connection::open(db_path).expect("Failed to open DB");
-        register_regex_function(&conn).expect("Failed to register regex function");
-        App {
+    /// The app as started with `settings`, which pick the main module and restore the layout.
+    fn with_settings(settings: AppSettings, initial_lookup: Option<String>) -> (Self, Command<Message>) {
+        let db_dir = settings.db_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        let (db, status) = match open_main_bible(&db_dir) {
+            Ok(bible) => (Some(bible), None),
+            Err(e) => (None, Some(e)),
+        };
+        let books = db
+            .as_ref()
+            .and_then(|bible| load_books(bible).ok())
+            .unwrap_or_default();
+        let main_label = db
+            .as_ref()
+            .map(|bible| translation_label(bible_description(&bible.conn), &bible.path));
+        let red_letter_markup = db
+            .as_ref()
+            .and_then(|bible| detect_red_letter(&bible.conn).ok().flatten());
+        let leading_numbers_detected = db
+            .as_ref()
+            .is_some_and(|bible| detect_leading_numbers(&bible.conn).unwrap_or(false));
+        let has_strongs = db.as_ref().is_some_and(|bible| detect_strongs(&bible.conn).unwrap_or(false));
+        let bible_cache = BibleCache::default();
+        let (available_modules, discovered_modules) = discover_module_lists(
+            &db_dir,
+            settings.compare_subdirectories,
+            DedupMode::Description,
+            &mut bible_cache.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+        let mut app = App {
+            quick_input: String::new(),
+            quick_route: QueryRoute::Auto,
+            last_route: settings.lookup_tab_active.then_some(QueryRoute::Lookup),
+            editing: None,
             search_input: String::new(),
             search_results: Vec::new(),
+            search_segments: SegmentCache::default(),
+            compare_segments: SegmentCache::default(),
+            search_options: SearchOptions { not_semantics: settings.not_semantics, ..SearchOptions::default() },
+            search_notes: Vec::new(),
+            search_error: None,
+            streaming_search: None,
+            scope: SearchScope::All,
+            search_page_size: DEFAULT_SEARCH_PAGE_SIZE,
+            search_page: None,
+            search_page_rows: 0,
+            search_has_more: false,
+            search_total: None,
+            search_generation: 0,
+            search_connections: BibleCache::default(),
+            lookup_error: None,
+            chapters_only: false,
+            group_by_chapter: false,
+            collapsed_chapters: BTreeSet::new(),
+            export_format: ExportFormat::Plain,
+            search_mode: settings.initial_search_mode(),
+            red_letter_markup,
+            leading_numbers_detected,
+            red_letter_only: false,
+            red_letter_render: false,
+            has_strongs,
+            show_strongs: false,
+            raw_markup: false,
+            available_modules,
+            baseline: None,
+            baseline_flags: HashMap::new(),
+            baseline_generation: 0,
+            search_lines: vec![String::new()],
+            cross_verse_phrases: false,
+            word_count_op: WordCountOp::Exactly,
+            word_count_input: String::new(),
+            last_search: None,
+            term_frequency: term_counts_path().map(|p| load_term_counts(&p)).unwrap_or_default(),
+            history: history_path().map(|p| load_history(&p)).unwrap_or_default(),
             lookup_input: String::new(),
+            last_lookup: None,
             lookup_results: Vec::new(),
+            verse_of_day: None,
+            lookup_notes: Vec::new(),
+            font_size: DEFAULT_FONT_SIZE,
+            poetry_layout: false,
+            books,
+            preview_cache: HashMap::new(),
+            preview_key: None,
+            preview_generation: 0,
+            book_extents: None,
+            passage_weighting: PassageWeighting::Even,
+            navigator: None,
+            expanded_books: BTreeSet::new(),
+            bookmarks: bookmarks_path().map(|p| load_bookmarks(&p)).unwrap_or_default(),
+            import_path: String::new(),
+            import_skipped: Vec::new(),
+            export_path: String::new(),
+            export_overwrite: None,
+            window_size: settings.window_size.unwrap_or(DEFAULT_WINDOW_SIZE),
+            panel_weights: settings
+                .panel_weights
+                .filter(|weights| weights.iter().all(|w| (1..=MAX_PANEL_WEIGHT).contains(w)))
+                .unwrap_or([1; 3]),
             compare_results: Vec::new(),
-            db: conn,
+            bookmark_report: None,
+            bookmark_report_page: 0,
+            pinned_verses: BTreeSet::new(),
+            compare_dedup: DedupMode::Description,
+            discovered_modules: Some(discovered_modules),
+            module_scan_generation: 0,
+            bible_cache,
+            compare_reference: None,
+            compare_pinned: false,
+            comparing: false,
+            queued_compare: None,
+            dim_identical: false,
+            normalize_versification: false,
+            compare_layout: CellLayout::default(),
+            diff_first: None,
+            diff_second: None,
+            review_format: ReviewFormat::Markdown,
+            db,
+            db_dir_input: db_dir.display().to_string(),
+            db_dir,
+            main_label,
+            recent_activity: None,
+            status,
+            settings,
+        };
+        if app.settings.navigator_open {
+            app.load_navigator();
+        }
+        app.pick_verse_of_day();
+        if let Some(reference) = initial_lookup {
+            app.lookup_input = reference;
+            let command = app.update(Message::LookupSubmitted);
+            return (app, command);
         }
+        (app, Command::none())
+    }
+}
+
+impl Application for App {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    /// A lookup reference to open at startup (from a `biblical_gui://` link argument).
+    type Flags = Option<String>;
+
+    fn new(initial_lookup: Option<String>) -> (Self, Command<Message>) {
+        let settings = settings_path().map(|p| load_settings(&p)).unwrap_or_default();
+        App::with_settings(settings, initial_lookup)
     }
 
     fn title(&self) -> String {
-        String::from("Bible Verse Lookup – Search, Lookup & Compare")
+        window_title(self.main_label.as_deref(), self.recent_activity.as_ref())
     }
 
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Command<Message> {
         match message {
+            // Quick bar updates
+            Message::QuickInputChanged(query) => {
+                self.quick_input = query;
+                self.editing = Some(ClearableInput::Quick);
+            }
+            Message::QuickRouteChanged(route) => {
+                self.quick_route = route;
+            }
+            Message::ContentWidthChanged(width) => {
+                self.settings.content_width = Some(width);
+                self.save_settings();
+            }
+            Message::ScopeChanged(scope) => {
+                self.scope = scope;
+            }
+            Message::BookExcluded(book) => {
+                self.settings.excluded_books.insert(book.book_number);
+                self.save_settings();
+            }
+            Message::BookIncluded(book_number) => {
+                self.settings.excluded_books.remove(&book_number);
+                self.save_settings();
+            }
+            Message::ContextVersesChanged(verses) => {
+                self.settings.context_verses = Some(verses);
+                self.save_settings();
+            }
+            Message::ShowContext(idx) => {
+                let Some(verse) = self.search_results.get(idx) else {
+                    return Command::none();
+                };
+                let (book, chapter) = (verse.short_name.clone(), verse.chapter);
+                let (first, last) = (verse.verse, verse.verse_end.unwrap_or(verse.verse));
+                let chapter_end = self.chapter_last_verse(&book, chapter).unwrap_or(last);
+                let (start, end) = context_range(first, last, self.settings.context_verses(), chapter_end);
+                self.lookup_input = format!("{} {}:{}-{}", book, chapter, start, end);
+                self.refresh_preview();
+                return self.update(Message::LookupSubmitted);
+            }
+            Message::UnifiedResultsToggled(enabled) => {
+                self.settings.unified_results = enabled;
+                self.save_settings();
+            }
+            Message::ResultsTabChosen(route) => {
+                self.show_results_tab(route);
+            }
+            Message::QuickSubmitted => {
+                let query = self.quick_input.trim().to_string();
+                let route = self.quick_route.resolve(&query, &self.books);
+                self.show_results_tab(route);
+                if route == QueryRoute::Lookup {
+                    self.lookup_input = query;
+                    self.refresh_preview();
+                    return self.update(Message::LookupSubmitted);
+                }
+                self.search_mode = SearchMode::Plain;
+                self.search_input = query;
+                return self.update(Message::SearchSubmitted);
+            }
             // Advanced search updates
             Message::SearchChanged(query) => {
                 self.search_input = query;
+                self.editing = Some(ClearableInput::Search);
+            }
+            Message::FrequentTermChosen(term) => {
+                self.search_mode = SearchMode::Plain;
+                self.search_input = term;
+            }
+            Message::CrossVersePhrasesToggled(enabled) => {
+                self.cross_verse_phrases = enabled;
+            }
+            Message::WordCountOpChanged(op) => {
+                self.word_count_op = op;
+            }
+            Message::WordCountChanged(count) => {
+                self.word_count_input = count;
+                self.editing = Some(ClearableInput::Search);
+            }
+            Message::MinTermLenChanged(len) => {
+                self.search_options.min_term_len = len;
+            }
+            Message::MaxHighlightsChanged(max) => {
+                self.search_options.max_highlights = max;
+            }
+            Message::MatchPositionChanged(position) => {
+                self.search_options.matching.position = position;
+            }
+            Message::CaseSensitivityToggled(enabled) => {
+                self.search_options.matching.case_sensitive = enabled;
+            }
+            Message::WholeWordToggled(enabled) => {
+                self.search_options.matching.whole_word = enabled;
+            }
+            Message::NotSemanticsChanged(semantics) => {
+                self.search_options.not_semantics = semantics;
+                self.settings.not_semantics = semantics;
+                self.save_settings();
+            }
+            Message::SearchHelpToggled => {
+                self.settings.search_help_open = !self.settings.search_help_open;
+                self.save_settings();
+            }
+            Message::SearchModeChanged(mode) => {
+                self.search_mode = mode;
+                self.settings.last_search_mode = Some(mode);
+                self.save_settings();
+            }
+            Message::CycleSearchMode => {
+                self.search_mode = self.search_mode.next();
+                self.settings.last_search_mode = Some(self.search_mode);
+                self.save_settings();
+                if self.has_search_query() {
+                    return self.update(Message::SearchSubmitted);
+                }
+            }
+            Message::DefaultSearchModeChanged(DefaultSearchMode(mode)) => {
+                self.settings.default_search_mode = mode;
+                self.save_settings();
+            }
+            Message::RedLetterOnlyToggled(enabled) => {
+                self.red_letter_only = enabled;
+            }
+            Message::ExportFormatChanged(format) => {
+                self.export_format = format;
+            }
+            Message::CopyAllResults(lookup) => {
+                let verses = if lookup { &self.lookup_results } else { &self.search_results };
+                let limit = self.settings.copy_limit();
+                let copied = &verses[..verses.len().min(limit)];
+                let contents = format_verses(copied, self.export_format, self.settings.abbreviate_references, self.raw_markup);
+                self.status = Some(if copied.len() < verses.len() {
+                    format!(
+                        "Copied the first {} of {} results to the clipboard (copy limit {})",
+                        copied.len(),
+                        verses.len(),
+                        limit
+                    )
+                } else {
+                    format!("Copied {} results to the clipboard", copied.len())
+                });
+                return iced::clipboard::write(contents);
+            }
+            Message::CopyLimitChanged(limit) => {
+                self.settings.copy_limit = Some(limit);
+                self.save_settings();
+            }
+            Message::ExportPathChanged(path) => {
+                self.export_path = path;
+                self.export_overwrite = None;
+            }
+            // Minimizing reports a 0x0 size and (on Windows) an off-screen position; those
+            // aren't remembered, so the window reopens as it last was on screen.
+            Message::WindowResized(width, height) => {
+                self.window_size = (width, height);
+                if let Some(size) = remembered_window_size(self.window_size) {
+                    self.settings.window_size = Some(size);
+                }
+            }
+            Message::WindowMoved(x, y) => {
+                if let Some(position) = remembered_window_position((x, y)) {
+                    self.settings.window_position = Some(position);
+                }
+            }
+            Message::WindowCloseRequested => {
+                self.save_settings();
+                return window::close();
+            }
+            Message::PanelResized(panel, taller) => {
+                let weight = &mut self.panel_weights[panel.index()];
+                *weight = if taller { (*weight + 1).min(MAX_PANEL_WEIGHT) } else { weight.saturating_sub(1).max(1) };
+                self.settings.panel_weights = Some(self.panel_weights);
+                self.save_settings();
+            }
+            Message::ExportResults(lookup) => {
+                let Some(db) = self.db.as_ref() else {
+                    self.status = Some(NO_BIBLE_LOADED.to_string());
+                    return Command::none();
+                };
+                let verses = if lookup { &self.lookup_results } else { &self.search_results };
+                let bible = translation_label(bible_description(&db.conn), &db.path);
+                let export = export_document(
+                    verses,
+                    self.export_format,
+                    self.settings.abbreviate_references,
+                    self.raw_markup,
+                    &bible,
+                );
+                let path = match self.export_path.trim() {
+                    "" => self.export_format.default_file(),
+                    path => path,
+                };
+                // An existing file is only replaced when the export is repeated after a warning.
+                let written = if self.export_overwrite.as_deref() == Some(path) {
+                    fs::write(path, export)
+                } else {
+                    fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(path)
+                        .and_then(|mut file| file.write_all(export.as_bytes()))
+                };
+                self.status = Some(match written {
+                    Ok(()) => {
+                        self.export_overwrite = None;
+                        format!("Exported {} verses to {}", verses.len(), path)
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                        self.export_overwrite = Some(path.to_string());
+                        format!("{} already exists — export again to overwrite it", path)
+                    }
+                    Err(e) => format!("Could not export to {}: {}", path, e),
+                });
+            }
+            Message::AbbreviateReferencesToggled(enabled) => {
+                self.settings.abbreviate_references = enabled;
+                self.save_settings();
+            }
+            Message::ChaptersOnlyToggled(enabled) => {
+                self.chapters_only = enabled;
+            }
+            Message::MatchingChapterChosen(book, chapter) => {
+                match self.chapter_last_verse(&book, chapter) {
+                    Some(last_verse) => return self.update(Message::NavigateChapter(book, chapter, last_verse)),
+                    None => self.status = Some(format!("{} {} is not in this module", book, chapter)),
+                }
+            }
+            Message::GroupByChapterToggled(enabled) => {
+                self.group_by_chapter = enabled;
+            }
+            Message::ChapterGroupToggled(key) => {
+                if !self.collapsed_chapters.remove(&key) {
+                    self.collapsed_chapters.insert(key);
+                }
+            }
+            Message::AllChapterGroupsCollapsed(collapse) => {
+                self.collapsed_chapters = if collapse {
+                    group_by_chapter(&self.search_results).into_iter().map(|(key, _)| key).collect()
+                } else {
+                    BTreeSet::new()
+                };
+            }
+            Message::ShowRawTextToggled(enabled) => {
+                self.settings.show_raw_text = enabled;
+                self.save_settings();
+            }
+            Message::ZebraStripesToggled(enabled) => {
+                self.settings.zebra_stripes = enabled;
+                self.save_settings();
+            }
+            Message::ThemeToggled => {
+                self.settings.dark_theme = !self.settings.dark_theme;
+                self.save_settings();
+            }
+            Message::FontSizeChanged(size) => {
+                self.font_size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+            }
+            Message::TwoColumnResultsToggled(enabled) => {
+                self.settings.two_column_results = enabled;
+                self.save_settings();
+            }
+            Message::RedLetterRenderToggled(enabled) => {
+                self.red_letter_render = enabled;
+            }
+            Message::StrongsToggled(enabled) => {
+                self.show_strongs = enabled;
+            }
+            Message::RawMarkupToggled(enabled) => {
+                self.raw_markup = enabled;
+            }
+            Message::BaselineChanged(module) => {
+                self.baseline = Some(module);
+                self.clear_baseline_flags();
+                return self.refresh_baseline_flags();
+            }
+            Message::BaselineCleared => {
+                self.baseline = None;
+                self.clear_baseline_flags();
+            }
+            Message::SearchLineChanged(idx, line) => {
+                if let Some(existing) = self.search_lines.get_mut(idx) {
+                    *existing = line;
+                }
+                self.editing = Some(ClearableInput::SearchLine(idx));
+            }
+            Message::SearchLineAdded(idx) => {
+                let at = (idx + 1).min(self.search_lines.len());
+                self.search_lines.insert(at, String::new());
+            }
+            Message::SearchLineRemoved(idx) => {
+                if idx < self.search_lines.len() {
+                    self.search_lines.remove(idx);
+                }
+                if self.search_lines.is_empty() {
+                    self.search_lines.push(String::new());
+                }
+            }
+            Message::RepeatLastSearch => {
+                if let Some(last) = self.last_search.clone() {
+                    self.search_mode = last.mode;
+                    self.search_input = last.input;
+                    self.search_lines = last.lines;
+                    (self.word_count_op, self.word_count_input) = last.word_count;
+                    return self.update(Message::SearchSubmitted);
+                }
             }
             Message::SearchSubmitted => {
                 println!("Advanced Search query: {}", self.search_input);
-                let (where_clause, params_vec) = build_where_clause(&self.search_input);
-                let sql = format!(
-                    "SELECT b.long_name, v.chapter, v.verse, v.text \
-                     FROM verses v \
-                     JOIN books b ON v.book_number = b.book_number \
-                     WHERE {}",
-                    where_clause
+                // An invalid pattern is rejected before anything is recorded, and stops the
+                // previous search so none of its pages land under the error.
+                let WhereClause { sql: where_clause, params: params_vec, notes } = match self.search_mode {
+                    SearchMode::Plain => build_where_clause(&self.search_input, &self.search_options),
+                    SearchMode::Lines => build_lines_clause(&self.search_lines, self.search_options.matching),
+                    SearchMode::WordCount => build_word_count_clause(self.word_count_op, &self.word_count_input),
+                    SearchMode::Regex => match build_regex_clause(&self.search_input) {
+                        Ok(clause) => clause,
+                        Err(e) => {
+                            self.search_generation += 1;
+                            self.streaming_search = None;
+                            self.search_page = None;
+                            self.search_has_more = false;
+                            self.search_error = Some(format!("Invalid regular expression: {}", e));
+                            self.search_notes.clear();
+                            self.search_results.clear();
+                            return Command::none();
+                        }
+                    },
+                };
+                self.last_search = Some(LastSearch {
+                    mode: self.search_mode,
+                    input: self.search_input.clone(),
+                    lines: self.search_lines.clone(),
+                    word_count: (self.word_count_op, self.word_count_input.clone()),
+                });
+                let query = match self.search_mode {
+                    SearchMode::Plain | SearchMode::Regex => Some(self.search_input.clone()),
+                    SearchMode::Lines => Some(self.search_lines.join("\n")),
+                    SearchMode::WordCount => None,
+                };
+                if let Some(query) = query {
+                    self.record_history(HistoryEntry::Search { mode: self.search_mode, query });
+                }
+                let Some(db) = self.db.as_ref() else {
+                    self.status = Some(NO_BIBLE_LOADED.to_string());
+                    self.search_results.clear();
+                    return Command::none();
+                };
+                // Only searches that run are counted.
+                let terms: Vec<String> = self.search_terms().into_iter().map(str::to_string).collect();
+                self.term_frequency.record(terms.iter().map(String::as_str));
+                if let Some(path) = term_counts_path() {
+                    if let Err(e) = save_term_counts(&path, &self.term_frequency) {
+                        self.status = Some(format!("Could not save search term counts: {}", e));
+                    }
+                }
+                self.search_notes = notes;
+                let where_clause = narrowed_where_clause(
+                    where_clause,
+                    &self.settings.excluded_books,
+                    &self.scope,
+                    &db.schema.verse_book(),
                 );
-                println!("Advanced Search SQL Query: {}", sql);
+                println!("Advanced Search SQL Query: {}", search_sql(&db.schema, &where_clause));
                 println!("Advanced Search Parameters: {:?}", params_vec);
-                let mut stmt = self.db.prepare(&sql).expect("Failed to prepare statement");
-                let verse_iter = stmt
-                    .query_map(params_from_iter(params_vec.iter()), |row| {
-                        Ok(Verse {
-                            long_name: row.get(0)?,
-                            chapter: row.get(1)?,
-                            verse: row.get(2)?,
-                            text: row.get(3)?,
-                        })
-                    })
-                    .expect("Query failed");
-                self.search_results = verse_iter.filter_map(|result| result.ok()).collect();
+                // Results stream in through `streaming_search`, starting from an empty list,
+                // and the total follows once the first page is in.
+                self.search_total = None;
+                self.search_generation += 1;
+                let page = StreamingSearch {
+                    id: self.search_generation,
+                    path: db.path.clone(),
+                    where_clause,
+                    params: params_vec,
+                    limit: self.search_page_size,
+                    offset: 0,
+                    count: true,
+                };
+                self.streaming_search = Some(page.clone());
+                self.search_page = Some(page);
+                self.search_page_rows = 0;
+                self.search_has_more = false;
+                self.search_error = None;
+                self.search_results.clear();
+                self.clear_baseline_flags();
+                self.search_segments.clear();
+                self.collapsed_chapters.clear();
+            }
+            Message::LoadMore => {
+                let Some(mut page) = self.search_page.clone().filter(|_| self.streaming_search.is_none()) else {
+                    return Command::none();
+                };
+                self.search_generation += 1;
+                page.id = self.search_generation;
+                page.offset += page.limit;
+                page.count = false;
+                // The next page is appended; highlights come from the unchanged search terms.
+                self.streaming_search = Some(page.clone());
+                self.search_page = Some(page);
+                self.search_page_rows = 0;
+                self.search_has_more = false;
+            }
+            Message::SearchPageSizeChanged(size) => {
+                self.search_page_size = size;
+            }
+            Message::SearchBatch(id, batch) => {
+                if self.streaming_search.as_ref().is_some_and(|search| search.id == id) {
+                    self.search_page_rows += batch.len();
+                    self.accept_search_batch(batch);
+                }
+            }
+            Message::SearchCounted(id, total) => {
+                if self.streaming_search.as_ref().is_some_and(|search| search.id == id) {
+                    self.search_total = Some(total);
+                }
+            }
+            Message::SearchDone(id, error) => {
+                if !self.streaming_search.as_ref().is_some_and(|search| search.id == id) {
+                    return Command::none();
+                }
+                self.streaming_search = None;
+                if let Some(e) = error {
+                    self.search_error = Some(format!("Search failed: {}", e));
+                    self.search_results.clear();
+                    return Command::none();
+                }
+                self.search_has_more = match (&self.search_page, self.search_total) {
+                    (Some(page), Some(total)) => page.offset + self.search_page_rows < total,
+                    (Some(page), None) => self.search_page_rows >= page.limit,
+                    (None, _) => false,
+                };
                 println!("Advanced Search found {} verses", self.search_results.len());
+                self.recent_activity = Some(RecentActivity::Search {
+                    query: self.search_description(),
+                    matches: self.search_results.len(),
+                });
+                let flags = self.refresh_baseline_flags();
+                // Phrases spanning verses are found over the whole module, so look for them
+                // in the background once the last page is in.
+                let phrases = if self.cross_verse_phrases && !self.search_has_more {
+                    self.cross_verse_candidates()
+                } else {
+                    Vec::new()
+                };
+                if let (false, Some(db)) = (phrases.is_empty(), self.db.as_ref()) {
+                    let path = db.path.clone();
+                    let connections = Arc::clone(&self.search_connections);
+                    return Command::batch([
+                        flags,
+                        Command::perform(
+                            async move { find_cross_verse_matches(&path, &connections, &phrases) },
+                            move |matches| Message::CrossVerseMatched(id, matches),
+                        ),
+                    ]);
+                }
+                return flags;
+            }
+            Message::CrossVerseMatched(id, matches) => {
+                return self.add_cross_verse_matches(id, matches);
+            }
+            Message::BaselineFetched(generation, refs, texts) => {
+                if generation != self.baseline_generation {
+                    return Command::none();
+                }
+                match texts {
+                    Ok(texts) => {
+                        let requested: BTreeSet<VerseRef> = refs.into_iter().collect();
+                        let hits = self
+                            .search_results
+                            .iter()
+                            .filter(|v| requested.contains(&(v.book_number, v.chapter, v.verse)));
+                        self.baseline_flags.extend(baseline_flags(hits, &texts));
+                    }
+                    Err(e) => {
+                        let label = self.baseline.as_ref().map(DiscoveredModule::label).unwrap_or_default();
+                        self.status = Some(format!("Could not read baseline {}: {}", label, e));
+                    }
+                }
             }
             // Lookup updates
             Message::LookupChanged(query) => {
                 self.lookup_input = query;
+                self.editing = Some(ClearableInput::Lookup);
+                self.preview_generation += 1;
+                let generation = self.preview_generation;
+                return Command::perform(
+                    async move {
+                        let (done, elapsed) = oneshot::channel();
+                        std::thread::spawn(move || {
+                            std::thread::sleep(PREVIEW_DEBOUNCE);
+                            let _ = done.send(());
+                        });
+                        let _ = elapsed.await;
+                        generation
+                    },
+                    Message::PreviewDue,
+                );
+            }
+            Message::PreviewDue(generation) => {
+                if generation == self.preview_generation {
+                    self.refresh_preview();
+                }
+            }
+            Message::BookSuggestionChosen(short_name) => {
+                self.lookup_input = format!("{} ", short_name);
+                self.refresh_preview();
+            }
+            Message::FocusSearch => {
+                self.editing = Some(ClearableInput::Search);
+                return text_input::focus(search_input_id());
+            }
+            Message::EscapePressed => match self.editing.take() {
+                Some(ClearableInput::Search) => match self.search_mode {
+                    SearchMode::Plain | SearchMode::Regex => self.search_input.clear(),
+                    SearchMode::Lines => self.search_lines.iter_mut().take(1).for_each(String::clear),
+                    SearchMode::WordCount => self.word_count_input.clear(),
+                },
+                Some(ClearableInput::SearchLine(idx)) => {
+                    if let Some(line) = self.search_lines.get_mut(idx) {
+                        line.clear();
+                    }
+                }
+                Some(ClearableInput::Lookup) => {
+                    self.lookup_input.clear();
+                    self.refresh_preview();
+                }
+                Some(ClearableInput::Quick) => self.quick_input.clear(),
+                None => {}
+            },
+            Message::RepeatLastLookup => {
+                if let Some(last) = self.last_lookup.clone() {
+                    self.lookup_input = last;
+                    self.refresh_preview();
+                    return self.update(Message::LookupSubmitted);
+                }
+            }
+            Message::HistoryChosen(HistoryEntry::Search { mode, query }) => {
+                self.search_mode = mode;
+                if mode == SearchMode::Lines {
+                    self.search_lines = query.lines().map(str::to_string).collect();
+                } else {
+                    self.search_input = query;
+                }
+                return self.update(Message::SearchSubmitted);
+            }
+            Message::HistoryChosen(HistoryEntry::Lookup(reference)) => {
+                self.lookup_input = reference;
+                self.refresh_preview();
+                return self.update(Message::LookupSubmitted);
+            }
+            Message::NextChapter | Message::PrevChapter => {
+                let forward = matches!(message, Message::NextChapter);
+                // Step from the last chapter shown going forward, from the first going back.
+                let shown = if forward { self.lookup_results.last() } else { self.lookup_results.first() };
+                let Some((book, chapter)) = shown.map(|v| (v.short_name.clone(), v.chapter)) else {
+                    return Command::none();
+                };
+                if let (None, Some(db)) = (&self.book_extents, self.db.as_ref()) {
+                    self.book_extents = load_book_extents(db).ok();
+                }
+                let extents = self.book_extents.as_deref().unwrap_or_default();
+                match adjacent_chapter(extents, &book, chapter, forward) {
+                    Some((book, chapter)) => {
+                        self.lookup_input = format!("{} {}", book, chapter);
+                        self.refresh_preview();
+                        return self.update(Message::LookupSubmitted);
+                    }
+                    None if forward => self.status = Some("Already at the last chapter".to_string()),
+                    None => self.status = Some("Already at the first chapter".to_string()),
+                }
             }
             Message::LookupSubmitted => {
                 println!("Lookup query: {}", self.lookup_input);
-                // When doing a lookup, clear previous compare results.
-                self.compare_results.clear();
-                if let Some((book, start_ch, start_v, end_ch, end_v)) = parse_lookup(&self.lookup_input) {
-                    let sql = "
-                        SELECT b.long_name, v.chapter, v.verse, v.text
-                        FROM verses v
-                        JOIN books b ON v.book_number = b.book_number
-                        WHERE b.short_name = ?
-                          AND ((v.chapter * 1000) + v.verse) BETWEEN ((? * 1000) + ?) AND ((? * 1000) + ?)
-                        ORDER BY v.chapter, v.verse
-                    ";
-                    println!("Lookup SQL Query: {}", sql);
-                    println!("Lookup Parameters: [book: {}, start: {}:{}, end: {}:{}]", book, start_ch, start_v, end_ch, end_v);
-                    let mut stmt = self.db.prepare(sql).expect("Failed to prepare statement");
-                    let verse_iter = stmt
-                        .query_map(params![book, start_ch, start_v, end_ch, end_v], |row| {
-                            Ok(Verse {
-                                long_name: row.get(0)?,
-                                chapter: row.get(1)?,
-                                verse: row.get(2)?,
-                                text: row.get(3)?,
-                            })
-                        })
-                        .expect("Query failed");
-                    self.lookup_results = verse_iter.filter_map(|result| result.ok()).collect();
-                    println!("Lookup found {} verses", self.lookup_results.len());
-                } else {
-                    println!("Failed to parse lookup input: {}", self.lookup_input);
+                self.last_lookup = Some(self.lookup_input.clone());
+                self.record_history(HistoryEntry::Lookup(self.lookup_input.trim().to_string()));
+                // An unpinned comparison follows the looked-up reference; a pinned one stays put.
+                let compare = match followed_compare_reference(
+                    self.compare_pinned,
+                    self.compare_reference.as_deref(),
+                    &self.lookup_input,
+                ) {
+                    Some(reference) => self.run_compare(reference),
+                    None => Command::none(),
+                };
+                let Some(db) = self.db.as_ref() else {
+                    self.status = Some(NO_BIBLE_LOADED.to_string());
                     self.lookup_results.clear();
+                    return compare;
+                };
+                self.lookup_error = None;
+                self.lookup_notes.clear();
+                // Several references separated by semicolons are read one after another.
+                let mut results = Vec::new();
+                for piece in lookup_references(&self.lookup_input) {
+                    match run_lookup(&db.conn, &db.schema, &self.books, &piece) {
+                        Ok(verses) => results.extend(verses),
+                        Err(e @ LookupError::Database(_)) => {
+                            self.lookup_error = Some(e.to_string());
+                            results.clear();
+                            break;
+                        }
+                        Err(e) => {
+                            println!("Skipping lookup input '{}': {}", piece, e);
+                            self.lookup_notes.push(e.to_string());
+                        }
+                    }
+                }
+                if self.strips_verse_numbers() {
+                    strip_verse_numbers(&mut results);
+                }
+                self.lookup_results = results;
+                println!("Lookup found {} verses", self.lookup_results.len());
+                self.recent_activity = Some(RecentActivity::Lookup {
+                    reference: self.lookup_input.trim().to_string(),
+                    verses: self.lookup_results.len(),
+                });
+                return compare;
+            }
+            Message::PoetryLayoutToggled(enabled) => {
+                self.poetry_layout = enabled;
+            }
+            Message::LeadingNumbersChanged(mode) => {
+                self.settings.leading_numbers = mode;
+                self.save_settings();
+                self.status = Some("Verse number handling applies to the next search or lookup".to_string());
+            }
+            Message::PassageWeightingChanged(weighting) => {
+                self.passage_weighting = weighting;
+            }
+            Message::SurpriseMe => {
+                let Some(db) = self.db.as_ref() else {
+                    self.status = Some(NO_BIBLE_LOADED.to_string());
+                    return Command::none();
+                };
+                if self.book_extents.is_none() {
+                    self.book_extents = load_book_extents(db).ok();
+                }
+                let seed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default();
+                let extents = self.book_extents.as_deref().unwrap_or_default();
+                match pick_passage(extents, self.passage_weighting, PASSAGE_LENGTH, seed) {
+                    Some(pick) => {
+                        self.lookup_input = pick.reference();
+                        return self.update(Message::LookupSubmitted);
+                    }
+                    None => self.status = Some("No passages available in this module".to_string()),
+                }
+            }
+            // Navigator updates
+            Message::NavigatorToggled => {
+                self.settings.navigator_open = !self.settings.navigator_open;
+                self.save_settings();
+                if self.settings.navigator_open {
+                    self.load_navigator();
+                }
+            }
+            Message::NavigatorBookToggled(book) => {
+                if !self.expanded_books.remove(&book) {
+                    self.expanded_books.insert(book);
+                }
+            }
+            Message::NavigateChapter(book, chapter, last_verse) => {
+                self.lookup_input = format!("{} {}:1-{}", book, chapter, last_verse);
+                self.refresh_preview();
+                return self.update(Message::LookupSubmitted);
+            }
+            // Bookmark updates
+            Message::BookmarkToggled(bookmark) => {
+                if let Some(pos) = self.bookmarks.iter().position(|b| *b == bookmark) {
+                    self.bookmarks.remove(pos);
+                } else {
+                    self.bookmarks.push(bookmark);
+                }
+                self.save_bookmarks();
+            }
+            Message::ImportPathChanged(path) => {
+                self.import_path = path;
+            }
+            Message::ImportReferences => {
+                self.import_skipped.clear();
+                let contents = match fs::read_to_string(self.import_path.trim()) {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        self.status = Some(format!("Could not read {}: {}", self.import_path.trim(), e));
+                        return Command::none();
+                    }
+                };
+                let (refs, mut skipped) = parse_reference_list(&contents);
+                let mut imported = 0;
+                for mut bookmark in refs {
+                    match self.canonical_book(&bookmark.book) {
+                        Some(short_name) => bookmark.book = short_name,
+                        None => {
+                            skipped.push(format!("unknown book: {}", bookmark));
+                            continue;
+                        }
+                    }
+                    if !self.bookmarks.contains(&bookmark) {
+                        self.bookmarks.push(bookmark);
+                        imported += 1;
+                    }
+                }
+                self.save_bookmarks();
+                self.status = Some(format!("Imported {}, skipped {}", imported, skipped.len()));
+                self.import_skipped = skipped;
+            }
+            Message::CopyVerseLink(idx) => {
+                if let Some(verse) = self.lookup_results.get(idx) {
+                    let contents = format_verse_link(verse, self.settings.abbreviate_references, self.raw_markup);
+                    self.status = Some(format!("Copied {} with a link", verse.reference(self.settings.abbreviate_references)));
+                    return iced::clipboard::write(contents);
+                }
+            }
+            Message::ExportBookmarks => {
+                let Some(db) = self.db.as_ref() else {
+                    self.status = Some(NO_BIBLE_LOADED.to_string());
+                    return Command::none();
+                };
+                let bible = translation_label(bible_description(&db.conn), &db.path);
+                let strip = self.strips_verse_numbers();
+                let books = &self.books;
+                let export = format_bookmark_export(&self.bookmarks, &bible, |b| {
+                    let book = find_book(books, &b.book).map_or(b.book.as_str(), |book| book.short_name.as_str());
+                    let text = verse_text(db, book, b.chapter, b.verse).ok().flatten()?;
+                    Some(if strip { strip_leading_number(b.verse, &text).to_string() } else { text })
+                });
+                self.status = Some(match fs::write(BOOKMARK_EXPORT_FILE, export) {
+                    Ok(()) => format!("Exported {} bookmarks to {}", self.bookmarks.len(), BOOKMARK_EXPORT_FILE),
+                    Err(e) => format!("Could not export bookmarks: {}", e),
+                });
+            }
+            Message::CompareBookmarks => {
+                return self.update(Message::BookmarkReportPage(0));
+            }
+            Message::BookmarkReportPage(page) => {
+                let pages = self.bookmarks.len().div_ceil(BOOKMARK_REPORT_PAGE_SIZE).max(1);
+                self.bookmark_report_page = page.min(pages - 1);
+                let page = self.bookmark_report_page;
+                let bookmarks: Vec<Bookmark> = self
+                    .bookmarks
+                    .iter()
+                    .skip(page * BOOKMARK_REPORT_PAGE_SIZE)
+                    .take(BOOKMARK_REPORT_PAGE_SIZE)
+                    .cloned()
+                    .collect();
+                let dir = self.db_dir.clone();
+                let discovered = self.discovered_modules.clone();
+                let generation = self.module_scan_generation;
+                let recursive = self.settings.compare_subdirectories;
+                let dedup = self.compare_dedup;
+                let cache = Arc::clone(&self.bible_cache);
+                let column_options = self.column_options();
+                // As in `run_compare`: the main Bible's column is queried here; the directory
+                // scan and the other modules happen in the background, under the cache lock.
+                let main = self.db.as_ref().map(|db| {
+                    let columns: Vec<Vec<Verse>> = bookmarks
+                        .iter()
+                        .map(|b| {
+                            compare_passage(&[(String::new(), db)], &b.book, b.chapter, b.verse, b.chapter, b.verse, column_options)
+                                .into_iter()
+                                .next()
+                                .map(|(_, verses)| verses)
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    (canonical_path(&db.path), columns)
+                });
+                return Command::perform(
+                    async move {
+                        let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+                        let modules = discovered.unwrap_or_else(|| discover_modules(&dir, recursive, dedup, &mut cache));
+                        let others = modules_besides(&modules, main.as_ref().map(|(path, _)| path.as_path()));
+                        let bibles = open_modules(&others, &mut cache);
+                        let (main_path, mut main_columns) = match main {
+                            Some((path, columns)) => (Some(path), columns.into_iter()),
+                            None => (None, Vec::new().into_iter()),
+                        };
+                        let report = bookmarks
+                            .into_iter()
+                            .map(|b| {
+                                let mut results =
+                                    compare_passage(&bibles, &b.book, b.chapter, b.verse, b.chapter, b.verse, column_options);
+                                if let Some(path) = &main_path {
+                                    insert_main_column(&mut results, &modules, path, main_columns.next().unwrap_or_default());
+                                }
+                                (b, results)
+                            })
+                            .collect();
+                        (modules, report)
+                    },
+                    move |(modules, report)| Message::BookmarkReportLoaded(page, generation, modules, report),
+                );
+            }
+            Message::BookmarkReportLoaded(page, generation, modules, report) => {
+                self.keep_module_scan(generation, modules);
+                // A page asked for later replaces this one.
+                if page == self.bookmark_report_page {
+                    self.bookmark_report = Some(report);
                 }
             }
+            Message::CloseBookmarkReport => {
+                self.bookmark_report = None;
+            }
             // Compare updates
+            Message::CompareDedupChanged(mode) => {
+                self.compare_dedup = mode;
+                self.discard_module_scan();
+            }
+            Message::CompareSubdirectoriesToggled(enabled) => {
+                self.settings.compare_subdirectories = enabled;
+                self.save_settings();
+                return self.update(Message::RescanModules);
+            }
+            Message::RescanModules => {
+                self.discard_module_scan();
+                self.bible_cache = BibleCache::default();
+                if !self.db_dir.is_dir() {
+                    self.available_modules.clear();
+                    self.status = Some(format!("Bible directory {} does not exist", self.db_dir.display()));
+                    return Command::none();
+                }
+                let (baselines, modules) = discover_module_lists(
+                    &self.db_dir,
+                    self.settings.compare_subdirectories,
+                    self.compare_dedup,
+                    &mut self.bible_cache.lock().unwrap_or_else(PoisonError::into_inner),
+                );
+                self.available_modules = baselines;
+                self.status = Some(format!("Found {} modules in {}", modules.len(), self.db_dir.display()));
+                self.discovered_modules = Some(modules);
+            }
+            Message::DbDirChanged(input) => {
+                self.db_dir_input = input;
+            }
+            Message::DbDirSubmitted => {
+                let dir = expand_home(&self.db_dir_input);
+                if !dir.is_dir() {
+                    self.status = Some(format!("Bible directory {} does not exist", dir.display()));
+                    return Command::none();
+                }
+                let remembered = can_remember_dir(&dir);
+                self.db_dir = dir.clone();
+                self.settings.db_dir = Some(dir);
+                self.save_settings();
+                let opened = open_main_bible(&self.db_dir).map(|bible| self.set_main_bible(bible));
+                let command = self.update(Message::RescanModules);
+                // The rescan reports what it found; a failed open or a directory the settings
+                // file can't hold matters more.
+                if let Err(e) = opened {
+                    self.status = Some(format!("Keeping the current main Bible: {}", e));
+                } else if !remembered {
+                    self.status = Some(format!(
+                        "Using {}, but its path can't be saved in the settings file, so it won't be remembered",
+                        self.db_dir.display()
+                    ));
+                }
+                return command;
+            }
+            Message::CompareVersePinToggled(key) => {
+                if !self.pinned_verses.remove(&key) {
+                    self.pinned_verses.insert(key);
+                }
+            }
+            Message::CompareLayoutChanged(layout) => {
+                self.compare_layout = layout;
+            }
+            Message::ReviewFormatChanged(format) => {
+                self.review_format = format;
+            }
+            Message::DiffFirstChosen(label) => {
+                self.diff_first = Some(label);
+            }
+            Message::DiffSecondChosen(label) => {
+                self.diff_second = Some(label);
+            }
+            Message::GenerateReviewSheet => {
+                if self.compare_results.is_empty() {
+                    self.status = Some("Run a comparison first to generate a review sheet".to_string());
+                    return Command::none();
+                }
+                // The main translation is the diff base when it is among the compared modules.
+                let base = self
+                    .db
+                    .as_ref()
+                    .map(|db| translation_label(bible_description(&db.conn), &db.path))
+                    .unwrap_or_default();
+                let reference = self.compare_reference.clone().unwrap_or_default();
+                // Words are diffed as displayed, so markup doesn't show up as changed words.
+                let displayed: CompareResults = self
+                    .compare_results
+                    .iter()
+                    .map(|(label, verses)| (label.clone(), displayed_verses(verses, self.raw_markup)))
+                    .collect();
+                let sheet = review_sheet(&displayed, &base, &reference, self.review_format);
+                let mut status = format!("Copied the review sheet for {} to the clipboard", reference);
+                if !self.compare_results.iter().any(|(label, _)| *label == base) {
+                    status.push_str(&format!(
+                        " (the main Bible {} is not among the compared translations, so {} is the base)",
+                        if base.is_empty() { "(none open)" } else { base.as_str() },
+                        self.compare_results[0].0
+                    ));
+                }
+                self.status = Some(status);
+                return iced::clipboard::write(sheet);
+            }
+            Message::DimIdenticalToggled(enabled) => {
+                self.dim_identical = enabled;
+            }
+            Message::NormalizeVersificationToggled(enabled) => {
+                self.normalize_versification = enabled;
+                if let Some(reference) = self.compare_reference.clone() {
+                    return self.run_compare(reference);
+                }
+            }
+            Message::ClearPinnedVerses => {
+                self.pinned_verses.clear();
+            }
             Message::CompareSubmitted => {
                 println!("Compare lookup based on: {}", self.lookup_input);
                 // When doing a comparison, clear previous lookup results.
                 self.lookup_results.clear();
-                if let Some((book, start_ch, start_v, end_ch, end_v)) = parse_lookup(&self.lookup_input) {
-                    self.compare_results.clear();
-                    // Look for all files in the current directory with extension ".SQLite3"
-                    if let Ok(entries) = fs::read_dir(".") {
-                        for entry in entries.filter_map(Result::ok) {
-                            let path = entry.path();
-                            if let Some(ext) = path.extension() {
-                                if ext.to_str().map(|s| s.eq_ignore_ascii_case("SQLite3")).unwrap_or(false) {
-                                    if let Ok(bible_conn) = Connection::open(&path) {
-                                        // Get the Bible's description from the info table.
-                                        let bible_name: String = bible_conn.query_row(
-                                            "SELECT value FROM info WHERE name = 'description'",
-                                            [],
-                                            |row| row.get(0),
-                                        ).unwrap_or_else(|_| "Unknown Bible".to_string());
-                                        let sql = "
-                                            SELECT v.chapter, v.verse, v.text
-                                            FROM verses v
-                                            JOIN books b ON v.book_number = b.book_number
-                                            WHERE b.short_name = ?
-                                              AND ((v.chapter * 1000) + v.verse) BETWEEN ((? * 1000) + ?) AND ((? * 1000) + ?)
-                                            ORDER BY v.chapter, v.verse
-                                        ";
-                                        if let Ok(mut stmt) = bible_conn.prepare(sql) {
-                                            let verse_iter = stmt
-                                                .query_map(params![book, start_ch, start_v, end_ch, end_v], |row| {
-                                                    Ok(Verse {
-                                                        long_name: bible_name.clone(),
-                                                        chapter: row.get(0)?,
-                                                        verse: row.get(1)?,
-                                                        text: row.get(2)?,
-                                                    })
-                                                });
-                                            if let Ok(iter) = verse_iter {
-                                                let verses: Vec<Verse> = iter.filter_map(|v| v.ok()).collect();
-                                                println!("Bible '{}' (file {:?}) returned {} verses", bible_name, path, verses.len());
-                                                self.compare_results.push((bible_name, verses));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    println!("Comparison completed with {} Bibles", self.compare_results.len());
-                } else {
-                    println!("Failed to parse lookup input for compare: {}", self.lookup_input);
-                    self.compare_results.clear();
+                return self.run_compare(self.lookup_input.clone());
+            }
+            Message::CompareCompleted(generation, modules, results) => {
+                println!("Comparison completed with {} Bibles", results.len());
+                self.comparing = false;
+                self.keep_module_scan(generation, modules);
+                self.compare_results = results;
+                self.compare_segments.clear();
+                if let Some(reference) = self.queued_compare.take() {
+                    return self.run_compare(reference);
                 }
             }
+            Message::CompareReferencePinToggled(pinned) => {
+                self.compare_pinned = pinned;
+            }
+        }
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let hotkeys = subscription::events_with(hotkey);
+        match &self.streaming_search {
+            Some(search) => Subscription::batch([
+                hotkeys,
+                streaming_search(search.clone(), Arc::clone(&self.search_connections)),
+            ]),
+            None => hotkeys,
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        if self.settings.dark_theme {
+            Theme::Dark
+        } else {
+            Theme::Light
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         // Advanced Search Section
-        let search_input = text_input("Enter advanced search query...", &self.search_input)
-            .on_input(Message::SearchChanged)
+        let search_input: Element<Message> = match self.search_mode {
+            SearchMode::Lines => {
+                let mut lines = Column::new().spacing(5);
+                for (idx, line) in self.search_lines.iter().enumerate() {
+                    let mut line_input = text_input("Enter a term or phrase (Enter adds a line)...", line)
+                        .on_input(move |value| Message::SearchLineChanged(idx, value))
+                        .on_submit(Message::SearchLineAdded(idx))
+                        .padding(10);
+                    if idx == 0 {
+                        line_input = line_input.id(search_input_id());
+                    }
+                    let remove_button = button(text("−"))
+                        .on_press(Message::SearchLineRemoved(idx))
+                        .padding(10);
+                    lines = lines.push(Row::new().spacing(5).push(line_input).push(remove_button));
+                }
+                lines
+                    .push(checkbox(
+                        "Also match phrases across verse boundaries (slower)",
+                        self.cross_verse_phrases,
+                        Message::CrossVersePhrasesToggled,
+                    ))
+                    .into()
+            }
+            SearchMode::WordCount => Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(text("Verses with").style(NormalText))
+                .push(pick_list(&WordCountOp::ALL[..], Some(self.word_count_op), Message::WordCountOpChanged).padding(10))
+                .push(
+                    text_input("Number of words...", &self.word_count_input)
+                        .id(search_input_id())
+                        .on_input(Message::WordCountChanged)
+                        .on_submit(Message::SearchSubmitted)
+                        .padding(10)
+                        .width(Length::Fixed(150.0)),
+                )
+                .push(text("words").style(NormalText))
+                .into(),
+            SearchMode::Plain => Column::new()
+                .spacing(5)
+                .push(
+                    text_input("Enter advanced search query...", &self.search_input)
+                        .id(search_input_id())
+                        .on_input(Message::SearchChanged)
+                        .on_submit(Message::SearchSubmitted)
+                        .padding(10),
+                )
+                .push(checkbox(
+                    "Also match quoted phrases across verse boundaries (slower)",
+                    self.cross_verse_phrases,
+                    Message::CrossVersePhrasesToggled,
+                ))
+                .into(),
+            SearchMode::Regex => text_input(r"Enter a regular expression, e.g. \bLord\b.*God", &self.search_input)
+                .id(search_input_id())
+                .on_input(Message::SearchChanged)
+                .on_submit(Message::SearchSubmitted)
+                .padding(10)
+                .into(),
+        };
+        let mode_picker = pick_list(&SearchMode::ALL[..], Some(self.search_mode), Message::SearchModeChanged)
             .padding(10);
+        let default_mode_picker = pick_list(
+            DefaultSearchMode::choices(),
+            Some(DefaultSearchMode(self.settings.default_search_mode)),
+            Message::DefaultSearchModeChanged,
+        )
+        .padding(10);
         let search_button = button(text("Search"))
             .on_press(Message::SearchSubmitted)
             .padding(10);
+        let mut repeat_search_button = button(text("Repeat last search (Ctrl+R)")).padding(10);
+        if self.last_search.is_some() {
+            repeat_search_button = repeat_search_button.on_press(Message::RepeatLastSearch);
+        }
+        let min_len_picker = pick_list(
+            &MIN_TERM_LEN_CHOICES[..],
+            Some(self.search_options.min_term_len),
+            Message::MinTermLenChanged,
+        )
+        .padding(10);
+        let position_picker = pick_list(
+            &MatchPosition::ALL[..],
+            Some(self.search_options.matching.position),
+            Message::MatchPositionChanged,
+        )
+        .padding(10);
+        let not_semantics_picker = pick_list(
+            &NotSemantics::ALL[..],
+            Some(self.search_options.not_semantics),
+            Message::NotSemanticsChanged,
+        )
+        .padding(10);
+        let help_button = button(text(if self.settings.search_help_open { "Hide help" } else { "Search help" }))
+            .on_press(Message::SearchHelpToggled)
+            .padding(10);
+        let mut frequent_terms_row = Row::new().spacing(5).align_items(Alignment::Center);
+        let frequent_terms = self.term_frequency.top(FREQUENT_TERMS_SHOWN);
+        if !frequent_terms.is_empty() {
+            frequent_terms_row = frequent_terms_row.push(text("Frequent:").size(14).style(NormalText));
+        }
+        for (term, count) in frequent_terms {
+            frequent_terms_row = frequent_terms_row.push(
+                button(text(format!("{} ({})", term, count)).size(14))
+                    .on_press(Message::FrequentTermChosen(term.to_string()))
+                    .padding(5),
+            );
+        }
+        let highlight = self.search_terms();
         let mut search_results_column = Column::new().spacing(10);
-        if self.search_results.is_empty() {
-            search_results_column = search_results_column.push(text("No advanced search results found").style(NormalText));
+        if !self.settings.excluded_books.is_empty() {
+            let count = self.settings.excluded_books.len();
+            search_results_column = search_results_column.push(
+                text(format!("{} {} excluded from all searches", count, if count == 1 { "book" } else { "books" }))
+                    .size(12)
+                    .style(PreviewText),
+            );
+        }
+        for note in &self.search_notes {
+            search_results_column = search_results_column.push(text(note).style(NormalText));
+        }
+        if self.streaming_search.is_some() {
+            search_results_column = search_results_column
+                .push(text(format!("Searching… {} verses so far", self.search_results.len())).style(PreviewText));
+        }
+        let shown = self.search_results.len();
+        // The total is counted in SQL, before the red-letter filter drops verses.
+        let red_letter_filtered = self.red_letter_only && self.red_letter_markup.is_some();
+        let results_count = match self.search_total {
+            Some(total) if red_letter_filtered => format!(
+                "{} verses in red letter, of {} matching the query",
+                group_thousands(shown),
+                group_thousands(total)
+            ),
+            Some(total) => format!("showing 1–{} of {}", group_thousands(shown), group_thousands(total.max(shown))),
+            None if self.search_has_more => format!("{} verses shown, more available", shown),
+            None => format!("{} verses", shown),
+        };
+        if let Some(error) = &self.search_error {
+            search_results_column = search_results_column.push(text(error).style(self.highlight_style()));
+        } else if self.search_results.is_empty() {
+            if self.streaming_search.is_none() {
+                search_results_column =
+                    search_results_column.push(text("No advanced search results found").style(NormalText));
+            }
+        } else if self.chapters_only {
+            let chapters = chapter_counts(&self.search_results);
+            search_results_column = search_results_column.push(
+                text(format!("Advanced Search Results ({}, in {} chapters)", results_count, chapters.len()))
+                .style(NormalText),
+            );
+            let mut items: Vec<Element<Message>> = Vec::with_capacity(chapters.len());
+            for (idx, found) in chapters.into_iter().enumerate() {
+                let name = if self.settings.abbreviate_references { &found.short_name } else { &found.long_name };
+                let noun = if found.matches == 1 { "match" } else { "matches" };
+                let chapter_button = button(
+                    text(format!("{} {} ({} {})", name, found.chapter, found.matches, noun)).style(NormalText),
+                )
+                .style(iced::theme::Button::Text)
+                .padding(2)
+                .on_press(Message::MatchingChapterChosen(found.short_name.clone(), found.chapter));
+                items.push(striped(chapter_button, idx, self.settings.zebra_stripes));
+            }
+            search_results_column = search_results_column.push(flow_columns(items, self.settings.two_column_results));
         } else {
-            search_results_column = search_results_column.push(text(format!("Advanced Search Results ({} verses)", self.search_results.len())).style(NormalText));
-            for verse in &self.search_results {
-                let header = text(format!("{} {}:{}", verse.long_name, verse.chapter, verse.verse))
-                    .size(16)
+            search_results_column = search_results_column.push(text(format!("Advanced Search Results ({})", results_count)).size(self.font_size).style(NormalText));
+            let mut items: Vec<Element<Message>> = Vec::with_capacity(self.search_results.len());
+            for (idx, verse) in self.search_results.iter().enumerate() {
+                let header = text(verse.reference(self.settings.abbreviate_references))
+                    .size(self.font_size)
                     .style(NormalText);
-                let segments = split_for_highlight(&verse.text, &self.search_input);
-                let mut verse_text_row = Row::new().spacing(0);
-                for (segment, is_highlight) in segments {
-                    let seg_text = if is_highlight {
-                        text(segment).style(HighlightText)
-                    } else {
-                        text(segment).style(NormalText)
-                    };
-                    verse_text_row = verse_text_row.push(seg_text);
+                let badge = self.baseline_flags.get(&(verse.book_number, verse.chapter, verse.verse)).map(|flag| match flag {
+                    Some(true) => text("same as baseline").size(12).style(PreviewText),
+                    Some(false) => text("differs from baseline").size(12).style(self.highlight_style()),
+                    None => text("not in baseline").size(12).style(PreviewText),
+                });
+                let mut header = Row::new().spacing(10).align_items(Alignment::Center).push(header);
+                if let Some(badge) = badge {
+                    header = header.push(badge);
                 }
-                search_results_column = search_results_column.push(
-                    Column::new().spacing(5).push(header).push(verse_text_row)
+                header = header.push(
+                    button(text("Context").size(12))
+                        .on_press(Message::ShowContext(idx))
+                        .padding(3),
                 );
+                let segments = self
+                    .search_segments
+                    .segments(
+                        verse,
+                        &highlight,
+                        self.search_options.matching,
+                        self.search_options.max_highlights,
+                        self.red_letter_markup.as_ref(),
+                    );
+                let verse_text_row: Element<Message> = if self.raw_markup {
+                    text(verse_layout(&verse.text, self.poetry_layout)).size(self.font_size).style(NormalText).into()
+                } else {
+                    segments_row(
+                        segments,
+                        self.red_letter_render,
+                        self.poetry_layout,
+                        self.show_strongs,
+                        self.highlight_style(),
+                        self.font_size,
+                    )
+                    .into()
+                };
+                let mut item = Column::new().spacing(5).push(header).push(verse_text_row);
+                if self.settings.show_raw_text {
+                    item = item.push(raw_text_line(&verse.text));
+                }
+                items.push(striped(item, idx, self.settings.zebra_stripes));
+            }
+            if self.group_by_chapter {
+                let mut items = items.into_iter();
+                for ((book, chapter), members) in group_by_chapter(&self.search_results) {
+                    let group_items: Vec<Element<Message>> = items.by_ref().take(members.len()).collect();
+                    let key = (book, chapter);
+                    let collapsed = self.collapsed_chapters.contains(&key);
+                    let first = members[0];
+                    let name = if self.settings.abbreviate_references { &first.short_name } else { &first.long_name };
+                    let noun = if members.len() == 1 { "match" } else { "matches" };
+                    let group_header = button(
+                        text(format!("{} {} {} ({} {})", if collapsed { "+" } else { "-" }, name, chapter, members.len(), noun))
+                            .size(self.font_size)
+                            .style(NormalText),
+                    )
+                    .style(iced::theme::Button::Text)
+                    .padding(2)
+                    .on_press(Message::ChapterGroupToggled(key));
+                    search_results_column = search_results_column.push(group_header);
+                    if !collapsed {
+                        search_results_column =
+                            search_results_column.push(flow_columns(group_items, self.settings.two_column_results));
+                    }
+                }
+            } else {
+                search_results_column = search_results_column.push(flow_columns(items, self.settings.two_column_results));
             }
         }
-        let search_scroll = Scrollable::new(search_results_column).height(Length::Fixed(200.0));
-        let advanced_search_section = Column::new()
+        if self.search_has_more && self.streaming_search.is_none() {
+            search_results_column =
+                search_results_column.push(button(text("Load more")).on_press(Message::LoadMore).padding(5));
+        }
+        // Distribution of the matches across the canon, one cell per book.
+        let heatmap = (!self.search_results.is_empty() && !self.books.is_empty()).then(|| {
+            let counts = count_by_book(&self.search_results);
+            let max = counts.values().copied().max().unwrap_or(0);
+            let mut strip = Row::new().spacing(1).width(Length::Fill);
+            for book in &self.books {
+                let count = counts.get(book.short_name.as_str()).copied().unwrap_or(0);
+                let noun = if count == 1 { "match" } else { "matches" };
+                let cell = Container::new(Space::new(Length::Fill, Length::Fixed(14.0)))
+                    .width(Length::Fill)
+                    .style(HeatCell(heat_color(count, max)));
+                strip = strip.push(tooltip(
+                    cell,
+                    format!("{}: {} {}", book.long_name, count, noun),
+                    tooltip::Position::Bottom,
+                ));
+            }
+            strip
+        });
+        let search_scroll = Scrollable::new(search_results_column).height(self.panel_height(ResultsPanel::Search));
+        let mut advanced_search_section = Column::new()
             .spacing(10)
             .push(search_input)
-            .push(search_button)
-            .push(search_scroll);
+            .push(frequent_terms_row)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(search_button)
+                    .push(repeat_search_button)
+                    .push(text("Min term length").style(NormalText))
+                    .push(min_len_picker)
+                    .push(position_picker)
+                    .push(checkbox(
+                        "Case sensitive",
+                        self.search_options.matching.case_sensitive,
+                        Message::CaseSensitivityToggled,
+                    ))
+                    .push(checkbox(
+                        "Whole word only",
+                        self.search_options.matching.whole_word,
+                        Message::WholeWordToggled,
+                    ))
+                    .push(mode_picker)
+                    .push(default_mode_picker)
+                    .push(help_button),
+            );
+        let max_highlights_picker = pick_list(
+            &MAX_HIGHLIGHT_CHOICES[..],
+            Some(self.search_options.max_highlights),
+            Message::MaxHighlightsChanged,
+        )
+        .padding(10);
+        if self.settings.search_help_open {
+            let mut help = Column::new().spacing(3).push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text("NOT terms").size(14).style(NormalText))
+                    .push(not_semantics_picker),
+            );
+            for rule in search_help(&self.search_options) {
+                help = help.push(text(format!("• {}", rule)).size(14).style(NormalText));
+            }
+            advanced_search_section = advanced_search_section.push(help);
+        }
+        let baseline_picker = pick_list(
+            &self.available_modules[..],
+            self.baseline.clone(),
+            Message::BaselineChanged,
+        )
+        .placeholder("Compare hits with a baseline...")
+        .padding(10);
+        let mut baseline_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text("Baseline").style(NormalText))
+            .push(baseline_picker);
+        if self.baseline.is_some() {
+            baseline_row = baseline_row.push(button(text("Clear")).on_press(Message::BaselineCleared).padding(10));
+        }
+        advanced_search_section = advanced_search_section.push(baseline_row);
+        if self.red_letter_markup.is_some() {
+            advanced_search_section = advanced_search_section.push(
+                Row::new()
+                    .spacing(20)
+                    .push(checkbox("Words of Christ only", self.red_letter_only, Message::RedLetterOnlyToggled))
+                    .push(checkbox("Words of Christ in red", self.red_letter_render, Message::RedLetterRenderToggled)),
+            );
+        }
+        let mut markup_row = Row::new()
+            .spacing(20)
+            .push(checkbox("Show markup tags (raw text)", self.raw_markup, Message::RawMarkupToggled));
+        if self.has_strongs {
+            markup_row = markup_row.push(checkbox("Show Strong's numbers", self.show_strongs, Message::StrongsToggled));
+        }
+        advanced_search_section = advanced_search_section.push(markup_row);
+        let export_format_picker = pick_list(&ExportFormat::ALL[..], Some(self.export_format), Message::ExportFormatChanged)
+            .padding(10);
+        let mut copy_results_button = button(text("Copy all results")).padding(10);
+        let mut export_results_button = button(text("Export")).padding(10);
+        if !self.search_results.is_empty() {
+            copy_results_button = copy_results_button.on_press(Message::CopyAllResults(false));
+            export_results_button = export_results_button.on_press(Message::ExportResults(false));
+        }
+        let export_path_input = text_input(self.export_format.default_file(), &self.export_path)
+            .on_input(Message::ExportPathChanged)
+            .padding(10)
+            .width(Length::Fixed(150.0));
+        advanced_search_section = advanced_search_section.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(checkbox(
+                    "Two-column results",
+                    self.settings.two_column_results,
+                    Message::TwoColumnResultsToggled,
+                ))
+                .push(checkbox("Striped rows", self.settings.zebra_stripes, Message::ZebraStripesToggled))
+                .push(
+                    button(text(if self.settings.dark_theme { "Light theme" } else { "Dark theme" }))
+                        .on_press(Message::ThemeToggled)
+                        .padding(5),
+                )
+                .push(
+                    button(text("A-"))
+                        .on_press(Message::FontSizeChanged(self.font_size.saturating_sub(FONT_SIZE_STEP)))
+                        .padding(5),
+                )
+                .push(
+                    button(text("A+"))
+                        .on_press(Message::FontSizeChanged(self.font_size.saturating_add(FONT_SIZE_STEP)))
+                        .padding(5),
+                )
+                .push(checkbox(
+                    "Abbreviate book names",
+                    self.settings.abbreviate_references,
+                    Message::AbbreviateReferencesToggled,
+                ))
+                .push(checkbox(
+                    "Show raw text (developer)",
+                    self.settings.show_raw_text,
+                    Message::ShowRawTextToggled,
+                ))
+                .push(text("Highlights per verse").style(NormalText))
+                .push(max_highlights_picker)
+                .push(text("Context verses").style(NormalText))
+                .push(
+                    pick_list(
+                        &CONTEXT_VERSE_CHOICES[..],
+                        Some(self.settings.context_verses()),
+                        Message::ContextVersesChanged,
+                    )
+                    .padding(10),
+                )
+                .push(text("Page size").style(NormalText))
+                .push(
+                    pick_list(&SEARCH_PAGE_SIZES[..], Some(self.search_page_size), Message::SearchPageSizeChanged)
+                        .padding(10),
+                )
+                .push(export_format_picker)
+                .push(text("Copy at most").style(NormalText))
+                .push(
+                    pick_list(&COPY_LIMIT_CHOICES[..], Some(self.settings.copy_limit()), Message::CopyLimitChanged)
+                        .padding(10),
+                )
+                .push(copy_results_button)
+                .push(export_path_input)
+                .push(export_results_button),
+        );
+        let includable: Vec<Book> = self
+            .books
+            .iter()
+            .filter(|book| !self.settings.excluded_books.contains(&book.book_number))
+            .cloned()
+            .collect();
+        let mut exclusions_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text("Search in").style(NormalText))
+            .push(
+                pick_list(SearchScope::choices(&self.books), Some(self.scope.clone()), Message::ScopeChanged)
+                    .padding(5),
+            )
+            .push(text("Never search").style(NormalText))
+            .push(pick_list(includable, None, Message::BookExcluded).placeholder("Exclude a book...").padding(5));
+        for &book_number in &self.settings.excluded_books {
+            let name = self
+                .books
+                .iter()
+                .find(|book| book.book_number == book_number)
+                .map(|book| book.long_name.clone())
+                .unwrap_or_else(|| format!("Book {}", book_number));
+            exclusions_row = exclusions_row.push(
+                button(text(format!("{} ×", name)).size(12))
+                    .on_press(Message::BookIncluded(book_number))
+                    .padding(3),
+            );
+        }
+        let mut grouping_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(checkbox("Chapters only", self.chapters_only, Message::ChaptersOnlyToggled))
+            .push(checkbox("Group by chapter", self.group_by_chapter, Message::GroupByChapterToggled))
+            .push(panel_size_buttons(ResultsPanel::Search));
+        if self.group_by_chapter && !self.search_results.is_empty() {
+            grouping_row = grouping_row
+                .push(button(text("Collapse all").size(12)).on_press(Message::AllChapterGroupsCollapsed(true)).padding(3))
+                .push(button(text("Expand all").size(12)).on_press(Message::AllChapterGroupsCollapsed(false)).padding(3));
+        }
+        advanced_search_section = advanced_search_section.push(grouping_row).push(exclusions_row);
+        if let Some(heatmap) = heatmap {
+            advanced_search_section = advanced_search_section.push(heatmap);
+        }
+        let advanced_search_section = advanced_search_section.push(search_scroll);
 
         // Lookup Section
         let lookup_input = text_input("Enter lookup reference (e.g. Gen 6:1-6)...", &self.lookup_input)
+            .id(lookup_input_id())
             .on_input(Message::LookupChanged)
+            .on_submit(Message::LookupSubmitted)
             .padding(10);
         let lookup_button = button(text("Lookup"))
             .on_press(Message::LookupSubmitted)
             .padding(10);
+        let mut repeat_lookup_button = button(text("Repeat last lookup (Ctrl+L)")).padding(10);
+        if self.last_lookup.is_some() {
+            repeat_lookup_button = repeat_lookup_button.on_press(Message::RepeatLastLookup);
+        }
+        let mut prev_chapter_button = button(text("Previous chapter")).padding(10);
+        let mut next_chapter_button = button(text("Next chapter")).padding(10);
+        if !self.lookup_results.is_empty() {
+            prev_chapter_button = prev_chapter_button.on_press(Message::PrevChapter);
+            next_chapter_button = next_chapter_button.on_press(Message::NextChapter);
+        }
+        let surprise_button = button(text("Surprise me"))
+            .on_press(Message::SurpriseMe)
+            .padding(10);
+        let weighting_picker = pick_list(
+            &PassageWeighting::ALL[..],
+            Some(self.passage_weighting),
+            Message::PassageWeightingChanged,
+        )
+        .padding(10);
         let compare_button = button(text("Compare"))
             .on_press(Message::CompareSubmitted)
             .padding(10);
+        let dedup_picker = pick_list(
+            &DedupMode::ALL[..],
+            Some(self.compare_dedup),
+            Message::CompareDedupChanged,
+        )
+        .padding(10);
+        let mut suggestions_row = Row::new().spacing(5);
+        for book in book_suggestions(&self.books, &self.lookup_input, 8) {
+            suggestions_row = suggestions_row.push(
+                button(text(&book.long_name).size(14))
+                    .on_press(Message::BookSuggestionChosen(book.short_name.clone()))
+                    .padding(5),
+            );
+        }
+        let preview = self
+            .preview_key
+            .as_ref()
+            .and_then(|k| self.preview_cache.get(k))
+            .and_then(|p| p.as_ref())
+            .map(|(verse, p)| text(format!("{} {}", verse, display_text(p, self.raw_markup))).size(14).style(PreviewText));
+        let mut export_bookmarks_button = button(text("Export bookmarks")).padding(10);
+        if !self.bookmarks.is_empty() {
+            export_bookmarks_button = export_bookmarks_button.on_press(Message::ExportBookmarks);
+        }
+        let mut compare_bookmarks_button = button(text("Compare bookmarks")).padding(10);
+        if !self.bookmarks.is_empty() {
+            compare_bookmarks_button = compare_bookmarks_button.on_press(Message::CompareBookmarks);
+        }
+        let mut import_button = button(text("Import references")).padding(10);
+        if !self.import_path.trim().is_empty() {
+            import_button = import_button.on_press(Message::ImportReferences);
+        }
+        let mut copy_lookup_button = button(text("Copy all results")).padding(10);
+        let mut export_lookup_button = button(text("Export")).padding(10);
+        if !self.lookup_results.is_empty() {
+            copy_lookup_button = copy_lookup_button.on_press(Message::CopyAllResults(true));
+            export_lookup_button = export_lookup_button.on_press(Message::ExportResults(true));
+        }
+        let bookmarks_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text(format!("Bookmarks ({})", self.bookmarks.len())).style(NormalText))
+            .push(export_bookmarks_button)
+            .push(compare_bookmarks_button)
+            .push(
+                text_input("Reference list file...", &self.import_path)
+                    .on_input(Message::ImportPathChanged)
+                    .on_submit(Message::ImportReferences)
+                    .padding(10)
+                    .width(Length::Fixed(200.0)),
+            )
+            .push(import_button)
+            .push(copy_lookup_button)
+            .push(export_lookup_button);
+        let mut bookmarks_section = Column::new().spacing(5).push(bookmarks_row);
+        for reason in &self.import_skipped {
+            bookmarks_section = bookmarks_section.push(text(format!("Skipped {}", reason)).size(12).style(PreviewText));
+        }
         let mut lookup_results_column = Column::new().spacing(10);
-        if self.lookup_results.is_empty() {
-            lookup_results_column = lookup_results_column.push(text("No lookup results found").style(NormalText));
+        for note in &self.lookup_notes {
+            lookup_results_column = lookup_results_column.push(text(note).style(NormalText));
+        }
+        if let Some(error) = &self.lookup_error {
+            lookup_results_column = lookup_results_column.push(text(error).style(self.highlight_style()));
+        } else if self.lookup_results.is_empty() {
+            // Notes already say why a reference found nothing.
+            if self.lookup_notes.is_empty() {
+                lookup_results_column = lookup_results_column.push(text("No lookup results found").style(NormalText));
+            }
         } else {
-            lookup_results_column = lookup_results_column.push(text(format!("Lookup Results ({} verses)", self.lookup_results.len())).style(NormalText));
-            for verse in &self.lookup_results {
-                let header = text(format!("{} {}:{}", verse.long_name, verse.chapter, verse.verse))
-                    .size(16)
+            lookup_results_column = lookup_results_column.push(text(format!("Lookup Results ({} verses)", self.lookup_results.len())).size(self.font_size).style(NormalText));
+            for (idx, verse) in self.lookup_results.iter().enumerate() {
+                let header = text(verse.reference(self.settings.abbreviate_references))
+                    .size(self.font_size)
                     .style(NormalText);
-                let verse_text = text(&verse.text).style(NormalText);
-                lookup_results_column = lookup_results_column.push(
-                    Column::new().spacing(5).push(header).push(verse_text)
+                let mut header_row = Row::new().spacing(10).align_items(Alignment::Center).push(header);
+                let bookmark = Bookmark {
+                    book: verse.short_name.clone(),
+                    chapter: verse.chapter,
+                    verse: verse.verse,
+                };
+                let label = if self.bookmarks.contains(&bookmark) { "Unbookmark" } else { "Bookmark" };
+                header_row = header_row.push(
+                    button(text(label).size(12))
+                        .on_press(Message::BookmarkToggled(bookmark))
+                        .padding(3),
                 );
+                header_row = header_row.push(
+                    button(text("Copy as link").size(12))
+                        .on_press(Message::CopyVerseLink(idx))
+                        .padding(3),
+                );
+                // The plain lists read as one paragraph per verse; poetry layout keeps the module's line breaks.
+                // Cleaned text is split into segments, which drop the markup and style what it marks.
+                let red_letter = self.red_letter_markup.filter(|_| self.red_letter_render);
+                let verse_text: Element<Message> = if !self.raw_markup {
+                    segments_row(
+                        segment_verse(&verse.text, &[], TermMatching::default(), 0, red_letter.as_ref()),
+                        red_letter.is_some(),
+                        self.poetry_layout,
+                        self.show_strongs,
+                        self.highlight_style(),
+                        self.font_size,
+                    )
+                    .into()
+                } else {
+                    text(verse_layout(&verse.text, self.poetry_layout)).size(self.font_size).style(NormalText).into()
+                };
+                let mut item = Column::new().spacing(5).push(header_row).push(verse_text);
+                if self.settings.show_raw_text {
+                    item = item.push(raw_text_line(&verse.text));
+                }
+                lookup_results_column = lookup_results_column.push(item);
             }
         }
-        let lookup_scroll = Scrollable::new(lookup_results_column).height(Length::Fixed(200.0));
-        let lookup_section = Column::new()
+        let lookup_scroll = Scrollable::new(lookup_results_column).height(self.panel_height(ResultsPanel::Lookup));
+        let mut lookup_section = Column::new()
             .spacing(10)
             .push(lookup_input)
-            .push(lookup_button)
-            .push(compare_button)
+            .push(suggestions_row);
+        if let Some(preview) = preview {
+            lookup_section = lookup_section.push(preview);
+        }
+        let lookup_section = lookup_section
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(lookup_button)
+                    .push(repeat_lookup_button)
+                    .push(prev_chapter_button)
+                    .push(next_chapter_button)
+                    .push(surprise_button)
+                    .push(weighting_picker)
+                    .push(checkbox("Keep line breaks (poetry)", self.poetry_layout, Message::PoetryLayoutToggled))
+                    .push(
+                        pick_list(
+                            &LeadingNumbers::ALL[..],
+                            Some(self.settings.leading_numbers),
+                            Message::LeadingNumbersChanged,
+                        )
+                        .padding(10),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(compare_button)
+                    .push(dedup_picker)
+                    .push(
+                        text_input("Bible directory...", &self.db_dir_input)
+                            .on_input(Message::DbDirChanged)
+                            .on_submit(Message::DbDirSubmitted)
+                            .padding(10)
+                            .width(Length::Fixed(200.0)),
+                    )
+                    .push(button(text("Use directory")).on_press(Message::DbDirSubmitted).padding(10))
+                    .push(button(text("Rescan modules")).on_press(Message::RescanModules).padding(10))
+                    .push(checkbox(
+                        "Scan subdirectories",
+                        self.settings.compare_subdirectories,
+                        Message::CompareSubdirectoriesToggled,
+                    ))
+                    .push(pick_list(&CellLayout::ALL[..], Some(self.compare_layout), Message::CompareLayoutChanged))
+                    .push(pick_list(&ReviewFormat::ALL[..], Some(self.review_format), Message::ReviewFormatChanged))
+                    .push(button(text("Generate review sheet")).on_press(Message::GenerateReviewSheet).padding(10))
+                    .push(checkbox("Dim identical verses", self.dim_identical, Message::DimIdenticalToggled))
+                    .push(checkbox(
+                        "Align verse numbering",
+                        self.normalize_versification,
+                        Message::NormalizeVersificationToggled,
+                    ))
+                    .push(checkbox("Pin comparison reference", self.compare_pinned, Message::CompareReferencePinToggled)),
+            )
+            .push(bookmarks_section)
+            .push(panel_size_buttons(ResultsPanel::Lookup))
             .push(lookup_scroll);
 
         // Comparison Section
-        let compare_header = text(format!("Comparison Results ({} Bibles)", self.compare_results.len()))
-            .size(16)
+        let compare_reference = match (&self.compare_reference, self.compare_pinned) {
+            (Some(reference), true) => format!(" — {} (pinned)", reference),
+            (Some(reference), false) => format!(" — {}", reference),
+            (None, _) => String::new(),
+        };
+        let compare_header = text(format!("Comparison Results ({} Bibles){}", self.compare_results.len(), compare_reference))
+            .size(self.font_size)
             .style(NormalText);
         let mut compare_results_column = Column::new().spacing(10).push(compare_header);
+        let identical = self.dimmed_verses();
+        if self.comparing {
+            compare_results_column = compare_results_column.push(text("Comparing…").style(PreviewText));
+        }
+        if !self.pinned_verses.is_empty() {
+            let mut pinned_column = Column::new().spacing(5).push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(format!("Pinned verses ({})", self.pinned_verses.len())).size(self.font_size).style(NormalText))
+                    .push(button(text("Clear pins").size(12)).on_press(Message::ClearPinnedVerses).padding(3)),
+            );
+            for ((chapter, verse), cells) in pinned_rows(&self.compare_results, &self.pinned_verses) {
+                let dimmed = identical.contains(&(chapter, verse));
+                let style: iced::theme::Text = if dimmed { PreviewText.into() } else { NormalText.into() };
+                pinned_column = pinned_column.push(text(format!("{}:{}", chapter, verse)).size(self.font_size).style(style));
+                for (bible_name, verse_text) in cells {
+                    let verse_text = verse_text.map_or(Cow::Borrowed("(missing)"), |t| display_text(t, self.raw_markup));
+                    pinned_column = pinned_column.push(
+                        text(format!("    {}: {}", bible_name, verse_text))
+                            .size(self.font_size)
+                            .style(style),
+                    );
+                }
+            }
+            compare_results_column = compare_results_column.push(pinned_column);
+        }
         if self.compare_results.is_empty() {
             compare_results_column = compare_results_column.push(text("No comparison results found").style(NormalText));
         } else {
             for (bible_name, verses) in &self.compare_results {
-                let header = text(format!("Bible: {} ({} verses)", bible_name, verses.len()))
-                    .size(16)
+                let totals = text_totals(verses.iter().map(|v| v.text.as_str()));
+                let header = text(format!(
+                    "Bible: {} — {} chars, {} words ({} verses)",
+                    bible_name,
+                    totals.chars,
+                    totals.words,
+                    verses.len()
+                ))
+                    .size(self.font_size)
                     .style(NormalText);
                 let mut bible_column = Column::new().spacing(5).push(header);
-                for verse in verses {
-                    let verse_line = text(format!("{}:{} {}", verse.chapter, verse.verse, verse.text))
-                        .style(NormalText);
-                    bible_column = bible_column.push(verse_line);
+                // A paragraph flows each verse's piece after the last, each still pinned by a click
+                // and dimmed when identical; search highlighting is left to the verse-per-line layout.
+                if self.compare_layout == CellLayout::Paragraph {
+                    let mut paragraph = Row::new().spacing(0);
+                    for (key, piece) in cell_pieces(verses, CellLayout::Paragraph, &self.pinned_verses, self.raw_markup, self.poetry_layout) {
+                        let style: iced::theme::Text =
+                            if identical.contains(&key) { PreviewText.into() } else { NormalText.into() };
+                        paragraph = paragraph.push(
+                            button(text(piece).size(self.font_size).style(style))
+                                .style(iced::theme::Button::Text)
+                                .padding(0)
+                                .on_press(Message::CompareVersePinToggled(key)),
+                        );
+                    }
+                    bible_column = bible_column.push(paragraph);
+                    compare_results_column = compare_results_column.push(bible_column);
+                    continue;
+                }
+                let lines = cell_pieces(verses, CellLayout::VersePerLine, &self.pinned_verses, self.raw_markup, self.poetry_layout);
+                for (idx, (verse, (key, line_text))) in verses.iter().zip(lines).enumerate() {
+                    let marker = if self.pinned_verses.contains(&key) { "* " } else { "" };
+                    let style: iced::theme::Text =
+                        if identical.contains(&key) { PreviewText.into() } else { NormalText.into() };
+                    // Search terms are highlighted as in the search results, through a segment cache
+                    // of their own; dimmed identical verses, an empty query and raw markup stay plain text.
+                    let line: Element<Message> = if highlight.is_empty() || identical.contains(&key) || self.raw_markup {
+                        text(line_text).size(self.font_size).style(style).into()
+                    } else {
+                        let segments = self.compare_segments.segments(
+                            verse,
+                            &highlight,
+                            self.search_options.matching,
+                            self.search_options.max_highlights,
+                            self.red_letter_markup.as_ref(),
+                        );
+                        Row::new()
+                            .push(
+                                text(format!("{}{}:{} ", marker, verse.chapter, verse.verse_label()))
+                                    .size(self.font_size)
+                                    .style(style),
+                            )
+                            .push(segments_row(
+                                segments,
+                                false,
+                                false,
+                                self.show_strongs,
+                                self.highlight_style(),
+                                self.font_size,
+                            ))
+                            .into()
+                    };
+                    let verse_line = button(line)
+                        .style(iced::theme::Button::Text)
+                        .padding(0)
+                        .on_press(Message::CompareVersePinToggled(key));
+                    bible_column = bible_column.push(striped(verse_line, idx, self.settings.zebra_stripes));
                 }
                 compare_results_column = compare_results_column.push(bible_column);
             }
         }
-        let compare_scroll = Scrollable::new(compare_results_column).height(Length::Fixed(200.0));
+        let compare_scroll = Scrollable::new(compare_results_column).height(self.panel_height(ResultsPanel::Compare));
+
+        // Word diff of two compared translations
+        let labels: Vec<String> = self.compare_results.iter().map(|(label, _)| label.clone()).collect();
+        let column_of = |label: &Option<String>| {
+            let label = label.as_ref()?;
+            self.compare_results.iter().find(|(name, _)| name == label)
+        };
+        let mut diff_column = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(text("Word diff").style(NormalText))
+                .push(
+                    pick_list(labels.clone(), self.diff_first.clone(), Message::DiffFirstChosen)
+                        .placeholder("First translation")
+                        .padding(5),
+                )
+                .push(
+                    pick_list(labels, self.diff_second.clone(), Message::DiffSecondChosen)
+                        .placeholder("Second translation")
+                        .padding(5),
+                ),
+        );
+        if let (Some((first_name, first)), Some((second_name, second))) =
+            (column_of(&self.diff_first), column_of(&self.diff_second))
+        {
+            // Words are compared as displayed, so markup doesn't show up as changed words.
+            let (first, second) = (displayed_verses(first, self.raw_markup), displayed_verses(second, self.raw_markup));
+            let dark = self.settings.dark_theme;
+            for ((chapter, verse), diff) in pair_diff(&first, &second) {
+                let mut line = Row::new()
+                    .spacing(4)
+                    .push(text(format!("{}:{}", chapter, verse)).size(self.font_size).style(NormalText));
+                line = match diff {
+                    VerseDiff::Both(words) => diff_runs(&words).into_iter().fold(line, |line, (op, run)| {
+                        let run = text(run).size(self.font_size);
+                        line.push(match op {
+                            DiffOp::Same => run.style(NormalText),
+                            DiffOp::Added => run.style(AddedText { dark }),
+                            DiffOp::Removed => run.style(RemovedText { dark }),
+                        })
+                    }),
+                    VerseDiff::OnlyFirst(only) => line
+                        .push(text(single_line(only)).size(self.font_size).style(RemovedText { dark }))
+                        .push(text(format!("(missing in {})", second_name)).size(12).style(PreviewText)),
+                    VerseDiff::OnlySecond(only) => line
+                        .push(text(single_line(only)).size(self.font_size).style(AddedText { dark }))
+                        .push(text(format!("(missing in {})", first_name)).size(12).style(PreviewText)),
+                };
+                diff_column = diff_column.push(line);
+            }
+        }
         let comparison_section = Column::new()
             .spacing(10)
-            .push(text("Comparison Results").style(NormalText))
-            .push(compare_scroll);
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text("Comparison Results").style(NormalText))
+                    .push(panel_size_buttons(ResultsPanel::Compare)),
+            )
+            .push(compare_scroll)
+            .push(Scrollable::new(diff_column).height(Length::Fixed(150.0)));
+
+        // Bookmark Comparison Report
+        let bookmark_report_section = self.bookmark_report.as_ref().map(|report| {
+            let pages = self.bookmarks.len().div_ceil(BOOKMARK_REPORT_PAGE_SIZE).max(1);
+            let mut prev_button = button(text("Previous")).padding(5);
+            if self.bookmark_report_page > 0 {
+                prev_button = prev_button.on_press(Message::BookmarkReportPage(self.bookmark_report_page - 1));
+            }
+            let mut next_button = button(text("Next")).padding(5);
+            if self.bookmark_report_page + 1 < pages {
+                next_button = next_button.on_press(Message::BookmarkReportPage(self.bookmark_report_page + 1));
+            }
+            let controls = Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(text(format!("Bookmarks across translations (page {} of {})", self.bookmark_report_page + 1, pages)).size(self.font_size).style(NormalText))
+                .push(prev_button)
+                .push(next_button)
+                .push(button(text("Close")).on_press(Message::CloseBookmarkReport).padding(5));
+            let mut report_column = Column::new().spacing(10);
+            for (bookmark, results) in report {
+                let mut entry = Column::new().spacing(3).push(text(bookmark.to_string()).size(self.font_size).style(NormalText));
+                for (bible_name, verses) in results {
+                    let rendering = match verses.first() {
+                        Some(v) => display_text(&v.text, self.raw_markup),
+                        None => Cow::Borrowed("(not in this translation)"),
+                    };
+                    entry = entry.push(text(format!("    {}: {}", bible_name, rendering)).size(self.font_size).style(NormalText));
+                }
+                report_column = report_column.push(entry);
+            }
+            Column::new()
+                .spacing(10)
+                .push(controls)
+                .push(Scrollable::new(report_column).height(Length::Fixed(200.0)))
+        });
 
         // Combine all sections into one column.
-        let content = Column::new()
+        // Quick bar: one input for both searching and browsing.
+        let quick_bar = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(
+                text_input("Search, or type a reference like Gen 1:1-3...", &self.quick_input)
+                    .id(quick_input_id())
+                    .on_input(Message::QuickInputChanged)
+                    .on_submit(Message::QuickSubmitted)
+                    .padding(10),
+            )
+            .push(pick_list(&QueryRoute::ALL[..], Some(self.quick_route), Message::QuickRouteChanged).padding(10))
+            .push(button(text("Go")).on_press(Message::QuickSubmitted).padding(10))
+            .push(
+                pick_list(self.history.entries(), None::<HistoryEntry>, Message::HistoryChosen)
+                    .placeholder("Recent searches and lookups")
+                    .padding(10),
+            )
+            .push(checkbox("One result area", self.settings.unified_results, Message::UnifiedResultsToggled))
+            .push(
+                pick_list(
+                    &ContentWidth::CHOICES[..],
+                    Some(self.settings.max_content_width()),
+                    Message::ContentWidthChanged,
+                )
+                .padding(10),
+            );
+
+        let mut content = Column::new()
             .spacing(20)
-            .align_items(Alignment::Start)
-            .push(advanced_search_section)
-            .push(lookup_section)
-            .push(comparison_section);
+            .align_items(Alignment::Start);
+        // The translation searches and lookups read from, also shown in the window title.
+        if let Some(label) = &self.main_label {
+            content = content.push(text(format!("Bible Lookup — {}", label)).size(self.font_size + 4).style(NormalText));
+        }
+        if let Some(status) = &self.status {
+            content = content.push(text(status).style(self.highlight_style()));
+        }
+        let mut content = content.push(quick_bar).push(text(SHORTCUT_HINT).size(12).style(PreviewText));
+        if let Some(verse) = &self.verse_of_day {
+            content = content.push(
+                Column::new()
+                    .spacing(5)
+                    .push(
+                        text(format!("Verse of the day — {}", verse.reference(self.settings.abbreviate_references)))
+                            .size(self.font_size)
+                            .style(NormalText),
+                    )
+                    .push(text(single_line(&clean_text(&verse.text)).into_owned()).size(self.font_size).style(NormalText)),
+            );
+        }
+        // With one result area, only the panel the last quick query went to is shown; the
+        // tab buttons switch to the other one.
+        if self.settings.unified_results {
+            let lookup_shown = self.last_route == Some(QueryRoute::Lookup);
+            let mut search_tab = button(text("Search results")).padding(5);
+            let mut lookup_tab = button(text("Lookup results")).padding(5);
+            if lookup_shown {
+                search_tab = search_tab.on_press(Message::ResultsTabChosen(QueryRoute::Search));
+            } else {
+                lookup_tab = lookup_tab.on_press(Message::ResultsTabChosen(QueryRoute::Lookup));
+            }
+            content = content.push(Row::new().spacing(5).push(search_tab).push(lookup_tab));
+        }
+        content = match (self.settings.unified_results, self.last_route) {
+            (true, Some(QueryRoute::Lookup)) => content.push(lookup_section),
+            (true, _) => content.push(advanced_search_section),
+            (false, _) => content.push(advanced_search_section).push(lookup_section),
+        };
+        let mut content = content.push(comparison_section);
+        if let Some(section) = bookmark_report_section {
+            content = content.push(section);
+        }
 
-        // Wrap the entire content in a scrollable container.
-        Scrollable::new(content).into()
+        // Wrap the entire content in a scrollable container, beside the navigator sidebar.
+        let mut sidebar = Column::new()
+            .spacing(5)
+            .push(button(text(if self.settings.navigator_open { "Hide books" } else { "Books" })).on_press(Message::NavigatorToggled).padding(5));
+        if self.settings.navigator_open {
+            let mut tree = Column::new().spacing(2);
+            for book in self.navigator.as_deref().unwrap_or_default() {
+                let expanded = self.expanded_books.contains(&book.short_name);
+                tree = tree.push(
+                    button(text(format!("{} {}", if expanded { "-" } else { "+" }, book.long_name)).size(14))
+                        .style(iced::theme::Button::Text)
+                        .padding(2)
+                        .on_press(Message::NavigatorBookToggled(book.short_name.clone())),
+                );
+                if expanded {
+                    let mut chapters = Column::new().spacing(2);
+                    for chunk in book.chapters.chunks(6) {
+                        let mut chapter_row = Row::new().spacing(2);
+                        for &(chapter, last_verse) in chunk {
+                            chapter_row = chapter_row.push(
+                                button(text(chapter.to_string()).size(12))
+                                    .padding(2)
+                                    .on_press(Message::NavigateChapter(book.short_name.clone(), chapter, last_verse)),
+                            );
+                        }
+                        chapters = chapters.push(chapter_row);
+                    }
+                    tree = tree.push(Row::new().push(Space::with_width(Length::Fixed(12.0))).push(chapters));
+                }
+            }
+            sidebar = sidebar.push(Scrollable::new(tree).height(Length::Fill));
+        }
+        let sidebar_width = if self.settings.navigator_open { 220.0 } else { 90.0 };
+        // On wide windows the content stops at the maximum width and is centered.
+        let content = match self.settings.max_content_width() {
+            ContentWidth(0) => Container::new(content),
+            ContentWidth(width) => Container::new(Container::new(content).max_width(width as f32)).center_x(),
+        };
+        Row::new()
+            .spacing(10)
+            .push(Container::new(sidebar).width(Length::Fixed(sidebar_width)))
+            .push(Scrollable::new(content.width(Length::Fill)))
+            .into()
     }
 }
 
 fn main() {
+    // A `biblical_gui://ref/Book.Chapter.Verse` argument opens that verse.
+    let initial_lookup = std::env::args().nth(1).and_then(|arg| parse_verse_uri(&arg)).map(|verse| {
+        format!("{} {}:{}-{}", verse.book, verse.chapter, verse.verse, verse.verse)
+    });
+    // The window opens where it was last closed; the app saves that itself on close.
+    let saved = settings_path().map(|p| load_settings(&p)).unwrap_or_default();
     let settings = Settings {
         window: iced::window::Settings {
-            size: (800, 600),
+            size: saved.window_size.and_then(remembered_window_size).unwrap_or(DEFAULT_WINDOW_SIZE),
+            position: saved
+                .window_position
+                .and_then(remembered_window_position)
+                .map_or(window::Position::Default, |(x, y)| window::Position::Specific(x, y)),
             ..Default::default()
         },
+        exit_on_close_request: false,
+        flags: initial_lookup,
         ..Default::default()
     };
-    App::run(settings);
+    if let Err(e) = App::run(settings) {
+        eprintln!("Failed to start application: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::{fixture_app, fixture_bible, temp_dir, write_fixture_module};
+
+    fn references(verses: &[Verse]) -> Vec<String> {
+        verses.iter().map(|v| v.reference(true)).collect()
+    }
+
+    #[test]
+    fn search_query_runs_against_a_module() {
+        let bible = fixture_bible();
+        let clause = build_where_clause("God AND beginning", &SearchOptions::default());
+        let mut found = Vec::new();
+        run_search(&bible.conn, &bible.schema, &clause.sql, &clause.params, 100, 0, |verse| {
+            found.push(verse);
+            true
+        })
+        .unwrap();
+        assert_eq!(references(&found), ["Gen 1:1", "John 1:1"]);
+        assert_eq!(count_verses(&bible.conn, &bible.schema, &clause.sql, &clause.params).unwrap(), 2);
+    }
+
+    fn search(bible: &Bible, clause: &str, params: &[String]) -> Vec<String> {
+        let mut found = Vec::new();
+        run_search(&bible.conn, &bible.schema, clause, params, 100, 0, |verse| {
+            found.push(verse.reference(true));
+            true
+        })
+        .unwrap();
+        found
+    }
+
+    #[test]
+    fn scope_applies_to_every_phrase_of_a_lines_search() {
+        let bible = fixture_bible();
+        let lines = ["beginning".to_string(), "only begotten".to_string()];
+        let clause = build_lines_clause(&lines, TermMatching::default());
+        let column = bible.schema.verse_book();
+        let scoped = narrowed_where_clause(clause.sql.clone(), &BTreeSet::new(), &SearchScope::OldTestament, &column);
+        assert_eq!(scoped, format!("({}) AND CAST({} AS INTEGER) BETWEEN 10 AND 460", clause.sql, column));
+        assert_eq!(search(&bible, &scoped, &clause.params), ["Gen 1:1"]);
+
+        let scoped = narrowed_where_clause(clause.sql.clone(), &BTreeSet::new(), &SearchScope::NewTestament, &column);
+        assert_eq!(search(&bible, &scoped, &clause.params), ["John 1:1", "John 3:16", "1Jn 4:9"]);
+    }
+
+    #[test]
+    fn excluded_books_and_scope_combine() {
+        let bible = fixture_bible();
+        let clause = build_where_clause("God", &SearchOptions::default());
+        let column = bible.schema.verse_book();
+        let excluded = BTreeSet::from([500]);
+        let narrowed = narrowed_where_clause(clause.sql.clone(), &excluded, &SearchScope::NewTestament, &column);
+        assert_eq!(
+            narrowed,
+            format!("(({}) AND CAST({c} AS INTEGER) NOT IN (500)) AND CAST({c} AS INTEGER) BETWEEN 470 AND 730", clause.sql, c = column)
+        );
+        assert_eq!(search(&bible, &narrowed, &clause.params), ["1Jn 4:9", "Jude 1:1"]);
+        assert_eq!(narrowed_where_clause(clause.sql.clone(), &BTreeSet::new(), &SearchScope::All, &column), clause.sql);
+    }
+
+    #[test]
+    fn lookup_reference_runs_against_a_module() {
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "Psalms 23:2-4").unwrap();
+        assert_eq!(references(&verses), ["Ps 23:2", "Ps 23:3", "Ps 23:4"]);
+        let whole_book = run_lookup(&bible.conn, &bible.schema, &books, "Jude").unwrap();
+        assert_eq!(references(&whole_book), ["Jude 1:1", "Jude 1:2"]);
+        assert!(matches!(
+            run_lookup(&bible.conn, &bible.schema, &books, "Hezekiah 1:1"),
+            Err(LookupError::NoSuchBook(_))
+        ));
+        assert!(matches!(
+            run_lookup(&bible.conn, &bible.schema, &books, "Gen 2:1"),
+            Err(LookupError::EmptyRange(_))
+        ));
+    }
+
+    fn query_nodes(query: &str) -> Vec<QueryNode<'_>> {
+        parse_query(&tokenize_query(query)).members
+    }
+
+    fn term(negated: bool, text: &str) -> QueryNode<'_> {
+        QueryNode::Term { negated, text }
+    }
+
+    #[test]
+    fn not_negates_the_following_term() {
+        assert_eq!(query_nodes("faith NOT fear"), [term(false, "faith"), term(true, "fear")]);
+        assert_eq!(query_nodes("NOT sin"), [term(true, "sin")]);
+        let clause = build_where_clause("faith NOT fear", &SearchOptions::default());
+        assert_eq!(clause.params, ["faith", "fear"]);
+        assert!(clause.sql.ends_with(&format!("AND {}", TermMatching::default().condition(true))));
+    }
+
+    #[test]
+    fn trailing_not_is_ignored() {
+        assert_eq!(query_nodes("love NOT"), [term(false, "love")]);
+        assert_eq!(build_where_clause("love NOT", &SearchOptions::default()).params, ["love"]);
+    }
+
+    #[test]
+    fn words_starting_with_not_are_plain_terms() {
+        assert_eq!(query_nodes("nothing"), [term(false, "nothing")]);
+        assert_eq!(query_nodes("Note NOTHING"), [term(false, "Note"), term(false, "NOTHING")]);
+        assert_eq!(query_nodes("faith NOTworks"), [term(false, "faith"), term(true, "works")]);
+        let bible = fixture_bible();
+        let clause = build_where_clause("nothing", &SearchOptions::default());
+        assert_eq!(clause.params, ["nothing"]);
+        assert!(search(&bible, &clause.sql, &clause.params).is_empty());
+    }
+
+    #[test]
+    fn whole_word_search_skips_longer_words() {
+        let bible = fixture_bible();
+        let options = SearchOptions {
+            matching: TermMatching { whole_word: true, ..TermMatching::default() },
+            ..SearchOptions::default()
+        };
+        let clause = build_where_clause("art", &options);
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Ps 23:4"]);
+        let anywhere = build_where_clause("art", &SearchOptions::default());
+        assert!(search(&bible, &anywhere.sql, &anywhere.params).contains(&"Ps 119:2".to_string()));
+    }
+
+    #[test]
+    fn invalid_regex_sets_a_search_error() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.search_input = "God".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        let running = app.streaming_search.as_ref().map(|search| search.id).unwrap();
+        let history = app.history.entries().to_vec();
+
+        app.search_mode = SearchMode::Regex;
+        app.search_input = r"\bLord\b.*(God".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        assert!(app.search_error.as_deref().is_some_and(|e| e.starts_with("Invalid regular expression")));
+        assert!(app.streaming_search.is_none());
+        assert!(app.search_page.is_none());
+        assert_eq!(app.history.entries(), history);
+        assert_eq!(app.last_search.as_ref().map(|last| last.input.as_str()), Some("God"));
+        let _ = app.update(Message::SearchBatch(running, vec![test_fixture::verse("Gen", 1, 1, "In the beginning God")]));
+        let _ = app.update(Message::LoadMore);
+        assert!(app.search_results.is_empty());
+        assert!(app.streaming_search.is_none());
+
+        app.search_input = r"\bLORD\b.*shepherd".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        assert_eq!(app.search_error, None);
+        let clause = build_regex_clause(&app.search_input).unwrap();
+        assert_eq!(search(app.db.as_ref().unwrap(), &clause.sql, &clause.params), ["Ps 23:1"]);
+    }
+
+    #[test]
+    fn streaming_search_counts_after_the_first_page() {
+        let path = temp_dir("streaming").join("Fixture.SQLite3");
+        write_fixture_module(&path, "Fixture Bible");
+        let clause = build_where_clause("God", &SearchOptions::default());
+        let page = StreamingSearch {
+            id: 1,
+            path,
+            where_clause: clause.sql,
+            params: clause.params,
+            limit: 2,
+            offset: 0,
+            count: true,
+        };
+        let connections = BibleCache::default();
+        let (sender, mut receiver) = mpsc::unbounded();
+        stream_verses(page.clone(), &connections, sender);
+        let mut events = Vec::new();
+        while let Ok(Some(event)) = receiver.try_next() {
+            events.push(event);
+        }
+        assert!(matches!(&events[..], [SearchEvent::Batch(batch), SearchEvent::Total(7)] if batch.len() == 2));
+        // The connection is kept for the next page.
+        assert!(connections.lock().unwrap().contains_key(&page.path));
+
+        let (sender, mut receiver) = mpsc::unbounded();
+        stream_verses(StreamingSearch { offset: 2, count: false, ..page }, &connections, sender);
+        assert!(matches!(receiver.try_next(), Ok(Some(SearchEvent::Batch(batch))) if batch.len() == 2));
+        assert!(matches!(receiver.try_next(), Ok(None)));
+    }
+
+    #[test]
+    fn groups_have_their_own_operator() {
+        let like = TermMatching::default().condition(false);
+        let clause = build_where_clause("(faith OR hope) AND love", &SearchOptions::default());
+        assert_eq!(clause.sql, format!("(({like} OR {like}) AND {like})", like = like));
+        assert_eq!(clause.params, ["faith", "hope", "love"]);
+        assert_eq!(
+            query_nodes("(faith OR hope) AND love"),
+            [
+                QueryNode::Group {
+                    negated: false,
+                    group: QueryGroup { operator: "OR", members: vec![term(false, "faith"), term(false, "hope")] },
+                },
+                term(false, "love"),
+            ]
+        );
+    }
+
+    #[test]
+    fn not_inside_a_group_excludes_within_it() {
+        let (like, not_like) = (TermMatching::default().condition(false), TermMatching::default().condition(true));
+        let clause = build_where_clause("love AND (faith OR hope NOT works)", &SearchOptions::default());
+        assert_eq!(clause.sql, format!("({like} AND (({like} OR {like}) AND {not_like}))", like = like, not_like = not_like));
+        assert_eq!(clause.params, ["love", "faith", "hope", "works"]);
+    }
+
+    #[test]
+    fn negated_group_is_excluded_after_the_query() {
+        let like = TermMatching::default().condition(false);
+        let clause = build_where_clause("NOT (fear OR dread) OR love", &SearchOptions::default());
+        assert_eq!(clause.sql, format!("({like}) AND NOT ({like} OR {like})", like = like));
+        assert_eq!(clause.params, ["love", "fear", "dread"]);
+
+        let operand = SearchOptions { not_semantics: NotSemantics::Operand, ..SearchOptions::default() };
+        let clause = build_where_clause("NOT (fear OR dread) OR love", &operand);
+        assert_eq!(clause.sql, format!("(NOT ({like} OR {like}) OR {like})", like = like));
+        assert_eq!(clause.params, ["fear", "dread", "love"]);
+    }
+
+    #[test]
+    fn params_follow_placeholder_order() {
+        let bible = fixture_bible();
+        let clause = build_where_clause("(shepherd OR pastures) NOT want AND LORD", &SearchOptions::default());
+        assert_eq!(clause.params, ["shepherd", "pastures", "LORD", "want"]);
+        assert_eq!(clause.sql.matches('?').count(), clause.params.len());
+        assert!(search(&bible, &clause.sql, &clause.params).is_empty());
+        let clause = build_where_clause("(shepherd OR table) AND LORD", &SearchOptions::default());
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Ps 23:1"]);
+    }
+
+    #[test]
+    fn export_asks_before_overwriting() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        let path = temp_dir("export").join("results.txt");
+        fs::write(&path, "keep me").unwrap();
+        app.lookup_results = vec![test_fixture::verse("John", 11, 35, "Jesus wept.")];
+        app.export_path = path.display().to_string();
+        let _ = app.update(Message::ExportResults(true));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        assert!(app.status.as_deref().is_some_and(|s| s.contains("already exists")));
+
+        let _ = app.update(Message::ExportResults(true));
+        assert!(fs::read_to_string(&path).unwrap().contains("Jesus wept."));
+        assert_eq!(app.export_overwrite, None);
+    }
+
+    fn key_press(key_code: keyboard::KeyCode, modifiers: keyboard::Modifiers) -> Event {
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers })
+    }
+
+    #[test]
+    fn escape_clears_the_input_being_typed_in() {
+        let escape = key_press(keyboard::KeyCode::Escape, keyboard::Modifiers::default());
+        assert!(matches!(hotkey(escape.clone(), event::Status::Captured), Some(Message::EscapePressed)));
+        assert!(hotkey(escape, event::Status::Ignored).is_none());
+
+        let mut app = fixture_app(Some(fixture_bible()));
+        let _ = app.update(Message::SearchChanged("faith".to_string()));
+        let _ = app.update(Message::LookupChanged("Gen 1".to_string()));
+        let _ = app.update(Message::EscapePressed);
+        assert_eq!(app.lookup_input, "");
+        assert_eq!(app.search_input, "faith");
+        let _ = app.update(Message::EscapePressed);
+        assert_eq!(app.search_input, "faith");
+
+        app.search_mode = SearchMode::Lines;
+        app.search_lines = vec!["living water".to_string(), "well".to_string()];
+        let _ = app.update(Message::SearchLineChanged(1, "wells".to_string()));
+        let _ = app.update(Message::EscapePressed);
+        assert_eq!(app.search_lines, ["living water", ""]);
+    }
+
+    #[test]
+    fn repeat_shortcuts_stay_inert_while_typing() {
+        let ctrl_r = key_press(keyboard::KeyCode::R, keyboard::Modifiers::CTRL);
+        assert!(matches!(hotkey(ctrl_r.clone(), event::Status::Ignored), Some(Message::RepeatLastSearch)));
+        assert!(hotkey(ctrl_r, event::Status::Captured).is_none());
+        let ctrl_l = key_press(keyboard::KeyCode::L, keyboard::Modifiers::CTRL);
+        assert!(matches!(hotkey(ctrl_l.clone(), event::Status::Ignored), Some(Message::RepeatLastLookup)));
+        assert!(hotkey(ctrl_l, event::Status::Captured).is_none());
+        let ctrl_m = key_press(keyboard::KeyCode::M, keyboard::Modifiers::CTRL);
+        assert!(matches!(hotkey(ctrl_m.clone(), event::Status::Ignored), Some(Message::CycleSearchMode)));
+        assert!(hotkey(ctrl_m, event::Status::Captured).is_none());
+        let ctrl_f = key_press(keyboard::KeyCode::F, keyboard::Modifiers::CTRL);
+        assert!(matches!(hotkey(ctrl_f, event::Status::Captured), Some(Message::FocusSearch)));
+        let plain_r = key_press(keyboard::KeyCode::R, keyboard::Modifiers::default());
+        assert!(hotkey(plain_r, event::Status::Ignored).is_none());
+    }
+
+    #[test]
+    fn last_queries_are_remembered_for_repeating() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.search_input = "shepherd".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        app.search_input = "something else".to_string();
+        app.search_mode = SearchMode::Regex;
+        let _ = app.update(Message::RepeatLastSearch);
+        assert_eq!(app.search_input, "shepherd");
+        assert_eq!(app.search_mode, SearchMode::Plain);
+
+        let _ = app.update(Message::RepeatLastLookup);
+        assert!(app.lookup_results.is_empty());
+        app.lookup_input = "Ps 23:1".to_string();
+        let _ = app.update(Message::LookupSubmitted);
+        assert_eq!(app.last_lookup.as_deref(), Some("Ps 23:1"));
+        app.lookup_input = "Gen 1".to_string();
+        let _ = app.update(Message::RepeatLastLookup);
+        assert_eq!(app.lookup_input, "Ps 23:1");
+        assert_eq!(references(&app.lookup_results), ["Ps 23:1"]);
+    }
+
+    #[test]
+    fn minimized_window_geometry_is_not_remembered() {
+        assert_eq!(remembered_window_size((0, 0)), None);
+        assert_eq!(remembered_window_size((1024, 0)), None);
+        assert_eq!(remembered_window_size((1024, 768)), Some((1024, 768)));
+        assert_eq!(remembered_window_size((120, 40)), Some(MIN_WINDOW_SIZE));
+        assert_eq!(remembered_window_position((-32000, -32000)), None);
+        assert_eq!(remembered_window_position((-8, 20)), Some((-8, 20)));
+
+        let mut app = fixture_app(None);
+        let _ = app.update(Message::WindowResized(1024, 768));
+        let _ = app.update(Message::WindowMoved(100, 50));
+        let _ = app.update(Message::WindowResized(0, 0));
+        let _ = app.update(Message::WindowMoved(-32000, -32000));
+        assert_eq!(app.settings.window_size, Some((1024, 768)));
+        assert_eq!(app.settings.window_position, Some((100, 50)));
+    }
+
+    #[test]
+    fn verses_mapped_onto_one_number_are_merged() {
+        let modern = [VERSIFICATIONS[1]];
+        let verses = vec![
+            test_fixture::verse("3Jn", 1, 13, "I had many things to write,"),
+            test_fixture::verse("3Jn", 1, 14, "But I trust I shall shortly see thee."),
+            test_fixture::verse("3Jn", 1, 15, "Peace be to thee."),
+        ];
+        let merged = canonical_verses(verses, &modern, (1, 14), (1, 15));
+        assert_eq!(references(&merged), ["3Jn 1:14"]);
+        assert_eq!(merged[0].text, "But I trust I shall shortly see thee. Peace be to thee.");
+
+        let hebrew = [VERSIFICATIONS[0]];
+        let verses = vec![
+            test_fixture::verse("Mal", 3, 18, "Then shall ye return"),
+            test_fixture::verse("Mal", 3, 19, "For, behold, the day cometh"),
+        ];
+        assert_eq!(references(&canonical_verses(verses, &hebrew, (3, 18), (4, 1))), ["Mal 3:18", "Mal 4:1"]);
+    }
+
+    #[test]
+    fn scan_discarded_during_a_compare_is_not_cached() {
+        let mut app = fixture_app(None);
+        let module = DiscoveredModule {
+            path: PathBuf::from("KJV.SQLite3"),
+            description: "King James Version".to_string(),
+            duplicates: Vec::new(),
+        };
+        let started = app.module_scan_generation;
+        let _ = app.update(Message::CompareDedupChanged(DedupMode::Off));
+        let _ = app.update(Message::CompareCompleted(started, vec![module.clone()], Vec::new()));
+        assert_eq!(app.discovered_modules, None);
+
+        let current = app.module_scan_generation;
+        let _ = app.update(Message::CompareCompleted(current, vec![module.clone()], Vec::new()));
+        assert_eq!(app.discovered_modules, Some(vec![module]));
+    }
+
+    #[test]
+    fn rescan_replaces_the_cached_module_list() {
+        let dir = temp_dir("rescan");
+        write_fixture_module(&dir.join("A.SQLite3"), "Alpha");
+        let mut app = fixture_app(None);
+        app.db_dir = dir.clone();
+        let _ = app.update(Message::RescanModules);
+        assert_eq!(app.discovered_modules.as_ref().map(Vec::len), Some(1));
+        assert_eq!(app.available_modules.len(), 1);
+
+        write_fixture_module(&dir.join("B.SQLite3"), "Beta");
+        let _ = app.update(Message::RescanModules);
+        assert_eq!(app.discovered_modules.as_ref().map(Vec::len), Some(2));
+        assert_eq!(app.available_modules.len(), 2);
+        assert!(app.status.as_deref().is_some_and(|status| status.starts_with("Found 2 modules")));
+    }
+
+    #[test]
+    fn anchored_terms_see_past_markup() {
+        let bible = fixture_bible();
+        let text = test_fixture::FIXTURE_VERSES[15].3;
+        for (position, query, highlighted) in [
+            (MatchPosition::Start, "For", "For"),
+            (MatchPosition::End, "\"everlasting life.\"", "everlasting life."),
+        ] {
+            let options = SearchOptions {
+                matching: TermMatching { position, ..TermMatching::default() },
+                ..SearchOptions::default()
+            };
+            let clause = build_where_clause(query, &options);
+            assert_eq!(search(&bible, &clause.sql, &clause.params), ["John 3:16"]);
+            let terms = highlight_terms(query, &options);
+            let (segments, _) = segment_verse(text, &terms, options.matching, 50, None);
+            let found: Vec<&str> = segments.iter().filter(|s| s.highlight).map(|s| s.text).collect();
+            assert_eq!(found, [highlighted]);
+        }
+    }
+
+    #[test]
+    fn several_anchored_and_terms_are_noted() {
+        let options = SearchOptions {
+            matching: TermMatching { position: MatchPosition::Start, ..TermMatching::default() },
+            ..SearchOptions::default()
+        };
+        assert_eq!(build_where_clause("the", &options).notes, Vec::<String>::new());
+        assert_eq!(build_where_clause("the OR and", &options).notes, Vec::<String>::new());
+        let clause = build_where_clause("the AND and", &options);
+        assert_eq!(
+            clause.notes,
+            ["2 terms joined by AND must all begin the verse; that only matches where each is part of the longest"]
+        );
+        assert!(search(&fixture_bible(), &clause.sql, &clause.params).is_empty());
+    }
+
+    #[test]
+    fn main_translation_is_compared_exactly_once() {
+        let dir = temp_dir("compare-main");
+        write_fixture_module(&dir.join("KJV.SQLite3"), "King James Version");
+        write_fixture_module(&dir.join("Other.SQLite3"), "Other Version");
+        let mut cache = HashMap::new();
+        let modules = discover_modules(&dir, false, DedupMode::Off, &mut cache);
+        assert_eq!(modules.len(), 2);
+
+        // The main Bible opened through a path spelled differently from the scanned one.
+        let main = open_bible(&dir.join(".").join("KJV.SQLite3")).unwrap();
+        let main_path = canonical_path(&main.path);
+        let others = modules_besides(&modules, Some(&main_path));
+        assert_eq!(others.iter().map(DiscoveredModule::label).collect::<Vec<_>>(), ["Other Version"]);
+
+        let bibles = open_modules(&others, &mut cache);
+        let options = ColumnOptions { normalize: false, leading: LeadingNumbers::Keep };
+        let mut results = compare_passage(&bibles, "John", 11, 35, 11, 35, options);
+        let main_column = compare_passage(&[(String::new(), &main)], "John", 11, 35, 11, 35, options);
+        insert_main_column(&mut results, &modules, &main_path, main_column.into_iter().next().unwrap().1);
+        let labels: Vec<&str> = results.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, ["King James Version", "Other Version"]);
+        assert!(results.iter().all(|(_, verses)| references(verses) == ["John 11:35"]));
+    }
+
+    #[test]
+    fn search_batches_accumulate_for_the_current_search_only() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.search_input = "LORD".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        let stale = app.streaming_search.as_ref().unwrap().id;
+        let _ = app.update(Message::SearchSubmitted);
+        let id = app.streaming_search.as_ref().unwrap().id;
+        assert_ne!(stale, id);
+
+        let psalm = |v| test_fixture::verse("Ps", 23, v, "The LORD");
+        let _ = app.update(Message::SearchBatch(stale, vec![psalm(6)]));
+        let _ = app.update(Message::SearchBatch(id, vec![psalm(1)]));
+        let _ = app.update(Message::SearchBatch(id, vec![psalm(6)]));
+        let _ = app.update(Message::SearchCounted(stale, 9));
+        let _ = app.update(Message::SearchCounted(id, 2));
+        assert_eq!(references(&app.search_results), ["Ps 23:1", "Ps 23:6"]);
+        assert_eq!(app.search_page_rows, 2);
+        assert_eq!(app.search_total, Some(2));
+
+        let _ = app.update(Message::SearchDone(stale, Some("stale failure".to_string())));
+        assert!(app.streaming_search.is_some());
+        let _ = app.update(Message::SearchDone(id, None));
+        assert!(app.streaming_search.is_none());
+        assert!(!app.search_has_more);
+        assert_eq!(app.search_error, None);
+
+        // Nothing more is taken once the search is done.
+        let _ = app.update(Message::SearchBatch(id, vec![psalm(2)]));
+        assert_eq!(app.search_results.len(), 2);
+    }
+
+    #[test]
+    fn quoted_phrases_are_matched_across_verses_in_the_background() {
+        let path = temp_dir("cross-verse").join("Fixture.SQLite3");
+        write_fixture_module(&path, "Fixture Bible");
+        let phrase = "the earth. And the earth";
+        let matches = find_cross_verse_matches(&path, &BibleCache::default(), &[phrase.to_string()]).unwrap();
+        assert_eq!(references(&matches), ["Gen 1:1"]);
+
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.cross_verse_phrases = true;
+        app.search_input = format!("\"{}\" OR \"green pastures\"", phrase);
+        assert_eq!(app.cross_verse_candidates(), [phrase, "green pastures"]);
+        app.search_input = format!("\"{}\" God", phrase);
+        assert!(app.cross_verse_candidates().is_empty());
+
+        app.search_input = format!("\"{}\"", phrase);
+        let _ = app.update(Message::SearchSubmitted);
+        let id = app.streaming_search.as_ref().unwrap().id;
+        let _ = app.update(Message::SearchDone(id, None));
+        assert!(app.search_results.is_empty());
+        let _ = app.update(Message::CrossVerseMatched(id - 1, Ok(matches.clone())));
+        assert!(app.search_results.is_empty());
+        let _ = app.update(Message::CrossVerseMatched(id, Ok(matches)));
+        assert_eq!(references(&app.search_results), ["Gen 1:1"]);
+        assert_eq!(app.search_notes.len(), 1);
+    }
+
+    #[test]
+    fn red_letter_filter_follows_the_query() {
+        let markup = crate::markup::RED_LETTER_CONVENTIONS[1];
+        let red = test_fixture::FIXTURE_VERSES[15].3;
+        let plain = test_fixture::FIXTURE_VERSES[14].3;
+        let mut app = fixture_app(None);
+        for (query, expected) in [
+            ("", true),
+            ("loved AND world", true),
+            ("loved AND Word", false),
+            ("loved OR Word", true),
+            ("Word", false),
+            ("loved NOT perish", true),
+            ("(loved AND Son) OR Word", true),
+        ] {
+            app.search_input = query.to_string();
+            assert_eq!(app.matches_in_red_letter(red, &markup), expected, "{}", query);
+        }
+        app.search_input = String::new();
+        assert!(!app.matches_in_red_letter(plain, &markup));
+
+        // An empty query keeps every verse with words of Christ.
+        app.red_letter_only = true;
+        app.red_letter_markup = Some(markup);
+        app.accept_search_batch(vec![
+            test_fixture::verse("John", 1, 1, plain),
+            test_fixture::verse("John", 3, 16, red),
+        ]);
+        assert_eq!(references(&app.search_results), ["John 3:16"]);
+    }
+
+    #[test]
+    fn handlers_without_a_bible_say_so_until_one_loads() {
+        for message in [
+            Message::SearchSubmitted,
+            Message::LookupSubmitted,
+            Message::ExportResults(false),
+            Message::ExportResults(true),
+            Message::SurpriseMe,
+            Message::ExportBookmarks,
+        ] {
+            let mut app = fixture_app(None);
+            app.search_input = "God".to_string();
+            app.lookup_input = "John 3:16".to_string();
+            let _ = app.update(message);
+            assert_eq!(app.status.as_deref(), Some(NO_BIBLE_LOADED));
+            assert!(app.search_results.is_empty() && app.lookup_results.is_empty());
+            assert!(app.streaming_search.is_none());
+        }
+
+        let mut app = fixture_app(None);
+        let _ = app.update(Message::SurpriseMe);
+        app.set_main_bible(fixture_bible());
+        assert_eq!(app.status, None);
+        let _ = app.update(Message::SurpriseMe);
+        assert_ne!(app.status.as_deref(), Some(NO_BIBLE_LOADED));
+    }
+
+    #[test]
+    fn compare_columns_strip_each_modules_own_verse_numbers() {
+        let numbered = test_fixture::fixture_connection();
+        numbered.execute_batch("UPDATE verses SET text = verse || ' ' || text;").unwrap();
+        let numbered = test_fixture::bible_from(numbered);
+        let plain = fixture_bible();
+        let bibles = [("Numbered".to_string(), &numbered), ("Plain".to_string(), &plain)];
+        let texts = |leading| -> Vec<String> {
+            compare_passage(&bibles, "Ps", 23, 1, 23, 1, ColumnOptions { normalize: false, leading })
+                .into_iter()
+                .map(|(_, verses)| verses[0].text.clone())
+                .collect()
+        };
+        let kjv = test_fixture::FIXTURE_VERSES[5].3;
+        assert_eq!(texts(LeadingNumbers::Auto), [kjv, kjv]);
+        assert_eq!(texts(LeadingNumbers::Keep), [format!("1 {}", kjv), kjv.to_string()]);
+    }
+
+    #[test]
+    fn whole_chapter_references_run_to_the_chapter_end() {
+        for (reference, book, chapter) in [("Gen 3", "Gen", 3), ("John 1", "John", 1), ("Ps 119", "Ps", 119)] {
+            assert_eq!(parse_lookup(reference), Some((book.to_string(), chapter, 1, chapter, CHAPTER_END)));
+        }
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "Ps 119").unwrap();
+        assert_eq!(references(&verses), ["Ps 119:1", "Ps 119:2"]);
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "Gen 3").unwrap();
+        assert_eq!(references(&verses), ["Gen 3:15"]);
+    }
+
+    #[test]
+    fn whole_book_references_run_to_the_book_end() {
+        for book in ["Jude", "Philemon", "Song of Solomon"] {
+            assert_eq!(parse_lookup(book), Some((book.to_string(), 1, 1, CHAPTER_END, CHAPTER_END)));
+        }
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "Philemon").unwrap();
+        assert_eq!(references(&verses), ["Phm 1:1"]);
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "jude").unwrap();
+        assert_eq!(references(&verses), ["Jude 1:1", "Jude 1:2"]);
+    }
+
+    #[test]
+    fn one_invalid_reference_among_several_is_skipped() {
+        assert_eq!(lookup_references("John 3:16; ; Rom 5:8 ;"), ["John 3:16", "Rom 5:8"]);
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.lookup_input = "John 3:16; Hezekiah 1:1".to_string();
+        let _ = app.update(Message::LookupSubmitted);
+        assert_eq!(references(&app.lookup_results), ["John 3:16"]);
+        assert_eq!(app.lookup_notes, ["No book named 'Hezekiah' in this module"]);
+
+        app.lookup_input = "Gen 1:1; Ps 23:9-9:1; Jude 1:2".to_string();
+        let _ = app.update(Message::LookupSubmitted);
+        assert_eq!(references(&app.lookup_results), ["Gen 1:1", "Jude 1:2"]);
+        assert_eq!(app.lookup_notes, ["end reference is before start: 'Ps 23:9-9:1'"]);
+        assert_eq!(app.lookup_error, None);
+    }
+
+    #[test]
+    fn book_names_with_spaces_and_numbers() {
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        for (reference, book, chapter, verse, found) in [
+            ("1 John 4:9", "1 John", 4, 9, "1Jn 4:9"),
+            ("2 Kings 2:11", "2 Kings", 2, 11, "2Ki 2:11"),
+            ("Song of Solomon 2:1", "Song of Solomon", 2, 1, "Song 2:1"),
+        ] {
+            assert_eq!(parse_lookup(reference), Some((book.to_string(), chapter, verse, chapter, verse)));
+            let verses = run_lookup(&bible.conn, &bible.schema, &books, reference).unwrap();
+            assert_eq!(references(&verses), [found]);
+        }
+    }
+
+    #[test]
+    fn book_names_match_loosely() {
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        for name in ["Genesis", "gen", "Gen.", "GEN", " genesis. "] {
+            assert_eq!(find_book(&books, name).map(|b| b.book_number), Some(10), "{}", name);
+        }
+        assert_eq!(find_book(&books, "1 john").map(|b| b.short_name.as_str()), Some("1Jn"));
+        assert!(find_book(&books, "Ge").is_none());
+        assert!(find_book(&books, "Hezekiah").is_none());
+    }
+
+    #[test]
+    fn comma_verse_lists_are_split_per_verse_or_range() {
+        assert_eq!(expand_verse_list("Ps 23:1,3,5"), ["Ps 23:1", "Ps 23:3", "Ps 23:5"]);
+        assert_eq!(expand_verse_list("Ps 23:1-3, 6"), ["Ps 23:1-3", "Ps 23:6"]);
+        assert_eq!(expand_verse_list("Ps 23:1-3"), ["Ps 23:1-3"]);
+        assert_eq!(expand_verse_list("Ps 23:1,"), ["Ps 23:1,"]);
+
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let verses: Vec<Verse> = lookup_references("Ps 23:1-3,6")
+            .iter()
+            .flat_map(|piece| run_lookup(&bible.conn, &bible.schema, &books, piece).unwrap())
+            .collect();
+        assert_eq!(references(&verses), ["Ps 23:1", "Ps 23:2", "Ps 23:3", "Ps 23:6"]);
+    }
+
+    #[test]
+    fn like_wildcards_in_terms_match_literally() {
+        let matching = TermMatching::default();
+        assert_eq!(matching.condition(false), r"text LIKE '%' || ? || '%' ESCAPE '\'");
+        assert_eq!(matching.condition(true), r"text NOT LIKE '%' || ? || '%' ESCAPE '\'");
+        assert_eq!(matching.param("100%"), r"100\%");
+        assert_eq!(matching.param(r"a_b\c"), r"a\_b\\c");
+        let case_sensitive = TermMatching { case_sensitive: true, ..TermMatching::default() };
+        assert_eq!(case_sensitive.param("100%*?"), "100%[*][?]");
+
+        let conn = test_fixture::fixture_connection();
+        conn.execute_batch(
+            "INSERT INTO verses VALUES (10, 1, 4, 'They gave 100% of the offering.');
+             INSERT INTO verses VALUES (10, 1, 5, 'They gave 1000 talents.');",
+        )
+        .unwrap();
+        let bible = test_fixture::bible_from(conn);
+        let clause = build_where_clause("100%", &SearchOptions::default());
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Gen 1:4"]);
+        let clause = build_where_clause("gave NOT 100%", &SearchOptions::default());
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Gen 1:5", "John 3:16"]);
+    }
+
+    #[test]
+    fn reversed_ranges_are_told_from_cross_chapter_ones() {
+        assert!(!is_reversed_range(6, 10, 7, 2));
+        assert!(!is_reversed_range(6, 1, 6, 1));
+        assert!(is_reversed_range(6, 10, 6, 2));
+        assert!(is_reversed_range(7, 1, 6, 5));
+        // A whole chapter runs to `CHAPTER_END` without overflowing.
+        assert!(!is_reversed_range(3, 1, 3, CHAPTER_END));
+
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let verses = run_lookup(&bible.conn, &bible.schema, &books, "Ps 23:6-119:1").unwrap();
+        assert_eq!(references(&verses), ["Ps 23:6", "Ps 119:1"]);
+        assert!(matches!(
+            run_lookup(&bible.conn, &bible.schema, &books, "Ps 119:1-23:6"),
+            Err(LookupError::ReversedRange(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_books_and_empty_ranges_are_told_apart() {
+        let bible = fixture_bible();
+        let books = load_books(&bible).unwrap();
+        let unknown = run_lookup(&bible.conn, &bible.schema, &books, "Hezekiah 3:1").unwrap_err();
+        assert!(matches!(unknown, LookupError::NoSuchBook(_)));
+        assert_eq!(unknown.to_string(), "No book named 'Hezekiah' in this module");
+        let out_of_range = run_lookup(&bible.conn, &bible.schema, &books, "Gen 60").unwrap_err();
+        assert!(matches!(out_of_range, LookupError::EmptyRange(_)));
+        assert_eq!(out_of_range.to_string(), "no verses in that range: 'Gen 60'");
+        // Without a book list the name is queried as typed, so nothing found means no such book.
+        assert!(matches!(
+            run_lookup(&bible.conn, &bible.schema, &[], "Hezekiah 3:1"),
+            Err(LookupError::NoSuchBook(_))
+        ));
+    }
+
+    #[test]
+    fn short_terms_are_dropped_with_a_note_and_phrases_kept() {
+        let options = SearchOptions::default();
+        assert!(is_short_term("I", false, &options));
+        assert!(!is_short_term("I", true, &options));
+        assert!(!is_short_term("in", false, &options));
+        // Length counts characters, not bytes.
+        assert!(is_short_term("é", false, &options));
+
+        let clause = build_where_clause("I AND shepherd", &options);
+        assert_eq!(clause.params, ["shepherd"]);
+        assert_eq!(clause.notes, ["ignored short term: 'I'"]);
+        let clause = build_where_clause("\"I\" AND shepherd", &options);
+        assert_eq!(clause.params, ["I", "shepherd"]);
+        assert!(clause.notes.is_empty());
+        let clause = build_where_clause("\"a b\" OR c", &options);
+        assert_eq!(clause.params, ["a b"]);
+        assert_eq!(clause.notes, ["ignored short term: 'c'"]);
+        // A query of nothing but short terms matches every verse.
+        assert_eq!(build_where_clause("a b", &options).sql, "1");
+    }
+
+    #[test]
+    fn lines_are_ored_as_phrases() {
+        let matching = TermMatching::default();
+        let lines = ["still waters".to_string(), "   ".to_string(), "  I AM ".to_string(), "a".to_string()];
+        let clause = build_lines_clause(&lines, matching);
+        assert_eq!(clause.sql, vec![matching.condition(false); 3].join(" OR "));
+        // Lines are trimmed phrases kept whole, even when shorter than the minimum term length.
+        assert_eq!(clause.params, [matching.param("still waters"), matching.param("I AM"), matching.param("a")]);
+        assert!(clause.notes.is_empty());
+        assert_eq!(build_lines_clause(&["".to_string(), " ".to_string()], matching).sql, "1");
+    }
+
+    #[test]
+    fn chapter_preview_waits_for_typing_to_settle() {
+        assert_eq!(chapter_prefix("Ps 23:1"), Some(("Ps".to_string(), 23)));
+        assert_eq!(chapter_prefix("Ps 2"), Some(("Ps".to_string(), 2)));
+        assert_eq!(chapter_prefix("Ps"), None);
+        assert_eq!(chapter_prefix("1 John 4"), Some(("1 John".to_string(), 4)));
+        assert_eq!(chapter_prefix("Song of Solomon 2:1"), Some(("Song of Solomon".to_string(), 2)));
+        assert_eq!(chapter_prefix("Song of Solomon"), None);
+
+        let mut app = fixture_app(Some(fixture_bible()));
+        let _ = app.update(Message::LookupChanged("Ps 2".to_string()));
+        let stale = app.preview_generation;
+        let _ = app.update(Message::LookupChanged("Ps 23".to_string()));
+        assert_eq!(app.preview_key, None);
+        let _ = app.update(Message::PreviewDue(stale));
+        assert_eq!(app.preview_key, None);
+        assert!(app.preview_cache.is_empty());
+
+        let _ = app.update(Message::PreviewDue(app.preview_generation));
+        assert_eq!(app.preview_key, Some(("Ps".to_string(), 23)));
+        assert_eq!(app.preview_cache.len(), 1);
+
+        let _ = app.update(Message::LookupChanged("1Jn 4:9".to_string()));
+        let _ = app.update(Message::PreviewDue(app.preview_generation));
+        assert_eq!(app.preview_key, Some(("1Jn".to_string(), 4)));
+        assert!(app.preview_cache[&("1Jn".to_string(), 4)].is_some());
+        for typed in ["1 John 4", "1jn 4:9", "1Jn. 4"] {
+            let _ = app.update(Message::LookupChanged(typed.to_string()));
+            let _ = app.update(Message::PreviewDue(app.preview_generation));
+            assert_eq!(app.preview_key, Some(("1Jn".to_string(), 4)), "{}", typed);
+        }
+        let _ = app.update(Message::LookupChanged("Genesis 1".to_string()));
+        let _ = app.update(Message::PreviewDue(app.preview_generation));
+        assert_eq!(app.preview_key, Some(("Gen".to_string(), 1)));
+        assert!(app.preview_cache[&("Gen".to_string(), 1)].as_ref().is_some_and(|(verse, _)| *verse == 1));
+
+        let books = load_books(&fixture_bible()).unwrap();
+        let names = |input| book_suggestions(&books, input, 8).iter().map(|b| b.long_name.clone()).collect::<Vec<_>>();
+        assert_eq!(names("1 Jo"), ["1 John"]);
+        assert_eq!(names("Song of"), ["Song of Solomon"]);
+        assert!(names("Song of Solomon 2").is_empty());
+        assert!(names("Ps 23:").is_empty());
+    }
+
+    #[test]
+    fn overlapping_terms_highlight_the_longest_match() {
+        let matching = TermMatching::default();
+        assert_eq!(highlight_pattern(&["faith", "faithful"], matching), "(?i)(faithful|faith)");
+        assert_eq!(highlight_pattern(&["son", "sons"], matching), "(?i)(sons|son)");
+        assert_eq!(
+            split_for_highlight("A faithful man hath faith", &["faith", "faithful"], matching),
+            [("A ", false), ("faithful", true), (" man hath ", false), ("faith", true)]
+        );
+        assert_eq!(
+            split_for_highlight("the sons of the Son", &["son", "sons"], matching),
+            [("the ", false), ("sons", true), (" of the ", false), ("Son", true)]
+        );
+        let whole_word = TermMatching { whole_word: true, ..matching };
+        assert_eq!(highlight_pattern(&["son", "sons"], whole_word), r"(?i)\b(sons|son)\b");
+        assert_eq!(
+            split_for_highlight("sons and grandsons", &["son", "sons"], whole_word),
+            [("sons", true), (" and grandsons", false)]
+        );
+    }
+
+    #[test]
+    fn identical_verses_are_dimmed_only_when_asked() {
+        let mut app = fixture_app(None);
+        app.compare_results = vec![
+            (
+                "KJV".to_string(),
+                vec![
+                    test_fixture::verse("Ps", 23, 1, "The LORD is my shepherd"),
+                    test_fixture::verse("Ps", 23, 2, "He maketh me"),
+                ],
+            ),
+            (
+                "AKJV".to_string(),
+                vec![
+                    test_fixture::verse("Ps", 23, 1, "The LORD  is my shepherd "),
+                    test_fixture::verse("Ps", 23, 2, "He makes me"),
+                ],
+            ),
+        ];
+        assert!(app.dimmed_verses().is_empty());
+        let _ = app.update(Message::DimIdenticalToggled(true));
+        assert_eq!(app.dimmed_verses(), BTreeSet::from([(23, 1)]));
+
+        // A verse one translation lacks is never identical, nor is one with nothing to compare against.
+        app.compare_results[1].1.remove(0);
+        assert!(app.dimmed_verses().is_empty());
+        app.compare_results.truncate(1);
+        assert!(app.dimmed_verses().is_empty());
+    }
+
+    #[test]
+    fn word_count_skips_markup_without_gluing_words() {
+        assert_eq!(word_count("Jesus wept."), 2);
+        assert_eq!(word_count("  "), 0);
+        assert_eq!(word_count("<J>Jesus</J><i>wept</i>"), 2);
+        assert_eq!(word_count("And God said,<pb/>Let there be light"), 7);
+        assert_eq!(word_count("<J></J>"), 0);
+    }
+
+    #[test]
+    fn word_count_clause_compares_the_number_of_words() {
+        let bible = fixture_bible();
+        let clause = build_word_count_clause(WordCountOp::Exactly, " 9 ");
+        assert_eq!(clause.sql, "word_count(v.text) = CAST(? AS INTEGER)");
+        assert_eq!(clause.params, ["9"]);
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Ps 23:1", "Jude 1:2"]);
+        let clause = build_word_count_clause(WordCountOp::FewerThan, "3");
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["John 11:35"]);
+        // Psalm 23:4 has thirty words exactly.
+        let clause = build_word_count_clause(WordCountOp::MoreThan, "29");
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["2Ki 2:11", "Ps 23:4"]);
+
+        let clause = build_word_count_clause(WordCountOp::Exactly, "nine");
+        assert_eq!(clause.notes, ["not a number of words: 'nine'"]);
+        assert!(search(&bible, &clause.sql, &clause.params).is_empty());
+    }
+
+    #[test]
+    fn results_group_into_runs_of_one_chapter() {
+        let verses = [
+            test_fixture::verse("Ps", 23, 1, "The LORD is my shepherd"),
+            test_fixture::verse("Ps", 23, 4, "Yea, though I walk"),
+            test_fixture::verse("Ps", 119, 1, "Blessed are the undefiled"),
+            test_fixture::verse("John", 3, 16, "For God so loved the world"),
+            test_fixture::verse("Ps", 23, 6, "Surely goodness and mercy"),
+        ];
+        let groups: Vec<((String, u32), Vec<String>)> = group_by_chapter(&verses)
+            .into_iter()
+            .map(|(key, members)| (key, members.iter().map(|v| v.reference(true)).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (("Ps".to_string(), 23), vec!["Ps 23:1".to_string(), "Ps 23:4".to_string()]),
+                (("Ps".to_string(), 119), vec!["Ps 119:1".to_string()]),
+                (("John".to_string(), 3), vec!["John 3:16".to_string()]),
+                // Out of order, a chapter starts a run of its own again.
+                (("Ps".to_string(), 23), vec!["Ps 23:6".to_string()]),
+            ]
+        );
+        assert!(group_by_chapter(&[]).is_empty());
+    }
+
+    #[test]
+    fn heat_color_runs_from_pale_yellow_to_deep_red() {
+        let gray = Color::from_rgb(0.9, 0.9, 0.9);
+        assert_eq!(heat_color(0, 10), gray);
+        assert_eq!(heat_color(3, 0), gray);
+        assert_eq!(heat_color(10, 10), Color::from_rgb(0.7, 0.0, 0.0));
+        // A count above the maximum is as hot as the maximum.
+        assert_eq!(heat_color(12, 10), heat_color(10, 10));
+        let half = heat_color(5, 10);
+        assert!((half.r - 0.85).abs() < 1e-6 && (half.g - 0.475).abs() < 1e-6 && (half.b - 0.3).abs() < 1e-6);
+        let greens: Vec<f32> = (1..=10).map(|count| heat_color(count, 10).g).collect();
+        assert!(greens.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn not_semantics_are_remembered() {
+        let mut app = fixture_app(None);
+        let _ = app.update(Message::NotSemanticsChanged(NotSemantics::Operand));
+        assert_eq!(app.search_options.not_semantics, NotSemantics::Operand);
+        let restored = AppSettings::parse(&app.settings.to_file_contents());
+        assert_eq!(restored.not_semantics, NotSemantics::Operand);
+    }
+
+    #[test]
+    fn case_is_only_ignored_for_ascii_letters() {
+        let conn = test_fixture::fixture_connection();
+        conn.execute_batch("INSERT INTO verses VALUES (10, 1, 4, 'Élie et Ève');").unwrap();
+        let bible = test_fixture::bible_from(conn);
+        let clause = build_where_clause("élie", &SearchOptions::default());
+        assert!(search(&bible, &clause.sql, &clause.params).is_empty());
+        let clause = build_where_clause("éLIE", &SearchOptions::default());
+        assert!(search(&bible, &clause.sql, &clause.params).is_empty());
+        let clause = build_where_clause("ÉLIE", &SearchOptions::default());
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Gen 1:4"]);
+        assert!(search_help(&SearchOptions::default())[0].contains("only for unaccented Latin letters"));
+    }
+
+    #[test]
+    fn skipped_import_lines_are_kept_for_display() {
+        let dir = temp_dir("import-references");
+        let list = dir.join("refs.txt");
+        fs::write(&list, "Ps 23:1\nHezekiah 1:1\nPs 23\n").unwrap();
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.bookmarks.clear();
+        let _ = app.update(Message::ImportPathChanged(list.display().to_string()));
+        let _ = app.update(Message::ImportReferences);
+        assert_eq!(app.status.as_deref(), Some("Imported 1, skipped 2"));
+        assert_eq!(app.import_skipped, ["line 3: Ps 23", "unknown book: Hezekiah 1:1"]);
+
+        fs::write(&list, "Ps 23:2\n").unwrap();
+        let _ = app.update(Message::ImportReferences);
+        assert!(app.import_skipped.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn combined_verses_are_found_by_any_verse_they_cover() {
+        let conn = test_fixture::fixture_connection();
+        conn.execute_batch(
+            "INSERT INTO verses VALUES (720, 1, '3-4', 'Beloved, when I gave all diligence to write unto you…');
+             INSERT INTO verses VALUES (720, 1, 5, 'I will therefore put you in remembrance.');",
+        )
+        .unwrap();
+        let bible = test_fixture::bible_from(conn);
+        let books = load_books(&bible).unwrap();
+        let lookup = |reference| references(&run_lookup(&bible.conn, &bible.schema, &books, reference).unwrap());
+        assert_eq!(lookup("Jude 1:4"), ["Jude 1:3-4"]);
+        assert_eq!(lookup("Jude 1:2-3"), ["Jude 1:2", "Jude 1:3-4"]);
+        // The text row sorts among the numbered ones, not after them.
+        assert_eq!(lookup("Jude 1"), ["Jude 1:1", "Jude 1:2", "Jude 1:3-4", "Jude 1:5"]);
+        assert_eq!(lookup("Jude 1:5"), ["Jude 1:5"]);
+    }
+
+    #[test]
+    fn quick_queries_that_look_like_references_are_looked_up() {
+        let books = load_books(&fixture_bible()).unwrap();
+        for reference in ["Gen 1:1", " Ps 23:1-3 ", "Ps 23", "Ps 23:1, 3-4", "Jude", "Psalms"] {
+            assert!(looks_like_reference(reference, &books), "{}", reference);
+        }
+        for query in ["", "love", "love one another", "Hezekiah", "love 3", "seventy 7", "grace 2:8"] {
+            assert!(!looks_like_reference(query, &books), "{}", query);
+        }
+        assert_eq!(QueryRoute::Auto.resolve("Ps 23", &books), QueryRoute::Lookup);
+        assert_eq!(QueryRoute::Auto.resolve("shepherd", &books), QueryRoute::Search);
+        assert_eq!(QueryRoute::Search.resolve("Ps 23", &books), QueryRoute::Search);
+    }
+
+    #[test]
+    fn either_panel_can_be_shown_in_one_result_area() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        let _ = app.update(Message::UnifiedResultsToggled(true));
+        assert_eq!(app.last_route, None);
+        let _ = app.update(Message::ResultsTabChosen(QueryRoute::Lookup));
+        assert_eq!(app.last_route, Some(QueryRoute::Lookup));
+        assert!(app.settings.lookup_tab_active);
+        let _ = app.update(Message::ResultsTabChosen(QueryRoute::Search));
+        assert_eq!(app.last_route, Some(QueryRoute::Search));
+        assert!(!app.settings.lookup_tab_active);
+    }
+
+    #[test]
+    fn search_terms_are_only_counted_with_a_bible_loaded() {
+        let mut app = fixture_app(None);
+        app.term_frequency = TermFrequency::default();
+        app.search_input = "shepherd".to_string();
+        let _ = app.update(Message::SearchSubmitted);
+        assert_eq!(app.status.as_deref(), Some(NO_BIBLE_LOADED));
+        assert!(app.term_frequency.top(1).is_empty());
+
+        app.db = Some(fixture_bible());
+        let _ = app.update(Message::SearchSubmitted);
+        assert_eq!(app.term_frequency.top(1), [("shepherd", 1)]);
+    }
+
+    #[test]
+    fn plain_layout_collapses_line_breaks_and_poetry_keeps_them() {
+        let verse = "The LORD is my shepherd; \r\n\tI shall not want.\nHe maketh me";
+        assert_eq!(verse_layout(verse, false), "The LORD is my shepherd; I shall not want. He maketh me");
+        assert_eq!(verse_layout(verse, true), verse);
+        assert!(matches!(verse_layout("No breaks here.", false), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn segmented_layout_collapses_line_breaks_per_mode() {
+        let verse = "He leadeth me\n  beside the <i>still</i> waters.";
+        let (segments, _) = segment_verse(verse, &[], TermMatching::default(), 0, None);
+        let laid_out = |keep_line_breaks| {
+            segments.iter().map(|segment| verse_layout(segment.text, keep_line_breaks)).collect::<String>()
+        };
+        assert_eq!(laid_out(false), "He leadeth me beside the still waters.");
+        assert_eq!(laid_out(true), "He leadeth me\n  beside the still waters.");
+    }
+
+    #[test]
+    fn cycling_the_search_mode_remembers_it() {
+        let mut app = fixture_app(None);
+        app.search_mode = SearchMode::Regex;
+        let _ = app.update(Message::CycleSearchMode);
+        assert_eq!(app.search_mode, SearchMode::Plain);
+        assert_eq!(app.settings.last_search_mode, Some(SearchMode::Plain));
+    }
+
+    #[test]
+    fn quoted_phrases_stay_one_token() {
+        assert_eq!(tokenize_query("\"living water\" AND well"), ["\"living water\"", "AND", "well"]);
+        assert_eq!(tokenize_query("\"his name's sake\" soul"), ["\"his name's sake\"", "soul"]);
+    }
+
+    #[test]
+    fn phrases_with_an_apostrophe_are_searched_as_written() {
+        let bible = fixture_bible();
+        let clause = build_where_clause("\"his name's sake\"", &SearchOptions::default());
+        assert_eq!(clause.params, ["his name's sake"]);
+        assert_eq!(search(&bible, &clause.sql, &clause.params), ["Ps 23:3"]);
+    }
+
+    #[test]
+    fn chapter_counts_tally_matches_per_chapter() {
+        let verses = [
+            test_fixture::verse("Ps", 23, 1, "The LORD is my shepherd;"),
+            test_fixture::verse("Ps", 23, 4, "I will fear no evil:"),
+            test_fixture::verse("John", 3, 16, "For God so loved the world,"),
+        ];
+        let counts = chapter_counts(&verses);
+        let summary: Vec<_> =
+            counts.iter().map(|count| (count.long_name.as_str(), count.chapter, count.matches)).collect();
+        assert_eq!(summary, [("Psalms", 23, 2), ("John", 3, 1)]);
+        assert_eq!(counts[0].short_name, "Ps");
+        assert!(chapter_counts(&[]).is_empty());
+    }
+
+    #[test]
+    fn regexp_is_registered_on_a_bare_connection() {
+        let conn = test_fixture::fixture_connection();
+        let schema = validate_schema(&conn).unwrap();
+        let clause = build_regex_clause("shep+herd;").unwrap();
+        assert_eq!(count_verses(&conn, &schema, &clause.sql, &clause.params).unwrap(), 1);
+        let mut found = Vec::new();
+        run_search(&conn, &schema, &clause.sql, &clause.params, 100, 0, |verse| {
+            found.push(verse);
+            true
+        })
+        .unwrap();
+        assert_eq!(references(&found), ["Ps 23:1"]);
+    }
+
+    #[test]
+    fn context_range_clamps_to_the_chapter() {
+        assert_eq!(context_range(5, 5, 2, 20), (3, 7));
+        assert_eq!(context_range(1, 2, 3, 20), (1, 5));
+        assert_eq!(context_range(18, 19, 3, 20), (15, 20));
+        assert_eq!(context_range(4, 4, 0, 20), (4, 4));
+        assert_eq!(context_range(7, 7, 2, 6), (5, 7));
+    }
+
+    #[test]
+    fn context_opens_the_configured_number_of_verses() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.search_results = vec![test_fixture::verse("Ps", 23, 5, "Thou preparest a table before me")];
+        let _ = app.update(Message::ContextVersesChanged(2));
+        let _ = app.update(Message::ShowContext(0));
+        assert_eq!(app.lookup_input, "Ps 23:3-6");
+        assert_eq!(references(&app.lookup_results), ["Ps 23:3", "Ps 23:4", "Ps 23:5", "Ps 23:6"]);
+
+        let _ = app.update(Message::ContextVersesChanged(0));
+        let _ = app.update(Message::ShowContext(0));
+        assert_eq!(app.lookup_input, "Ps 23:5-5");
+    }
+
+    #[test]
+    fn excluded_books_become_a_not_in_clause() {
+        assert_eq!(excluded_books_clause(&BTreeSet::new(), "v.book_number"), None);
+        assert_eq!(
+            excluded_books_clause(&BTreeSet::from([500, 10, 230]), "v.book_number").as_deref(),
+            Some("CAST(v.book_number AS INTEGER) NOT IN (10, 230, 500)")
+        );
+        let where_clause = "v.text LIKE ? OR v.text LIKE ?".to_string();
+        assert_eq!(
+            narrowed_where_clause(where_clause.clone(), &BTreeSet::new(), &SearchScope::All, "v.book_number"),
+            where_clause
+        );
+        assert_eq!(
+            narrowed_where_clause(where_clause, &BTreeSet::from([10]), &SearchScope::All, "v.book_number"),
+            "(v.text LIKE ? OR v.text LIKE ?) AND CAST(v.book_number AS INTEGER) NOT IN (10)"
+        );
+    }
+
+    #[test]
+    fn review_sheet_says_when_the_main_bible_is_not_compared() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.compare_reference = Some("Ps 23:1".to_string());
+        app.compare_results = vec![("KJV".to_string(), vec![test_fixture::verse("Ps", 23, 1, "The LORD is my shepherd")])];
+        let _ = app.update(Message::GenerateReviewSheet);
+        let status = app.status.clone().unwrap_or_default();
+        assert!(status.starts_with("Copied the review sheet for Ps 23:1 to the clipboard (the main Bible "));
+        assert!(status.ends_with("is not among the compared translations, so KJV is the base)"));
+
+        let main = app.db.as_ref().map(|db| translation_label(bible_description(&db.conn), &db.path)).unwrap();
+        app.compare_results.push((main, vec![test_fixture::verse("Ps", 23, 1, "The LORD is my shepherd;")]));
+        let _ = app.update(Message::GenerateReviewSheet);
+        assert_eq!(app.status.as_deref(), Some("Copied the review sheet for Ps 23:1 to the clipboard"));
+    }
+
+    #[test]
+    fn window_title_names_the_translation_and_last_action() {
+        let search = RecentActivity::Search { query: "faith".to_string(), matches: 142 };
+        let lookup = RecentActivity::Lookup { reference: "Ps 23:1".to_string(), verses: 1 };
+        assert_eq!(window_title(Some("KJV"), Some(&search)), "KJV — 'faith' (142 matches)");
+        assert_eq!(window_title(Some("KJV"), Some(&lookup)), "KJV — Ps 23:1 (1 verse)");
+        assert_eq!(
+            window_title(None, Some(&RecentActivity::Search { query: "grace".to_string(), matches: 1 })),
+            "'grace' (1 match)"
+        );
+        assert_eq!(window_title(Some("KJV"), None), format!("KJV — {}", DEFAULT_TITLE));
+        assert_eq!(window_title(None, None), DEFAULT_TITLE);
+    }
+
+    #[test]
+    fn a_main_bible_that_fails_to_open_is_reported() {
+        let dir = temp_dir("no-bibles");
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.db_dir_input = dir.display().to_string();
+        let _ = app.update(Message::DbDirSubmitted);
+        assert_eq!(app.db_dir, dir);
+        let status = app.status.clone().unwrap_or_default();
+        assert!(status.starts_with("Keeping the current main Bible: Could not open "), "{}", status);
+        assert!(app.db.is_some());
+    }
+
+    #[test]
+    fn baseline_texts_come_through_the_cache_with_numbers_stripped() {
+        let path = temp_dir("baseline").join("Numbered.SQLite3");
+        write_fixture_module(&path, "Numbered");
+        Connection::open(&path)
+            .unwrap()
+            .execute("UPDATE verses SET text = '1 ' || text WHERE book_number = 230 AND chapter = 23 AND verse = 1", [])
+            .unwrap();
+        let module = DiscoveredModule { path: path.clone(), description: "Numbered".to_string(), duplicates: Vec::new() };
+        let cache = BibleCache::default();
+        let refs = [(230, 23, 1), (500, 11, 35), (10, 50, 26)];
+        let stripped = fetch_baseline_texts(&cache, &module, &refs, LeadingNumbers::Strip).unwrap();
+        assert_eq!(stripped[&(230, 23, 1)], "The LORD is my shepherd; I shall not want.");
+        assert_eq!(stripped[&(500, 11, 35)], "Jesus wept.");
+        assert!(!stripped.contains_key(&(10, 50, 26)));
+        assert!(cache.lock().unwrap().contains_key(&path));
+        let kept = fetch_baseline_texts(&cache, &module, &refs[..1], LeadingNumbers::Keep).unwrap();
+        assert_eq!(kept[&(230, 23, 1)], "1 The LORD is my shepherd; I shall not want.");
+    }
+
+    #[test]
+    fn baseline_flags_from_an_older_fetch_are_dropped() {
+        let mut app = fixture_app(Some(fixture_bible()));
+        app.search_results = vec![test_fixture::verse("John", 11, 35, "Jesus wept.")];
+        let texts = HashMap::from([((500, 11, 35), "Jesus wept.".to_string())]);
+        let stale = app.baseline_generation;
+        let _ = app.update(Message::BaselineCleared);
+        let _ = app.update(Message::BaselineFetched(stale, vec![(500, 11, 35)], Ok(texts.clone())));
+        assert!(app.baseline_flags.is_empty());
+
+        let current = app.baseline_generation;
+        let _ = app.update(Message::BaselineFetched(current, vec![(500, 11, 35)], Ok(texts)));
+        assert_eq!(app.baseline_flags.get(&(500, 11, 35)), Some(&Some(true)));
+    }
+
+    #[test]
+    fn chapter_preview_starts_at_the_chapters_first_verse() {
+        let bible = fixture_bible();
+        assert_eq!(first_verse_text(&bible, "Gen", 3).unwrap().map(|(verse, _)| verse), Some(15));
+        assert_eq!(first_verse_text(&bible, "Gen", 2).unwrap(), None);
+    }
+
+    #[test]
+    fn bookmark_report_keeps_its_module_scan() {
+        let mut app = fixture_app(None);
+        let module = DiscoveredModule {
+            path: PathBuf::from("KJV.SQLite3"),
+            description: "King James Version".to_string(),
+            duplicates: Vec::new(),
+        };
+        let started = app.module_scan_generation;
+        let _ = app.update(Message::CompareDedupChanged(DedupMode::Off));
+        let _ = app.update(Message::BookmarkReportLoaded(0, started, vec![module.clone()], Vec::new()));
+        assert_eq!(app.discovered_modules, None);
+
+        let current = app.module_scan_generation;
+        let _ = app.update(Message::BookmarkReportLoaded(0, current, vec![module.clone()], Vec::new()));
+        assert_eq!(app.discovered_modules, Some(vec![module]));
+        assert!(app.bookmark_report.as_ref().is_some_and(Vec::is_empty));
+    }
 }
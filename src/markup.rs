@@ -0,0 +1,360 @@
+use std::borrow::Cow;
+
+use rusqlite::{Connection, Error as RusqliteError};
+
+use crate::{MatchPosition, TermMatching};
+
+// -------------------------------
+// Words-of-Christ (Red-Letter) Markup
+// -------------------------------
+
+/// A tag pair a module uses to mark the words of Christ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedLetterMarkup {
+    pub open: &'static str,
+    pub close: &'static str,
+}
+
+/// Conventions seen in the wild, checked in order: MyBible/theWord `<FR>…<Fr>` and `<J>…</J>`.
+pub const RED_LETTER_CONVENTIONS: [RedLetterMarkup; 2] = [
+    RedLetterMarkup { open: "<FR>", close: "<Fr>" },
+    RedLetterMarkup { open: "<J>", close: "</J>" },
+];
+
+/// Find which words-of-Christ convention the module uses, if any.
+/// `instr` is used rather than LIKE because the tags differ only by case.
+pub fn detect_red_letter(conn: &Connection) -> Result<Option<RedLetterMarkup>, RusqliteError> {
+    for markup in RED_LETTER_CONVENTIONS {
+        let found: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM verses WHERE instr(text, ?) > 0)",
+            [markup.open],
+            |row| row.get(0),
+        )?;
+        if found {
+            return Ok(Some(markup));
+        }
+    }
+    Ok(None)
+}
+
+/// The text inside each words-of-Christ span. An unclosed span runs to the end of the verse.
+pub fn red_letter_spans<'a>(text: &'a str, markup: &RedLetterMarkup) -> Vec<&'a str> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(markup.open) {
+        let inner = &rest[start + markup.open.len()..];
+        match inner.find(markup.close) {
+            Some(end) => {
+                spans.push(&inner[..end]);
+                rest = &inner[end + markup.close.len()..];
+            }
+            None => {
+                spans.push(inner);
+                break;
+            }
+        }
+    }
+    spans
+}
+
+/// The verse's words of Christ for the red-letter search filter, lowercased: one entry per
+/// span, or None when the verse has none (or only blank spans).
+pub fn red_letter_words(text: &str, markup: &RedLetterMarkup) -> Option<Vec<String>> {
+    let spans: Vec<String> = red_letter_spans(text, markup)
+        .into_iter()
+        .filter(|span| !span.trim().is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    (!spans.is_empty()).then_some(spans)
+}
+
+/// Whether `term` occurs, ignoring case, inside one of the `red_letter_words` spans.
+pub fn term_in_red_letter(words: &[String], term: &str) -> bool {
+    let term = term.to_lowercase();
+    words.iter().any(|span| span.contains(&term))
+}
+
+/// Tags marking words supplied by the translators (rendered as italics in print).
+const ITALIC_OPEN: &str = "<i>";
+const ITALIC_CLOSE: &str = "</i>";
+
+/// Tags around a Strong's number following the word it annotates, as in MyBible modules:
+/// "beginning<S>7225</S>".
+const STRONGS_OPEN: &str = "<S>";
+const STRONGS_CLOSE: &str = "</S>";
+
+/// Whether the module's verses carry Strong's numbers.
+pub fn detect_strongs(conn: &Connection) -> Result<bool, RusqliteError> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM verses WHERE instr(text, ?) > 0)",
+        [STRONGS_OPEN],
+        |row| row.get(0),
+    )
+}
+
+/// What a recognized tag switches on or off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Span {
+    RedLetter,
+    Italic,
+    Strongs,
+    /// Footnotes and notes, left out of the rendered text.
+    Hidden,
+}
+
+/// Footnote and note tags; what they enclose is not verse text and is dropped.
+const HIDDEN_TAGS: [(&str, &str); 2] = [("<f>", "</f>"), ("<n>", "</n>")];
+
+/// Longest tag recognized, in bytes; a `<` with no `>` soon after is ordinary text.
+const MAX_TAG_LEN: usize = 40;
+
+/// The next markup tag in `text`: its position and the tag itself, `<` through `>`. Only a
+/// `<` followed by a letter or `/` and closed within `MAX_TAG_LEN` bytes counts, so a stray
+/// `<` or an unclosed tag is left in the text.
+fn next_tag(text: &str) -> Option<(usize, &str)> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find('<') {
+        let pos = from + offset;
+        let after = &text[pos + 1..];
+        if after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+            if let Some(end) = after.find(['<', '>']).filter(|&end| end < MAX_TAG_LEN && after[end..].starts_with('>')) {
+                return Some((pos, &text[pos..pos + end + 2]));
+            }
+        }
+        from = pos + 1;
+    }
+    None
+}
+
+/// What `tag` switches on (true) or off (false); None for tags that are simply dropped.
+/// Words-of-Christ tags only count when the module's `red_letter` markup is given.
+fn classify(tag: &str, red_letter: Option<&RedLetterMarkup>) -> Option<(Span, bool)> {
+    match tag {
+        ITALIC_OPEN => Some((Span::Italic, true)),
+        ITALIC_CLOSE => Some((Span::Italic, false)),
+        STRONGS_OPEN => Some((Span::Strongs, true)),
+        STRONGS_CLOSE => Some((Span::Strongs, false)),
+        _ if red_letter.is_some_and(|m| tag == m.open) => Some((Span::RedLetter, true)),
+        _ if red_letter.is_some_and(|m| tag == m.close) => Some((Span::RedLetter, false)),
+        _ => HIDDEN_TAGS.iter().find_map(|&(open, close)| match tag {
+            _ if tag == open => Some((Span::Hidden, true)),
+            _ if tag == close => Some((Span::Hidden, false)),
+            _ => None,
+        }),
+    }
+}
+
+/// Whether `text` has nothing but markup, Strong's numbers, footnotes and whitespace, so an
+/// end-anchored term can still match the word they follow.
+fn only_markup(text: &str) -> bool {
+    let mut skipping = 0usize;
+    let mut rest = text;
+    loop {
+        let next = next_tag(rest);
+        let run = &rest[..next.map_or(rest.len(), |(pos, _)| pos)];
+        if skipping == 0 && !run.trim().is_empty() {
+            return false;
+        }
+        let Some((pos, tag)) = next else {
+            return true;
+        };
+        if let Some((Span::Strongs | Span::Hidden, opens)) = classify(tag, None) {
+            skipping = if opens { skipping + 1 } else { skipping.saturating_sub(1) };
+        }
+        rest = &rest[pos + tag.len()..];
+    }
+}
+
+/// Verse text with its markup removed for plain display: tags, Strong's numbers and
+/// footnotes are dropped. Text without tags is returned as is.
+pub fn clean_text(text: &str) -> Cow<'_, str> {
+    if next_tag(text).is_none() {
+        return Cow::Borrowed(text);
+    }
+    let (segments, _) = segment_verse(text, &[], TermMatching::default(), 0, None);
+    Cow::Owned(segments.iter().filter(|s| !s.strongs).map(|s| s.text).collect())
+}
+
+/// A run of verse text with the styling that applies to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerseSegment<'a> {
+    pub text: &'a str,
+    /// Matches a search term.
+    pub highlight: bool,
+    /// Inside a words-of-Christ span.
+    pub red_letter: bool,
+    /// Inside `<i>…</i>` (translator-supplied words).
+    pub italic: bool,
+    /// A Strong's number from `<S>…</S>`, shown or dropped rather than read as text.
+    pub strongs: bool,
+}
+
+/// Split a verse into styled segments: the red-letter, italic and Strong's tags are consumed
+/// and turned into flags, then each run is split further around the search terms (except
+/// Strong's numbers, which are never highlighted). Footnotes and notes are dropped with
+/// their content and other tags (`<pb/>`, `<br/>`, …) are dropped alone; a malformed `<`
+/// stays in the text. Unbalanced tags simply leave their flag on (or off) until the end
+/// of the verse.
+/// With an anchored match position, only the verse's first (or last) run of text is searched for terms.
+///
+/// At most `max_highlights` matches are highlighted, bounding the widgets a pathological
+/// verse produces: from the next match on, the rest of each run of text stays one plain
+/// segment (tags are still consumed), and the number of matches left unhighlighted is
+/// returned alongside the segments.
+pub fn segment_verse<'a>(
+    text: &'a str,
+    terms: &[&str],
+    matching: TermMatching,
+    max_highlights: usize,
+    red_letter: Option<&RedLetterMarkup>,
+) -> (Vec<VerseSegment<'a>>, usize) {
+    let mut segments = Vec::new();
+    let mut in_red = false;
+    let mut in_italic = false;
+    let mut in_strongs = false;
+    // Footnotes may nest; text is hidden until the outermost one closes.
+    let mut hidden_depth = 0usize;
+    let mut seen_text = false;
+    let mut highlights = 0;
+    let mut hidden = 0;
+    let mut rest = text;
+    loop {
+        let next = next_tag(rest);
+        let run_end = next.map_or(rest.len(), |(pos, _)| pos);
+        // Footnote content is skipped: an empty run keeps the segments pointing into `text`.
+        let run = &rest[..if hidden_depth > 0 { 0 } else { run_end }];
+        let run_terms = match matching.position {
+            _ if in_strongs => &[][..],
+            MatchPosition::Start if seen_text => &[][..],
+            MatchPosition::End if !only_markup(&rest[run_end..]) => &[][..],
+            _ => terms,
+        };
+        seen_text |= !in_strongs && !run.trim().is_empty();
+        let pieces = crate::split_for_highlight(run, run_terms, matching);
+        for (idx, &(piece, highlight)) in pieces.iter().enumerate() {
+            if highlight && highlights == max_highlights {
+                hidden += pieces[idx..].iter().filter(|(_, highlight)| *highlight).count();
+                segments.push(VerseSegment {
+                    text: &run[piece.as_ptr() as usize - run.as_ptr() as usize..],
+                    highlight: false,
+                    red_letter: in_red,
+                    italic: in_italic,
+                    strongs: in_strongs,
+                });
+                break;
+            }
+            highlights += highlight as usize;
+            if !piece.is_empty() {
+                segments.push(VerseSegment {
+                    text: piece,
+                    highlight,
+                    red_letter: in_red,
+                    italic: in_italic,
+                    strongs: in_strongs,
+                });
+            }
+        }
+        match next {
+            Some((pos, tag)) => {
+                match classify(tag, red_letter) {
+                    Some((Span::RedLetter, opens)) => in_red = opens,
+                    Some((Span::Italic, opens)) => in_italic = opens,
+                    Some((Span::Strongs, opens)) => in_strongs = opens,
+                    Some((Span::Hidden, true)) => hidden_depth += 1,
+                    Some((Span::Hidden, false)) => hidden_depth = hidden_depth.saturating_sub(1),
+                    None => {}
+                }
+                rest = &rest[pos + tag.len()..];
+            }
+            None => break,
+        }
+    }
+    (segments, hidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const J: Option<&RedLetterMarkup> = Some(&RED_LETTER_CONVENTIONS[1]);
+
+    fn segment(text: &str, highlight: bool, red_letter: bool, italic: bool) -> VerseSegment<'_> {
+        VerseSegment { text, highlight, red_letter, italic, strongs: false }
+    }
+
+    #[test]
+    fn highlight_cap_still_consumes_tags() {
+        let text = "<J>love and love</J> and <i>love</i><f>love note</f> end";
+        let (segments, hidden) = segment_verse(text, &["love"], TermMatching::default(), 1, J);
+        assert_eq!(
+            segments,
+            [
+                segment("love", true, true, false),
+                segment(" and ", false, true, false),
+                segment("love", false, true, false),
+                segment(" and ", false, false, false),
+                segment("love", false, false, true),
+                segment(" end", false, false, false),
+            ]
+        );
+        assert_eq!(hidden, 2);
+        assert!(segments.iter().all(|s| !s.text.contains('<')));
+    }
+
+    #[test]
+    fn red_letter_italic_and_highlight_combine() {
+        let text = "Jesus said, <J>I am the <i>good</i> shepherd</J>: the good <i>shepherd giveth</i>";
+        let (segments, hidden) = segment_verse(text, &["good shepherd", "shepherd"], TermMatching::default(), 50, J);
+        assert_eq!(
+            segments,
+            [
+                segment("Jesus said, ", false, false, false),
+                segment("I am the ", false, true, false),
+                segment("good", false, true, true),
+                segment(" ", false, true, false),
+                segment("shepherd", true, true, false),
+                segment(": the good ", false, false, false),
+                segment("shepherd", true, false, true),
+                segment(" giveth", false, false, true),
+            ]
+        );
+        assert_eq!(hidden, 0);
+
+        // Without known red-letter markup the tags are still dropped, but nothing is red.
+        let (segments, _) = segment_verse("<J><i>Follow</i> me</J>", &["me"], TermMatching::default(), 50, None);
+        assert_eq!(
+            segments,
+            [segment("Follow", false, false, true), segment(" ", false, false, false), segment("me", true, false, false)]
+        );
+
+        // The other convention, left open: the flag stays on to the end of the verse.
+        let fr = Some(&RED_LETTER_CONVENTIONS[0]);
+        let (segments, _) = segment_verse("Verily <FR>I say <i>unto</i> you", &["you"], TermMatching::default(), 50, fr);
+        assert_eq!(
+            segments,
+            [
+                segment("Verily ", false, false, false),
+                segment("I say ", false, true, false),
+                segment("unto", false, true, true),
+                segment(" ", false, true, false),
+                segment("you", true, true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_tag_skips_stray_and_unclosed_brackets() {
+        assert_eq!(next_tag("1 < 2 and 3 <4"), None);
+        assert_eq!(next_tag("unclosed <i tag runs on"), None);
+        assert_eq!(next_tag("a <b <pb/> c"), Some((5, "<pb/>")));
+    }
+
+    #[test]
+    fn clean_text_drops_markup_only() {
+        assert!(matches!(clean_text("1 < 2, unclosed <i"), Cow::Borrowed("1 < 2, unclosed <i")));
+        assert_eq!(clean_text("And God said,<pb/> Let there be light"), "And God said, Let there be light");
+        assert_eq!(clean_text("<J>I am</J> the <i>good</i> shepherd<S>4166</S>"), "I am the good shepherd");
+        assert_eq!(clean_text("word<f>note <f>inner</f> more</f> end"), "word end");
+    }
+}
+
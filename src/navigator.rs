@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::passage::BookExtent;
+use crate::Book;
+
+// -------------------------------
+// Book/Chapter Navigator
+// -------------------------------
+
+/// A book in the navigator sidebar with the last verse of each of its chapters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavBook {
+    pub short_name: String,
+    pub long_name: String,
+    /// `(chapter, last verse)` pairs in chapter order.
+    pub chapters: Vec<(u32, u32)>,
+}
+
+/// Build the navigator tree from the books table and the chapter extents.
+/// Books keep the books-table order; books with no verses in this module (e.g. apocrypha
+/// listed in the books table but not translated) are left out.
+pub fn build_navigator(books: &[Book], extents: &[BookExtent]) -> Vec<NavBook> {
+    let chapters_by_book: HashMap<&str, &Vec<(u32, u32)>> = extents
+        .iter()
+        .map(|extent| (extent.short_name.as_str(), &extent.chapters))
+        .collect();
+    books
+        .iter()
+        .filter_map(|book| {
+            let chapters = chapters_by_book.get(book.short_name.as_str())?;
+            Some(NavBook {
+                short_name: book.short_name.clone(),
+                long_name: book.long_name.clone(),
+                chapters: chapters.to_vec(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(book_number: u32, short_name: &str, long_name: &str) -> Book {
+        Book { book_number, short_name: short_name.to_string(), long_name: long_name.to_string() }
+    }
+
+    fn extent(book_number: u32, short_name: &str, chapters: &[(u32, u32)]) -> BookExtent {
+        BookExtent { book_number, short_name: short_name.to_string(), chapters: chapters.to_vec() }
+    }
+
+    #[test]
+    fn navigator_keeps_book_order_and_skips_untranslated_books() {
+        let books = [book(10, "Gen", "Genesis"), book(180, "Tob", "Tobit"), book(230, "Ps", "Psalms")];
+        // Extents come in whatever order the query returned them.
+        let extents = [extent(230, "Ps", &[(1, 6), (2, 12)]), extent(10, "Gen", &[(1, 31)])];
+        assert_eq!(
+            build_navigator(&books, &extents),
+            [
+                NavBook { short_name: "Gen".to_string(), long_name: "Genesis".to_string(), chapters: vec![(1, 31)] },
+                NavBook { short_name: "Ps".to_string(), long_name: "Psalms".to_string(), chapters: vec![(1, 6), (2, 12)] },
+            ]
+        );
+        assert!(build_navigator(&books, &[]).is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+
+// -------------------------------
+// Random Passage Selection
+// -------------------------------
+
+/// Number of consecutive verses in a "surprise me" passage.
+pub const PASSAGE_LENGTH: u32 = 5;
+
+/// A book's chapters and how many verses each has.
+#[derive(Debug, Clone)]
+pub struct BookExtent {
+    pub book_number: u32,
+    pub short_name: String,
+    /// `(chapter, last verse)` pairs in chapter order.
+    pub chapters: Vec<(u32, u32)>,
+}
+
+/// How the random passage's book is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassageWeighting {
+    /// Every book is equally likely.
+    Even,
+    /// Narrative books (Genesis–Esther histories, Jonah, Gospels, Acts) are favoured.
+    Narrative,
+    /// Like `Even`, but chapters that are mostly genealogy lists are never picked.
+    AvoidGenealogies,
+}
+
+impl PassageWeighting {
+    pub const ALL: [PassageWeighting; 3] = [
+        PassageWeighting::Even,
+        PassageWeighting::Narrative,
+        PassageWeighting::AvoidGenealogies,
+    ];
+
+    /// Relative weight of a book (by MyBible book number) under this weighting.
+    fn book_weight(&self, book_number: u32) -> u32 {
+        match self {
+            PassageWeighting::Narrative if NARRATIVE_BOOKS.contains(&book_number) => 4,
+            _ => 1,
+        }
+    }
+
+    fn allows_chapter(&self, book_number: u32, chapter: u32) -> bool {
+        match self {
+            PassageWeighting::AvoidGenealogies => !GENEALOGY_CHAPTERS
+                .iter()
+                .any(|&(book, first, last)| book == book_number && (first..=last).contains(&chapter)),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for PassageWeighting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            PassageWeighting::Even => "Any book",
+            PassageWeighting::Narrative => "Favour narrative",
+            PassageWeighting::AvoidGenealogies => "Avoid genealogies",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// MyBible book numbers of the narrative books.
+const NARRATIVE_BOOKS: [u32; 16] = [
+    10, 20, 60, 70, 80, 90, 100, 110, 120, 190, // Genesis, Exodus, Joshua–2 Kings, Esther
+    390, // Jonah
+    470, 480, 490, 500, 510, // Gospels and Acts
+];
+
+/// `(book number, first chapter, last chapter)` ranges that are mostly genealogies.
+const GENEALOGY_CHAPTERS: [(u32, u32, u32); 9] = [
+    (10, 5, 5),    // Genesis 5
+    (10, 10, 11),  // Genesis 10–11
+    (10, 36, 36),  // Genesis 36
+    (10, 46, 46),  // Genesis 46
+    (130, 1, 9),   // 1 Chronicles 1–9
+    (150, 2, 2),   // Ezra 2
+    (160, 7, 7),   // Nehemiah 7
+    (470, 1, 1),   // Matthew 1
+    (490, 3, 3),   // Luke 3
+];
+
+/// A randomly chosen run of consecutive verses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassagePick {
+    pub book: String,
+    pub chapter: u32,
+    pub start_verse: u32,
+    pub end_verse: u32,
+}
+
+impl PassagePick {
+    /// The pick as a lookup reference, e.g. "Gen 5:3-7".
+    pub fn reference(&self) -> String {
+        format!("{} {}:{}-{}", self.book, self.chapter, self.start_verse, self.end_verse)
+    }
+}
+
+/// SplitMix64: a tiny deterministic generator so picks are reproducible from a seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound` (`bound` must be non-zero).
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+/// A position in `0..count` chosen by `seed`, always the same for the same seed
+/// (`count` must be non-zero).
+pub fn seeded_index(seed: u64, count: u64) -> u64 {
+    SplitMix64(seed).below(count)
+}
+
+/// Pick a book (by weight), then one of its allowed chapters, then a start verse
+/// so that up to `length` consecutive verses fit in the chapter.
+/// Returns None if no book has an allowed chapter.
+pub fn pick_passage(
+    extents: &[BookExtent],
+    weighting: PassageWeighting,
+    length: u32,
+    seed: u64,
+) -> Option<PassagePick> {
+    let mut rng = SplitMix64(seed);
+    let candidates: Vec<(&BookExtent, Vec<(u32, u32)>)> = extents
+        .iter()
+        .map(|book| {
+            let chapters = book
+                .chapters
+                .iter()
+                .copied()
+                .filter(|&(ch, last)| last > 0 && weighting.allows_chapter(book.book_number, ch))
+                .collect::<Vec<_>>();
+            (book, chapters)
+        })
+        .filter(|(_, chapters)| !chapters.is_empty())
+        .collect();
+    let total_weight: u64 = candidates
+        .iter()
+        .map(|(book, _)| weighting.book_weight(book.book_number) as u64)
+        .sum();
+    if total_weight == 0 {
+        return None;
+    }
+    let mut ticket = rng.below(total_weight);
+    let (book, chapters) = candidates.iter().find(|(book, _)| {
+        let weight = weighting.book_weight(book.book_number) as u64;
+        if ticket < weight {
+            true
+        } else {
+            ticket -= weight;
+            false
+        }
+    })?;
+    let (chapter, last_verse) = chapters[rng.below(chapters.len() as u64) as usize];
+    let length = length.clamp(1, last_verse);
+    let start_verse = 1 + rng.below((last_verse - length + 1) as u64) as u32;
+    Some(PassagePick {
+        book: book.short_name.clone(),
+        chapter,
+        start_verse,
+        end_verse: start_verse + length - 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extent(book_number: u32, short_name: &str, chapters: &[(u32, u32)]) -> BookExtent {
+        BookExtent { book_number, short_name: short_name.to_string(), chapters: chapters.to_vec() }
+    }
+
+    fn extents() -> Vec<BookExtent> {
+        vec![
+            extent(10, "Gen", &[(1, 31), (5, 32), (6, 22)]),
+            extent(230, "Ps", &[(23, 6), (117, 2)]),
+            extent(470, "Matt", &[(1, 25), (5, 48)]),
+        ]
+    }
+
+    #[test]
+    fn the_same_seed_picks_the_same_passage() {
+        let extents = extents();
+        for weighting in PassageWeighting::ALL {
+            for seed in 0..50 {
+                let pick = pick_passage(&extents, weighting, PASSAGE_LENGTH, seed).unwrap();
+                assert_eq!(pick_passage(&extents, weighting, PASSAGE_LENGTH, seed), Some(pick.clone()));
+                let book = extents.iter().find(|b| b.short_name == pick.book).unwrap();
+                let &(_, last) = book.chapters.iter().find(|&&(ch, _)| ch == pick.chapter).unwrap();
+                assert!(pick.start_verse >= 1 && pick.end_verse <= last);
+                assert_eq!(pick.end_verse - pick.start_verse + 1, PASSAGE_LENGTH.min(last));
+            }
+        }
+        let picks: Vec<_> = (0..50).map(|seed| pick_passage(&extents, PassageWeighting::Even, 5, seed)).collect();
+        assert!(picks.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(seeded_index(7, 10), seeded_index(7, 10));
+    }
+
+    #[test]
+    fn genealogies_can_be_avoided() {
+        let extents = extents();
+        for seed in 0..200 {
+            let pick = pick_passage(&extents, PassageWeighting::AvoidGenealogies, 5, seed).unwrap();
+            assert!(!(pick.book == "Gen" && pick.chapter == 5) && !(pick.book == "Matt" && pick.chapter == 1));
+        }
+        let only_genealogies = [extent(10, "Gen", &[(5, 32)])];
+        assert_eq!(pick_passage(&only_genealogies, PassageWeighting::AvoidGenealogies, 5, 1), None);
+        assert_eq!(pick_passage(&[], PassageWeighting::Even, 5, 1), None);
+    }
+}
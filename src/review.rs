@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::compare::{pinned_rows, VerseKey};
+use crate::Verse;
+
+// -------------------------------
+// Translation Review Sheets
+// -------------------------------
+
+/// What happened to a word going from the base translation to another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Same,
+    /// Only in the other translation.
+    Added,
+    /// Only in the base translation.
+    Removed,
+}
+
+/// Word-level diff of `other` against `base`, splitting both on whitespace: the longest
+/// common run of words is kept as `Same`, everything else is `Removed` or `Added`.
+/// Removals are listed before the additions that replace them.
+pub fn word_diff<'a>(base: &'a str, other: &'a str) -> Vec<(DiffOp, &'a str)> {
+    let a: Vec<&str> = base.split_whitespace().collect();
+    let b: Vec<&str> = other.split_whitespace().collect();
+    // lcs[i][j]: length of the longest common subsequence of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let mut diff = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            diff.push((DiffOp::Same, a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push((DiffOp::Removed, a[i]));
+            i += 1;
+        } else {
+            diff.push((DiffOp::Added, b[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// One verse of a diff between two translations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerseDiff<'a> {
+    /// Both have the verse: the words of the second diffed against the first.
+    Both(Vec<(DiffOp, &'a str)>),
+    /// Only the first translation has the verse.
+    OnlyFirst(&'a str),
+    /// Only the second translation has the verse.
+    OnlySecond(&'a str),
+}
+
+/// Align two translations' verses by chapter:verse and diff the words of each verse both
+/// have, in canonical order.
+pub fn pair_diff<'a>(first: &'a [Verse], second: &'a [Verse]) -> Vec<(VerseKey, VerseDiff<'a>)> {
+    let mut rows: BTreeMap<VerseKey, (Option<&str>, Option<&str>)> = BTreeMap::new();
+    for v in first {
+        rows.entry((v.chapter, v.verse)).or_default().0 = Some(v.text.as_str());
+    }
+    for v in second {
+        rows.entry((v.chapter, v.verse)).or_default().1 = Some(v.text.as_str());
+    }
+    rows.into_iter()
+        .filter_map(|(key, texts)| {
+            let diff = match texts {
+                (Some(a), Some(b)) => VerseDiff::Both(word_diff(a, b)),
+                (Some(a), None) => VerseDiff::OnlyFirst(a),
+                (None, Some(b)) => VerseDiff::OnlySecond(b),
+                (None, None) => return None,
+            };
+            Some((key, diff))
+        })
+        .collect()
+}
+
+/// Consecutive words with the same diff op joined into runs, so a verse renders as a few
+/// styled pieces rather than one per word.
+pub fn diff_runs(diff: &[(DiffOp, &str)]) -> Vec<(DiffOp, String)> {
+    let mut runs: Vec<(DiffOp, String)> = Vec::new();
+    for &(op, word) in diff {
+        match runs.last_mut() {
+            Some((last, run)) if *last == op => {
+                run.push(' ');
+                run.push_str(word);
+            }
+            _ => runs.push((op, word.to_string())),
+        }
+    }
+    runs
+}
+
+/// Output format of a review sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewFormat {
+    Markdown,
+    Html,
+}
+
+impl ReviewFormat {
+    pub const ALL: [ReviewFormat; 2] = [ReviewFormat::Markdown, ReviewFormat::Html];
+}
+
+impl fmt::Display for ReviewFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ReviewFormat::Markdown => "Review sheet: Markdown",
+            ReviewFormat::Html => "Review sheet: HTML",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// One-line summary of a verse's diff against the base, e.g. "3 added, 2 removed".
+pub fn diff_summary(diff: &[(DiffOp, &str)]) -> String {
+    let added = diff.iter().filter(|(op, _)| *op == DiffOp::Added).count();
+    let removed = diff.iter().filter(|(op, _)| *op == DiffOp::Removed).count();
+    if added == 0 && removed == 0 {
+        "identical".to_string()
+    } else {
+        format!("{} added, {} removed", added, removed)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Escape what would break a Markdown table cell or start emphasis.
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('*', "\\*").replace('~', "\\~")
+}
+
+fn escape(text: &str, format: ReviewFormat) -> String {
+    match format {
+        ReviewFormat::Markdown => escape_markdown(text),
+        ReviewFormat::Html => escape_html(text),
+    }
+}
+
+/// A diff rendered inline: removed words struck through, added words emphasized.
+fn render_diff(diff: &[(DiffOp, &str)], format: ReviewFormat) -> String {
+    let words: Vec<String> = diff
+        .iter()
+        .map(|&(op, word)| {
+            let word = escape(word, format);
+            match (format, op) {
+                (_, DiffOp::Same) => word,
+                (ReviewFormat::Markdown, DiffOp::Added) => format!("**{}**", word),
+                (ReviewFormat::Markdown, DiffOp::Removed) => format!("~~{}~~", word),
+                (ReviewFormat::Html, DiffOp::Added) => format!("<ins>{}</ins>", word),
+                (ReviewFormat::Html, DiffOp::Removed) => format!("<del>{}</del>", word),
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+/// A review sheet for the compared passage `reference`: for every compared verse, a table
+/// with the base translation's text and each other translation's word diff against it,
+/// plus a per-verse change summary. `base` names the column to diff against; when no
+/// compared translation has that label, the first one is used.
+pub fn review_sheet(
+    compare_results: &[(String, Vec<Verse>)],
+    base: &str,
+    reference: &str,
+    format: ReviewFormat,
+) -> String {
+    let base_idx = compare_results.iter().position(|(label, _)| label == base).unwrap_or(0);
+    let base_label = compare_results.get(base_idx).map(|(label, _)| label.as_str()).unwrap_or(base);
+    let keys: BTreeSet<VerseKey> = compare_results
+        .iter()
+        .flat_map(|(_, verses)| verses.iter().map(|v| (v.chapter, v.verse)))
+        .collect();
+    let mut out = String::new();
+    match format {
+        ReviewFormat::Markdown => {
+            out.push_str(&format!("# Review sheet: {}\n\nBase translation: {}\n", reference, base_label));
+        }
+        ReviewFormat::Html => {
+            out.push_str(&format!(
+                "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Review sheet: {r}</title></head>\n<body>\n\
+                 <h1>Review sheet: {r}</h1>\n<p>Base translation: {b}</p>\n",
+                r = escape_html(reference),
+                b = escape_html(base_label)
+            ));
+        }
+    }
+    for ((chapter, verse), cells) in pinned_rows(compare_results, &keys) {
+        let base_text = cells.get(base_idx).and_then(|(_, text)| *text);
+        let rows: Vec<(&str, String, String)> = cells
+            .iter()
+            .enumerate()
+            .map(|(idx, &(label, text))| match (text, base_text) {
+                (None, _) => (label, String::new(), "missing".to_string()),
+                (Some(text), _) if idx == base_idx => (label, escape(text, format), "base".to_string()),
+                (Some(text), None) => (label, escape(text, format), "not in base".to_string()),
+                (Some(text), Some(base_text)) => {
+                    let diff = word_diff(base_text, text);
+                    (label, render_diff(&diff, format), diff_summary(&diff))
+                }
+            })
+            .collect();
+        match format {
+            ReviewFormat::Markdown => {
+                out.push_str(&format!("\n## {}:{}\n\n| Translation | Text | Changes |\n|---|---|---|\n", chapter, verse));
+                for (label, text, summary) in rows {
+                    out.push_str(&format!("| {} | {} | {} |\n", escape_markdown(label), text, summary));
+                }
+            }
+            ReviewFormat::Html => {
+                out.push_str(&format!(
+                    "<h2>{}:{}</h2>\n<table>\n<tr><th>Translation</th><th>Text</th><th>Changes</th></tr>\n",
+                    chapter, verse
+                ));
+                for (label, text, summary) in rows {
+                    out.push_str(&format!(
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                        escape_html(label),
+                        text,
+                        summary
+                    ));
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+    if format == ReviewFormat::Html {
+        out.push_str("</body>\n</html>\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::verse;
+
+    fn compared() -> Vec<(String, Vec<Verse>)> {
+        vec![
+            (
+                "KJV".to_string(),
+                vec![verse("Ps", 23, 1, "The LORD is my shepherd"), verse("Ps", 23, 2, "He maketh me")],
+            ),
+            ("WEB".to_string(), vec![verse("Ps", 23, 1, "Yahweh is my shepherd")]),
+        ]
+    }
+
+    #[test]
+    fn review_sheet_diffs_each_verse_against_the_base() {
+        let sheet = review_sheet(&compared(), "WEB", "Ps 23:1-2", ReviewFormat::Markdown);
+        assert_eq!(
+            sheet,
+            "# Review sheet: Ps 23:1-2\n\nBase translation: WEB\n\
+             \n## 23:1\n\n| Translation | Text | Changes |\n|---|---|---|\n\
+             | KJV | ~~Yahweh~~ **The** **LORD** is my shepherd | 2 added, 1 removed |\n\
+             | WEB | Yahweh is my shepherd | base |\n\
+             \n## 23:2\n\n| Translation | Text | Changes |\n|---|---|---|\n\
+             | KJV | He maketh me | not in base |\n\
+             | WEB |  | missing |\n"
+        );
+    }
+
+    #[test]
+    fn review_sheet_falls_back_to_the_first_translation() {
+        let sheet = review_sheet(&compared(), "NIV", "Ps 23:1-2", ReviewFormat::Html);
+        assert!(sheet.contains("<p>Base translation: KJV</p>"));
+        assert!(sheet.contains("<tr><td>KJV</td><td>The LORD is my shepherd</td><td>base</td></tr>"));
+        assert!(sheet.contains("<tr><td>WEB</td><td><del>The</del> <del>LORD</del> <ins>Yahweh</ins> is my shepherd</td><td>1 added, 2 removed</td></tr>"));
+        assert!(sheet.ends_with("</body>\n</html>\n"));
+    }
+}
@@ -0,0 +1,238 @@
+use rusqlite::{Connection, Error as RusqliteError};
+use std::fmt;
+use std::io;
+
+// -------------------------------
+// Module Schema Detection
+// -------------------------------
+
+/// Names the books table may go by, in order of preference.
+const BOOKS_TABLE_CANDIDATES: [&str; 2] = ["books", "books_all"];
+
+/// Names the book foreign-key column may go by, in order of preference.
+const BOOK_COLUMN_CANDIDATES: [&str; 3] = ["book_number", "book", "book_id"];
+
+/// Names of an optional verses column holding the last verse of a combined row.
+const VERSE_END_COLUMN_CANDIDATES: [&str; 2] = ["verse_end", "end_verse"];
+
+/// The table layout of an opened Bible module, as discovered by `validate_schema`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibleSchema {
+    /// Name of the table holding the book number, `short_name` and `long_name`.
+    pub books_table: String,
+    /// Column identifying the book in the books table.
+    pub books_book_column: String,
+    /// Column identifying the book in the verses table.
+    pub verses_book_column: String,
+    /// Column giving the last verse of a combined row (e.g. verses 3–4 stored once), if any.
+    pub verse_end_column: Option<String>,
+}
+
+impl Default for BibleSchema {
+    fn default() -> Self {
+        BibleSchema {
+            books_table: BOOKS_TABLE_CANDIDATES[0].to_string(),
+            books_book_column: BOOK_COLUMN_CANDIDATES[0].to_string(),
+            verses_book_column: BOOK_COLUMN_CANDIDATES[0].to_string(),
+            verse_end_column: None,
+        }
+    }
+}
+
+impl BibleSchema {
+    /// The `JOIN` attaching the books table (aliased `b`) to `verses v`.
+    pub fn books_join(&self) -> String {
+        format!(
+            "JOIN {} b ON v.{} = b.{}",
+            self.books_table, self.verses_book_column, self.books_book_column
+        )
+    }
+
+    /// The verse's book number column, qualified with the `v` alias.
+    pub fn verse_book(&self) -> String {
+        format!("v.{}", self.verses_book_column)
+    }
+
+    /// The row's first verse number. A combined row stored as text ("3-4") casts to its start.
+    pub fn verse_start(&self) -> String {
+        "CAST(v.verse AS INTEGER)".to_string()
+    }
+
+    /// The row's last verse number: the end column when the module has one, else the
+    /// part after the dash of a "3-4" verse, else the verse itself.
+    pub fn verse_end(&self) -> String {
+        match &self.verse_end_column {
+            Some(column) => format!("COALESCE(v.{}, CAST(v.verse AS INTEGER))", column),
+            None => "CASE WHEN instr(v.verse, '-') > 0 \
+                     THEN CAST(substr(v.verse, instr(v.verse, '-') + 1) AS INTEGER) \
+                     ELSE CAST(v.verse AS INTEGER) END"
+                .to_string(),
+        }
+    }
+
+    /// The columns `Verse::from_row` reads, in order.
+    pub fn verse_columns(&self) -> String {
+        format!(
+            "b.long_name, v.chapter, {}, v.text, CAST({} AS INTEGER), b.short_name, {}",
+            self.verse_start(),
+            self.verse_book(),
+            self.verse_end()
+        )
+    }
+
+    /// Condition on rows overlapping the range bound as `(start chapter, end chapter, start
+    /// chapter, start verse, end chapter, end verse)`, so a combined row is included when any
+    /// of its verses is. The plain chapter bounds come first so an index on the chapter can
+    /// narrow the rows before the verse numbers are cast.
+    pub fn range_overlap(&self) -> String {
+        format!(
+            "v.chapter BETWEEN ? AND ? \
+             AND ((v.chapter * 1000) + {}) >= ((? * 1000) + ?) AND ((v.chapter * 1000) + {}) <= ((? * 1000) + ?)",
+            self.verse_end(),
+            self.verse_start()
+        )
+    }
+}
+
+/// Why a database can't be used as a Bible module.
+#[derive(Debug)]
+pub enum SchemaError {
+    Sqlite(RusqliteError),
+    /// A compressed module could not be decompressed.
+    Decompress(io::Error),
+    MissingTable(&'static str),
+    /// `(table, column)`: the table lacks any accepted name for the column.
+    MissingColumn(&'static str, &'static str),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Sqlite(e) => write!(f, "{}", e),
+            SchemaError::Decompress(e) => write!(f, "could not decompress module: {}", e),
+            SchemaError::MissingTable(table) => write!(f, "not a Bible module: no {} table", table),
+            SchemaError::MissingColumn(table, column) => {
+                write!(f, "not a Bible module: no {} column in the {} table", column, table)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<RusqliteError> for SchemaError {
+    fn from(e: RusqliteError) -> Self {
+        SchemaError::Sqlite(e)
+    }
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, RusqliteError> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type IN ('table', 'view') AND name = ?",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, RusqliteError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?.collect();
+    columns
+}
+
+/// The first book-column candidate present in `table`.
+fn book_column(conn: &Connection, table: &'static str) -> Result<String, SchemaError> {
+    let columns = table_columns(conn, table)?;
+    BOOK_COLUMN_CANDIDATES
+        .iter()
+        .find(|candidate| columns.iter().any(|c| c.eq_ignore_ascii_case(candidate)))
+        .map(|c| c.to_string())
+        .ok_or(SchemaError::MissingColumn(table, "book_number"))
+}
+
+/// Check that the module has a `verses` table, find which name its books table uses,
+/// and which columns link verses to books.
+pub fn validate_schema(conn: &Connection) -> Result<BibleSchema, SchemaError> {
+    if !table_exists(conn, "verses")? {
+        return Err(SchemaError::MissingTable("verses"));
+    }
+    let mut books_table = None;
+    for candidate in BOOKS_TABLE_CANDIDATES {
+        if table_exists(conn, candidate)? {
+            books_table = Some(candidate);
+            break;
+        }
+    }
+    let books_table = books_table.ok_or(SchemaError::MissingTable("books"))?;
+    let verse_columns = table_columns(conn, "verses")?;
+    let verse_end_column = VERSE_END_COLUMN_CANDIDATES
+        .iter()
+        .find(|candidate| verse_columns.iter().any(|c| c.eq_ignore_ascii_case(candidate)))
+        .map(|c| c.to_string());
+    Ok(BibleSchema {
+        books_table: books_table.to_string(),
+        books_book_column: book_column(conn, books_table)?,
+        verses_book_column: book_column(conn, "verses")?,
+        verse_end_column,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::fixture_connection;
+
+    #[test]
+    fn book_column_naming_conventions() {
+        let conn = fixture_connection();
+        let schema = validate_schema(&conn).unwrap();
+        assert_eq!(schema.books_book_column, "book_number");
+        assert_eq!(schema.verses_book_column, "book_number");
+        assert_eq!(schema.books_join(), "JOIN books b ON v.book_number = b.book_number");
+
+        conn.execute_batch(
+            "ALTER TABLE verses RENAME COLUMN book_number TO book;
+             ALTER TABLE books RENAME COLUMN book_number TO book_id;",
+        )
+        .unwrap();
+        let schema = validate_schema(&conn).unwrap();
+        assert_eq!(schema.books_book_column, "book_id");
+        assert_eq!(schema.verses_book_column, "book");
+        assert_eq!(schema.books_join(), "JOIN books b ON v.book = b.book_id");
+
+        conn.execute_batch("ALTER TABLE verses RENAME COLUMN book TO volume;").unwrap();
+        assert!(matches!(
+            validate_schema(&conn),
+            Err(SchemaError::MissingColumn("verses", "book_number"))
+        ));
+    }
+
+    #[test]
+    fn books_table_naming_conventions() {
+        let conn = fixture_connection();
+        assert_eq!(validate_schema(&conn).unwrap().books_table, "books");
+
+        conn.execute_batch("ALTER TABLE books RENAME TO books_all;").unwrap();
+        let schema = validate_schema(&conn).unwrap();
+        assert_eq!(schema.books_table, "books_all");
+        assert_eq!(schema.books_join(), "JOIN books_all b ON v.book_number = b.book_number");
+        let long_name: String = conn
+            .query_row(
+                &format!("SELECT b.long_name FROM verses v {} WHERE v.chapter = 11", schema.books_join()),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(long_name, "John");
+
+        // With both present, `books` is preferred.
+        conn.execute_batch("CREATE TABLE books (book_number NUMERIC, short_name TEXT, long_name TEXT);").unwrap();
+        assert_eq!(validate_schema(&conn).unwrap().books_table, "books");
+
+        conn.execute_batch("DROP TABLE books; DROP TABLE books_all;").unwrap();
+        assert!(matches!(validate_schema(&conn), Err(SchemaError::MissingTable("books"))));
+        conn.execute_batch("DROP TABLE verses;").unwrap();
+        assert!(matches!(validate_schema(&conn), Err(SchemaError::MissingTable("verses"))));
+    }
+}
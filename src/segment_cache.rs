@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::markup::{segment_verse, RedLetterMarkup, VerseSegment};
+use crate::{TermMatching, Verse};
+
+// -------------------------------
+// Highlight Segment Cache
+// -------------------------------
+
+/// A cached segment, stored as a byte range into its verse text so the cache doesn't
+/// borrow from the results it describes.
+#[derive(Debug, Clone, Copy)]
+struct CachedSpan {
+    start: usize,
+    end: usize,
+    highlight: bool,
+    red_letter: bool,
+    italic: bool,
+    strongs: bool,
+}
+
+/// Memoizes `segment_verse` across `view()` calls.
+///
+/// iced rebuilds the whole widget tree on every update, so without this every keystroke
+/// in *any* input re-ran the highlight regex over every search result. Entries are keyed
+/// by the verse's book, chapter and verse plus a hash of its text, so a verse of another
+/// translation or another search never picks up spans computed for different text, and
+/// the whole cache is tied to the terms, term matching and markup it was built for: a
+/// different query empties it.
+#[derive(Debug, Default)]
+pub struct SegmentCache {
+    inner: RefCell<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// The terms, term matching, highlight cap and markup the entries were computed for.
+    key: String,
+    /// Each verse's spans and its number of matches beyond the highlight cap.
+    entries: HashMap<EntryKey, (Vec<CachedSpan>, usize)>,
+}
+
+impl SegmentCache {
+    /// Forget all entries, releasing the memory of verses no longer rendered.
+    pub fn clear(&self) {
+        let mut state = self.inner.borrow_mut();
+        state.entries.clear();
+        state.key.clear();
+    }
+
+    /// The segments of the verse's text (and its unhighlighted match count) for these terms,
+    /// matching, highlight cap and markup, computed at most once per verse.
+    pub fn segments<'a>(
+        &self,
+        verse: &'a Verse,
+        terms: &[&str],
+        matching: TermMatching,
+        max_highlights: usize,
+        red_letter: Option<&RedLetterMarkup>,
+    ) -> (Vec<VerseSegment<'a>>, usize) {
+        let key = cache_key(terms, matching, max_highlights, red_letter);
+        let text = verse.text.as_str();
+        let entry_key = entry_key(verse);
+        let mut state = self.inner.borrow_mut();
+        if state.key != key {
+            state.entries.clear();
+            state.key = key;
+        }
+        if let Some((spans, hidden)) = state.entries.get(&entry_key) {
+            let segments = spans
+                .iter()
+                .map(|span| VerseSegment {
+                    text: &text[span.start..span.end],
+                    highlight: span.highlight,
+                    red_letter: span.red_letter,
+                    italic: span.italic,
+                    strongs: span.strongs,
+                })
+                .collect();
+            return (segments, *hidden);
+        }
+        let (segments, hidden) = segment_verse(text, terms, matching, max_highlights, red_letter);
+        let base = text.as_ptr() as usize;
+        let spans = segments
+            .iter()
+            .map(|segment| {
+                let start = segment.text.as_ptr() as usize - base;
+                CachedSpan {
+                    start,
+                    end: start + segment.text.len(),
+                    highlight: segment.highlight,
+                    red_letter: segment.red_letter,
+                    italic: segment.italic,
+                    strongs: segment.strongs,
+                }
+            })
+            .collect();
+        state.entries.insert(entry_key, (spans, hidden));
+        (segments, hidden)
+    }
+}
+
+/// A verse's book number, chapter, verse and text hash.
+type EntryKey = (u32, u32, u32, u64);
+
+fn entry_key(verse: &Verse) -> EntryKey {
+    let mut hasher = DefaultHasher::new();
+    verse.text.hash(&mut hasher);
+    (verse.book_number, verse.chapter, verse.verse, hasher.finish())
+}
+
+/// Identifies the inputs a set of cached segments depends on.
+fn cache_key(
+    terms: &[&str],
+    matching: TermMatching,
+    max_highlights: usize,
+    red_letter: Option<&RedLetterMarkup>,
+) -> String {
+    let markup = red_letter.map(|m| m.open).unwrap_or("");
+    format!("{}\u{1}{:?}\u{1}{}\u{1}{}", terms.join("\u{0}"), matching, max_highlights, markup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixture::verse;
+
+    fn highlighted<'a>(segments: &[VerseSegment<'a>]) -> Vec<&'a str> {
+        segments.iter().filter(|s| s.highlight).map(|s| s.text).collect()
+    }
+
+    #[test]
+    fn repeated_verse_is_served_from_the_cache() {
+        let cache = SegmentCache::default();
+        let wept = verse("John", 11, 35, "Jesus wept.");
+        let (first, _) = cache.segments(&wept, &["wept"], TermMatching::default(), 50, None);
+        let (second, _) = cache.segments(&wept, &["wept"], TermMatching::default(), 50, None);
+        assert_eq!(highlighted(&first), ["wept"]);
+        assert_eq!(highlighted(&second), ["wept"]);
+        assert_eq!(cache.inner.borrow().entries.len(), 1);
+
+        // The same reference in another translation is a separate entry.
+        let other = verse("John", 11, 35, "Jesus shed tears, he wept.");
+        let (segments, _) = cache.segments(&other, &["wept"], TermMatching::default(), 50, None);
+        assert_eq!(segments.last().map(|s| s.text), Some("."));
+        assert_eq!(highlighted(&segments), ["wept"]);
+        assert_eq!(cache.inner.borrow().entries.len(), 2);
+    }
+
+    #[test]
+    fn changed_query_invalidates_the_cache() {
+        let cache = SegmentCache::default();
+        let wept = verse("John", 11, 35, "Jesus wept.");
+        cache.segments(&wept, &["wept"], TermMatching::default(), 50, None);
+        let (segments, _) = cache.segments(&wept, &["Jesus"], TermMatching::default(), 50, None);
+        assert_eq!(highlighted(&segments), ["Jesus"]);
+        assert_eq!(cache.inner.borrow().entries.len(), 1);
+    }
+}
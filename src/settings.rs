@@ -0,0 +1,481 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::verse_numbers::LeadingNumbers;
+
+// -------------------------------
+// Persisted Settings
+// -------------------------------
+
+/// The app's config directory: `$XDG_CONFIG_HOME/biblical_gui` (falling back to `~/.config`).
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("biblical_gui"))
+}
+
+/// A user-typed directory, with a leading `~` standing for the home directory.
+pub fn expand_home(input: &str) -> PathBuf {
+    let input = input.trim();
+    match (input.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(input),
+    }
+}
+
+/// Where settings are kept between runs.
+pub fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("settings.txt"))
+}
+
+/// How the advanced search input is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// One query line with AND/OR/NOT operators.
+    Plain,
+    /// One phrase per line, ORed together.
+    Lines,
+    /// Verses compared by their number of words.
+    WordCount,
+    /// A regular expression matched against the verse text.
+    Regex,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 4] = [SearchMode::Plain, SearchMode::Lines, SearchMode::WordCount, SearchMode::Regex];
+
+    /// Stable name used in the settings file.
+    pub fn key(&self) -> &'static str {
+        match self {
+            SearchMode::Plain => "plain",
+            SearchMode::Lines => "lines",
+            SearchMode::WordCount => "word_count",
+            SearchMode::Regex => "regex",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<SearchMode> {
+        SearchMode::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    /// The mode after this one in `ALL`, wrapping around to the first.
+    pub fn next(&self) -> SearchMode {
+        let idx = SearchMode::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        SearchMode::ALL[(idx + 1) % SearchMode::ALL.len()]
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SearchMode::Plain => "Query (AND/OR/NOT)",
+            SearchMode::Lines => "One phrase per line (OR)",
+            SearchMode::WordCount => "By number of words",
+            SearchMode::Regex => "Regular expression",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// How NOT terms combine with the other terms of an OR query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotSemantics {
+    /// NOT terms are removed from whatever the rest of the query matches:
+    /// "faith OR hope NOT works" means (faith OR hope) AND NOT works.
+    #[default]
+    Exclude,
+    /// NOT terms are joined by the query's operator like any other term:
+    /// "faith OR NOT works" means faith OR NOT works.
+    Operand,
+}
+
+impl NotSemantics {
+    pub const ALL: [NotSemantics; 2] = [NotSemantics::Exclude, NotSemantics::Operand];
+
+    /// Stable name used in the settings file.
+    pub fn key(&self) -> &'static str {
+        match self {
+            NotSemantics::Exclude => "exclude",
+            NotSemantics::Operand => "operand",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<NotSemantics> {
+        NotSemantics::ALL.into_iter().find(|semantics| semantics.key() == key)
+    }
+}
+
+impl fmt::Display for NotSemantics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            NotSemantics::Exclude => "NOT excludes from all results",
+            NotSemantics::Operand => "NOT joins like other terms",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// The search mode to start in: a pinned mode, or `None` to resume the last-used one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultSearchMode(pub Option<SearchMode>);
+
+impl DefaultSearchMode {
+    pub fn choices() -> Vec<DefaultSearchMode> {
+        std::iter::once(DefaultSearchMode(None))
+            .chain(SearchMode::ALL.into_iter().map(|m| DefaultSearchMode(Some(m))))
+            .collect()
+    }
+}
+
+impl fmt::Display for DefaultSearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            None => write!(f, "Start in last used mode"),
+            Some(mode) => write!(f, "Always start in: {}", mode),
+        }
+    }
+}
+
+/// Widest the main content column grows before it is centered with margins; 0 means no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentWidth(pub u32);
+
+impl ContentWidth {
+    pub const DEFAULT: ContentWidth = ContentWidth(900);
+    pub const CHOICES: [ContentWidth; 5] = [
+        ContentWidth(0),
+        ContentWidth(700),
+        ContentWidth(900),
+        ContentWidth(1200),
+        ContentWidth(1600),
+    ];
+}
+
+impl fmt::Display for ContentWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => write!(f, "Full width"),
+            width => write!(f, "Max {} px", width),
+        }
+    }
+}
+
+/// Verses shown on each side of a search result when opening it in context.
+pub const DEFAULT_CONTEXT_VERSES: u32 = 2;
+pub const CONTEXT_VERSE_CHOICES: [u32; 6] = [0, 1, 2, 3, 4, 5];
+
+/// Verses "Copy all results" puts on the clipboard at most; longer result sets are truncated.
+pub const DEFAULT_COPY_LIMIT: usize = 500;
+pub const COPY_LIMIT_CHOICES: [usize; 4] = [100, 500, 1000, 5000];
+
+/// Settings remembered between runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppSettings {
+    /// Mode pinned as the startup default; takes precedence over `last_search_mode`.
+    pub default_search_mode: Option<SearchMode>,
+    /// Mode in use when the app last saved its settings.
+    pub last_search_mode: Option<SearchMode>,
+    /// How NOT terms combine with the rest of a plain query.
+    pub not_semantics: NotSemantics,
+    /// Flow search results into two side-by-side columns.
+    pub two_column_results: bool,
+    /// Alternate the background of result items in the search and compare panels.
+    pub zebra_stripes: bool,
+    /// Light text on a dark background instead of the default light theme.
+    pub dark_theme: bool,
+    /// Show references with book abbreviations ("Gen 1:1") instead of full names.
+    pub abbreviate_references: bool,
+    /// Whether the book/chapter navigator sidebar is expanded.
+    pub navigator_open: bool,
+    /// Whether the query syntax help under the search input is expanded.
+    pub search_help_open: bool,
+    /// With one result area, whether the lookup panel was the one showing rather than search.
+    pub lookup_tab_active: bool,
+    /// How the search, lookup and compare panels share the window height; `None` shares it evenly.
+    pub panel_weights: Option<[u16; 3]>,
+    /// Also look for compare modules in subdirectories of the compare directory.
+    pub compare_subdirectories: bool,
+    /// Developer aid: show each verse's raw text, markup tags included, under its rendering.
+    pub show_raw_text: bool,
+    /// Show only the search or lookup panel, whichever the quick bar last ran.
+    pub unified_results: bool,
+    /// Maximum content width chosen by the user; `None` uses `ContentWidth::DEFAULT`.
+    pub content_width: Option<ContentWidth>,
+    /// Verses shown around a search result opened in context; `None` uses `DEFAULT_CONTEXT_VERSES`.
+    pub context_verses: Option<u32>,
+    /// Verses copied by "Copy all results" at most; `None` uses `DEFAULT_COPY_LIMIT`.
+    pub copy_limit: Option<usize>,
+    /// Book numbers left out of every search.
+    pub excluded_books: BTreeSet<u32>,
+    /// Directory holding the Bible modules; `None` uses the working directory. Only a
+    /// directory `can_remember_dir` accepts is written to the settings file.
+    pub db_dir: Option<PathBuf>,
+    /// Whether verse numbers stored at the start of verse text are stripped.
+    pub leading_numbers: LeadingNumbers,
+    /// Window size and position when the app was last closed.
+    pub window_size: Option<(u32, u32)>,
+    pub window_position: Option<(i32, i32)>,
+}
+
+impl AppSettings {
+    /// The search mode to start in: the pinned default, else the last-used mode, else plain.
+    pub fn initial_search_mode(&self) -> SearchMode {
+        self.default_search_mode
+            .or(self.last_search_mode)
+            .unwrap_or(SearchMode::Plain)
+    }
+
+    /// The maximum content width in effect.
+    pub fn max_content_width(&self) -> ContentWidth {
+        self.content_width.unwrap_or(ContentWidth::DEFAULT)
+    }
+
+    /// The number of context verses in effect, at most the largest offered choice.
+    pub fn context_verses(&self) -> u32 {
+        let max = CONTEXT_VERSE_CHOICES[CONTEXT_VERSE_CHOICES.len() - 1];
+        self.context_verses.unwrap_or(DEFAULT_CONTEXT_VERSES).min(max)
+    }
+
+    /// The number of verses "Copy all results" copies at most.
+    pub fn copy_limit(&self) -> usize {
+        self.copy_limit.unwrap_or(DEFAULT_COPY_LIMIT).max(1)
+    }
+
+    /// Parse `key=value` lines. Unknown keys and malformed values are ignored so an old or
+    /// hand-edited file never prevents startup.
+    pub fn parse(contents: &str) -> AppSettings {
+        let mut settings = AppSettings::default();
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "default_search_mode" => settings.default_search_mode = SearchMode::from_key(value),
+                "last_search_mode" => settings.last_search_mode = SearchMode::from_key(value),
+                "not_semantics" => settings.not_semantics = NotSemantics::from_key(value).unwrap_or_default(),
+                "two_column_results" => settings.two_column_results = value == "true",
+                "abbreviate_references" => settings.abbreviate_references = value == "true",
+                "zebra_stripes" => settings.zebra_stripes = value == "true",
+                "dark_theme" => settings.dark_theme = value == "true",
+                "navigator_open" => settings.navigator_open = value == "true",
+                "search_help_open" => settings.search_help_open = value == "true",
+                "lookup_tab_active" => settings.lookup_tab_active = value == "true",
+                "panel_weights" => {
+                    let weights: Option<Vec<u16>> = value.split(',').map(|w| w.trim().parse().ok()).collect();
+                    settings.panel_weights = weights.and_then(|w| w.try_into().ok());
+                }
+                "compare_subdirectories" => settings.compare_subdirectories = value == "true",
+                "show_raw_text" => settings.show_raw_text = value == "true",
+                "unified_results" => settings.unified_results = value == "true",
+                "content_width" => settings.content_width = value.parse().ok().map(ContentWidth),
+                "context_verses" => settings.context_verses = value.parse().ok(),
+                "copy_limit" => settings.copy_limit = value.parse().ok(),
+                "db_dir" if !value.is_empty() => settings.db_dir = Some(PathBuf::from(value)),
+                "leading_verse_numbers" => {
+                    settings.leading_numbers = LeadingNumbers::from_key(value).unwrap_or_default()
+                }
+                "window_size" => settings.window_size = parse_pair(value, 'x'),
+                "window_position" => settings.window_position = parse_pair(value, ','),
+                "excluded_books" => {
+                    settings.excluded_books = value.split(',').filter_map(|n| n.trim().parse().ok()).collect()
+                }
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    /// Serialize as `key=value` lines; unset options and disabled flags are omitted.
+    pub fn to_file_contents(&self) -> String {
+        let mut out = String::new();
+        if let Some(mode) = self.default_search_mode {
+            out.push_str(&format!("default_search_mode={}\n", mode.key()));
+        }
+        if let Some(mode) = self.last_search_mode {
+            out.push_str(&format!("last_search_mode={}\n", mode.key()));
+        }
+        if self.not_semantics != NotSemantics::Exclude {
+            out.push_str(&format!("not_semantics={}\n", self.not_semantics.key()));
+        }
+        if self.two_column_results {
+            out.push_str("two_column_results=true\n");
+        }
+        if self.abbreviate_references {
+            out.push_str("abbreviate_references=true\n");
+        }
+        if self.zebra_stripes {
+            out.push_str("zebra_stripes=true\n");
+        }
+        if self.dark_theme {
+            out.push_str("dark_theme=true\n");
+        }
+        if self.navigator_open {
+            out.push_str("navigator_open=true\n");
+        }
+        if self.search_help_open {
+            out.push_str("search_help_open=true\n");
+        }
+        if self.lookup_tab_active {
+            out.push_str("lookup_tab_active=true\n");
+        }
+        if let Some([search, lookup, compare]) = self.panel_weights {
+            out.push_str(&format!("panel_weights={},{},{}\n", search, lookup, compare));
+        }
+        if self.compare_subdirectories {
+            out.push_str("compare_subdirectories=true\n");
+        }
+        if self.show_raw_text {
+            out.push_str("show_raw_text=true\n");
+        }
+        if self.unified_results {
+            out.push_str("unified_results=true\n");
+        }
+        if let Some(ContentWidth(width)) = self.content_width {
+            out.push_str(&format!("content_width={}\n", width));
+        }
+        if let Some(verses) = self.context_verses {
+            out.push_str(&format!("context_verses={}\n", verses));
+        }
+        if let Some(limit) = self.copy_limit {
+            out.push_str(&format!("copy_limit={}\n", limit));
+        }
+        if let Some(dir) = self.db_dir.as_deref().filter(|dir| can_remember_dir(dir)).and_then(Path::to_str) {
+            out.push_str(&format!("db_dir={}\n", dir));
+        }
+        if self.leading_numbers != LeadingNumbers::Auto {
+            out.push_str(&format!("leading_verse_numbers={}\n", self.leading_numbers.key()));
+        }
+        if !self.excluded_books.is_empty() {
+            let books: Vec<String> = self.excluded_books.iter().map(u32::to_string).collect();
+            out.push_str(&format!("excluded_books={}\n", books.join(",")));
+        }
+        if let Some((width, height)) = self.window_size {
+            out.push_str(&format!("window_size={}x{}\n", width, height));
+        }
+        if let Some((x, y)) = self.window_position {
+            out.push_str(&format!("window_position={},{}\n", x, y));
+        }
+        out
+    }
+}
+
+/// Two numbers separated by `separator`, as in "800x600"; None when either is malformed.
+fn parse_pair<T: std::str::FromStr>(value: &str, separator: char) -> Option<(T, T)> {
+    let (first, second) = value.split_once(separator)?;
+    Some((first.trim().parse().ok()?, second.trim().parse().ok()?))
+}
+
+/// Whether the settings file can hold `dir` exactly: its lines are UTF-8 with trimmed
+/// values, so a non-UTF-8 path, a line break or surrounding spaces would not read back
+/// as the same directory.
+pub fn can_remember_dir(dir: &Path) -> bool {
+    dir.to_str().is_some_and(|dir| !dir.contains(['\n', '\r']) && dir.trim() == dir)
+}
+
+/// Read settings; a missing or unreadable file yields the defaults.
+pub fn load_settings(path: &Path) -> AppSettings {
+    fs::read_to_string(path)
+        .map(|contents| AppSettings::parse(&contents))
+        .unwrap_or_default()
+}
+
+/// Write settings, creating the config directory if needed.
+pub fn save_settings(path: &Path, settings: &AppSettings) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, settings.to_file_contents())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_pinned_default_mode_overrides_the_last_used_one() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.initial_search_mode(), SearchMode::Plain);
+        settings.last_search_mode = Some(SearchMode::Regex);
+        assert_eq!(settings.initial_search_mode(), SearchMode::Regex);
+        settings.default_search_mode = Some(SearchMode::Lines);
+        assert_eq!(settings.initial_search_mode(), SearchMode::Lines);
+
+        let reloaded = AppSettings::parse(&settings.to_file_contents());
+        assert_eq!(reloaded.initial_search_mode(), SearchMode::Lines);
+        let resumed = AppSettings::parse("default_search_mode=\nlast_search_mode=word_count\n");
+        assert_eq!(resumed.initial_search_mode(), SearchMode::WordCount);
+    }
+
+    #[test]
+    fn next_search_mode_cycles_in_order_and_wraps() {
+        assert_eq!(SearchMode::Plain.next(), SearchMode::Lines);
+        assert_eq!(SearchMode::Lines.next(), SearchMode::WordCount);
+        assert_eq!(SearchMode::WordCount.next(), SearchMode::Regex);
+        assert_eq!(SearchMode::Regex.next(), SearchMode::Plain);
+    }
+
+    #[test]
+    fn context_verses_default_and_cap() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.context_verses(), DEFAULT_CONTEXT_VERSES);
+        settings.context_verses = Some(0);
+        assert_eq!(AppSettings::parse(&settings.to_file_contents()).context_verses(), 0);
+        assert_eq!(AppSettings::parse("context_verses=50\n").context_verses(), 5);
+        assert_eq!(AppSettings::parse("context_verses=many\n").context_verses(), DEFAULT_CONTEXT_VERSES);
+    }
+
+    #[test]
+    fn only_directories_that_read_back_are_saved() {
+        let mut settings = AppSettings { db_dir: Some(PathBuf::from("/home/me/Bibles")), ..AppSettings::default() };
+        assert_eq!(AppSettings::parse(&settings.to_file_contents()).db_dir, settings.db_dir);
+        for dir in ["/home/me/Bibles\nzebra_stripes=true", " /home/me/Bibles "] {
+            assert!(!can_remember_dir(Path::new(dir)));
+            settings.db_dir = Some(PathBuf::from(dir));
+            assert!(!settings.to_file_contents().contains("db_dir="));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_directories_are_not_saved() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = Path::new(std::ffi::OsStr::from_bytes(b"/home/me/B\xffbles"));
+        assert!(!can_remember_dir(dir));
+        let settings = AppSettings { db_dir: Some(dir.to_path_buf()), ..AppSettings::default() };
+        assert!(!settings.to_file_contents().contains("db_dir="));
+    }
+
+    #[test]
+    fn layout_state_round_trips() {
+        let layout = AppSettings {
+            navigator_open: true,
+            search_help_open: true,
+            lookup_tab_active: true,
+            unified_results: true,
+            panel_weights: Some([1, 3, 2]),
+            ..AppSettings::default()
+        };
+        assert_eq!(AppSettings::parse(&layout.to_file_contents()), layout);
+        assert_eq!(AppSettings::parse(&AppSettings::default().to_file_contents()), AppSettings::default());
+
+        // Malformed layout values fall back to the defaults instead of failing.
+        let malformed = AppSettings::parse("navigator_open=yes\npanel_weights=1,2\nlookup_tab_active\n");
+        assert_eq!(malformed, AppSettings::default());
+        assert_eq!(AppSettings::parse("panel_weights=1,x,1").panel_weights, None);
+    }
+
+    #[test]
+    fn not_semantics_round_trip() {
+        let operand = AppSettings { not_semantics: NotSemantics::Operand, ..AppSettings::default() };
+        assert_eq!(operand.to_file_contents(), "not_semantics=operand\n");
+        assert_eq!(AppSettings::parse(&operand.to_file_contents()), operand);
+        assert_eq!(AppSettings::parse("not_semantics=sometimes").not_semantics, NotSemantics::Exclude);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::settings::config_dir;
+
+// -------------------------------
+// Search Term Frequency
+// -------------------------------
+
+/// Most distinct terms remembered; the least-searched are dropped beyond this.
+const MAX_TRACKED_TERMS: usize = 200;
+
+/// Where term counts are kept between runs.
+pub fn term_counts_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("term_counts.txt"))
+}
+
+/// How often each search term has been submitted, case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TermFrequency {
+    counts: HashMap<String, u64>,
+}
+
+impl TermFrequency {
+    /// Count one submission of each term. Operators are never counted, and a term
+    /// repeated within one search counts once.
+    pub fn record<'t, I>(&mut self, terms: I)
+    where
+        I: IntoIterator<Item = &'t str>,
+    {
+        let mut seen: Vec<String> = Vec::new();
+        for term in terms {
+            let term = term.trim().to_lowercase();
+            if term.is_empty() || matches!(term.as_str(), "and" | "or" | "not") || seen.contains(&term) {
+                continue;
+            }
+            *self.counts.entry(term.clone()).or_insert(0) += 1;
+            seen.push(term);
+        }
+        self.prune();
+    }
+
+    /// Drop the least-searched terms (ties broken alphabetically) beyond `MAX_TRACKED_TERMS`.
+    fn prune(&mut self) {
+        if self.counts.len() <= MAX_TRACKED_TERMS {
+            return;
+        }
+        let keep: Vec<String> = self.top(MAX_TRACKED_TERMS).into_iter().map(|(t, _)| t.to_string()).collect();
+        self.counts.retain(|term, _| keep.contains(term));
+    }
+
+    /// The `n` most-searched terms, most frequent first, ties in alphabetical order.
+    pub fn top(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut ranked: Vec<(&str, u64)> = self.counts.iter().map(|(t, &c)| (t.as_str(), c)).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Parse `count<TAB>term` lines; malformed lines are skipped.
+    pub fn parse(contents: &str) -> TermFrequency {
+        let mut frequency = TermFrequency::default();
+        for line in contents.lines() {
+            let Some((count, term)) = line.split_once('\t') else {
+                continue;
+            };
+            if let (Ok(count), false) = (count.trim().parse::<u64>(), term.trim().is_empty()) {
+                frequency.counts.insert(term.trim().to_string(), count);
+            }
+        }
+        frequency.prune();
+        frequency
+    }
+
+    /// Serialize as `count<TAB>term` lines, most frequent first.
+    pub fn to_file_contents(&self) -> String {
+        self.top(self.counts.len())
+            .into_iter()
+            .map(|(term, count)| format!("{}\t{}\n", count, term))
+            .collect()
+    }
+}
+
+/// Read term counts; a missing or unreadable file yields none.
+pub fn load_term_counts(path: &Path) -> TermFrequency {
+    fs::read_to_string(path)
+        .map(|contents| TermFrequency::parse(&contents))
+        .unwrap_or_default()
+}
+
+/// Write term counts, creating the config directory if needed.
+pub fn save_term_counts(path: &Path, frequency: &TermFrequency) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, frequency.to_file_contents())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_are_counted_once_per_search_without_operators() {
+        let mut frequency = TermFrequency::default();
+        frequency.record(["Faith", "AND", "hope", "faith", " "]);
+        frequency.record(["faith", "OR", "love", "not"]);
+        assert_eq!(frequency.top(5), [("faith", 2), ("hope", 1), ("love", 1)]);
+        assert_eq!(frequency.top(1), [("faith", 2)]);
+    }
+
+    #[test]
+    fn counts_round_trip_and_keep_the_most_searched() {
+        let mut frequency = TermFrequency::default();
+        frequency.record(["grace", "peace"]);
+        frequency.record(["grace"]);
+        assert_eq!(frequency.to_file_contents(), "2\tgrace\n1\tpeace\n");
+        assert_eq!(TermFrequency::parse(&frequency.to_file_contents()), frequency);
+        assert!(TermFrequency::parse("x\tgrace\n3\t \nno tab\n").top(5).is_empty());
+
+        let many: String = (0..MAX_TRACKED_TERMS + 10).map(|n| format!("{}\tterm{:03}\n", n + 1, n)).collect();
+        let pruned = TermFrequency::parse(&many);
+        assert_eq!(pruned.top(usize::MAX).len(), MAX_TRACKED_TERMS);
+        assert_eq!(pruned.top(1), [("term209", 210)]);
+        assert!(pruned.top(usize::MAX).iter().all(|&(_, count)| count > 10));
+    }
+}
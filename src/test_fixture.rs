@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use rusqlite::Connection;
+
+use crate::schema::validate_schema;
+use crate::settings::AppSettings;
+use crate::{
+    load_books, register_plain_text_function, register_regex_function, register_word_count_function, App, Bible, Verse,
+};
+
+// -------------------------------
+// In-Memory Test Module
+// -------------------------------
+
+/// The books of the fixture module: `(book number, short name, long name)`.
+pub const FIXTURE_BOOKS: [(u32, &str, &str); 11] = [
+    (10, "Gen", "Genesis"),
+    (120, "2Ki", "2 Kings"),
+    (230, "Ps", "Psalms"),
+    (260, "Song", "Song of Solomon"),
+    (290, "Joel", "Joel"),
+    (460, "Mal", "Malachi"),
+    (500, "John", "John"),
+    (640, "Phm", "Philemon"),
+    (690, "1Jn", "1 John"),
+    (710, "3Jn", "3 John"),
+    (720, "Jude", "Jude"),
+];
+
+/// The verses of the fixture module: `(book number, chapter, verse, text)`.
+pub const FIXTURE_VERSES: [(u32, u32, u32, &str); 21] = [
+    (10, 1, 1, "In the beginning God created the heaven and the earth."),
+    (10, 1, 2, "And the earth was without form, and void; and darkness was upon the face of the deep. And the Spirit of God moved upon the face of the waters."),
+    (10, 1, 3, "And God said, Let there be light: and there was light."),
+    (10, 3, 15, "And I will put enmity between thee and the woman, and between thy seed and her seed; it shall bruise thy head, and thou shalt bruise his heel."),
+    (120, 2, 11, "And it came to pass, as they still went on, and talked, that, behold, there appeared a chariot of fire, and horses of fire, and parted them both asunder; and Elijah went up by a whirlwind into heaven."),
+    (230, 23, 1, "The LORD is my shepherd; I shall not want."),
+    (230, 23, 2, "He maketh me to lie down in green pastures: he leadeth me beside the still waters."),
+    (230, 23, 3, "He restoreth my soul: he leadeth me in the paths of righteousness for his name's sake."),
+    (230, 23, 4, "Yea, though I walk through the valley of the shadow of death, I will fear no evil: for thou art with me; thy rod and thy staff they comfort me."),
+    (230, 23, 5, "Thou preparest a table before me in the presence of mine enemies: thou anointest my head with oil; my cup runneth over."),
+    (230, 23, 6, "Surely goodness and mercy shall follow me all the days of my life: and I will dwell in the house of the LORD for ever."),
+    (230, 119, 1, "Blessed are the undefiled in the way, who walk in the law of the LORD."),
+    (230, 119, 2, "Blessed are they that keep his testimonies, and that seek him with the whole heart."),
+    (260, 2, 1, "I am the rose of Sharon, and the lily of the valleys."),
+    (500, 1, 1, "In the beginning was the Word, and the Word was with God, and the Word was God."),
+    (500, 3, 16, "<J>For God so loved the world, that he gave his only begotten Son, that whosoever believeth in him should not perish, but have everlasting life.</J>"),
+    (500, 11, 35, "Jesus wept."),
+    (640, 1, 1, "Paul, a prisoner of Jesus Christ, and Timothy our brother, unto Philemon our dearly beloved, and fellowlabourer,"),
+    (690, 4, 9, "In this was manifested the love of God toward us, because that God sent his only begotten Son into the world, that we might live through him."),
+    (720, 1, 1, "Jude, the servant of Jesus Christ, and brother of James, to them that are sanctified by God the Father, and preserved in Jesus Christ, and called:"),
+    (720, 1, 2, "Mercy unto you, and peace, and love, be multiplied."),
+];
+
+/// A `:memory:` database laid out like a MyBible module (`info`, `books` and `verses`
+/// tables) holding `FIXTURE_BOOKS` and `FIXTURE_VERSES`. No SQL functions are registered,
+/// so tests can add rows or tables before turning it into a `Bible`.
+pub fn fixture_connection() -> Connection {
+    let conn = Connection::open_in_memory().expect("in-memory database");
+    fill_module(&conn, "Fixture Bible");
+    conn
+}
+
+/// Write the fixture module, described as `description`, to a module file at `path`.
+pub fn write_fixture_module(path: &Path, description: &str) {
+    let conn = Connection::open(path).expect("fixture module file");
+    fill_module(&conn, description);
+}
+
+fn fill_module(conn: &Connection, description: &str) {
+    conn.execute_batch(
+        "CREATE TABLE info (name TEXT, value TEXT);
+         CREATE TABLE books (book_number NUMERIC, short_name TEXT, long_name TEXT);
+         CREATE TABLE verses (book_number NUMERIC, chapter NUMERIC, verse NUMERIC, text TEXT);",
+    )
+    .expect("fixture tables");
+    conn.execute("INSERT INTO info VALUES ('description', ?)", [description])
+        .expect("fixture description");
+    for (book_number, short_name, long_name) in FIXTURE_BOOKS {
+        conn.execute("INSERT INTO books VALUES (?, ?, ?)", rusqlite::params![book_number, short_name, long_name])
+            .expect("fixture book");
+    }
+    for (book_number, chapter, verse, text) in FIXTURE_VERSES {
+        conn.execute("INSERT INTO verses VALUES (?, ?, ?, ?)", rusqlite::params![book_number, chapter, verse, text])
+            .expect("fixture verse");
+    }
+}
+
+/// `conn` as an opened module, the way `open_bible` would return it.
+pub fn bible_from(conn: Connection) -> Bible {
+    let schema = validate_schema(&conn).expect("fixture schema");
+    register_regex_function(&conn).expect("regexp function");
+    register_word_count_function(&conn).expect("word_count function");
+    register_plain_text_function(&conn).expect("plain_text function");
+    Bible {
+        conn,
+        schema,
+        path: PathBuf::from("Fixture.SQLite3"),
+    }
+}
+
+/// The fixture module, opened.
+pub fn fixture_bible() -> Bible {
+    bible_from(fixture_connection())
+}
+
+/// A verse of one of `FIXTURE_BOOKS`, for tests of code working on verse lists.
+pub fn verse(short_name: &str, chapter: u32, verse: u32, text: &str) -> Verse {
+    let &(book_number, short_name, long_name) = FIXTURE_BOOKS
+        .iter()
+        .find(|(_, short, _)| *short == short_name)
+        .expect("fixture book");
+    Verse {
+        book_number,
+        long_name: long_name.to_string(),
+        short_name: short_name.to_string(),
+        chapter,
+        verse,
+        verse_end: None,
+        text: text.to_string(),
+    }
+}
+
+/// An empty directory of its own for one test, under the system temp directory.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("biblical_gui-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("test directory");
+    dir
+}
+
+static CONFIG_DIR: Once = Once::new();
+
+/// The app as started with default settings, whatever other tests saved, its config
+/// directory pointed at a scratch directory so tests never read or write the user's files,
+/// and `bible` as its main module.
+pub fn fixture_app(bible: Option<Bible>) -> App {
+    CONFIG_DIR.call_once(|| std::env::set_var("XDG_CONFIG_HOME", temp_dir("config")));
+    let (mut app, _) = App::with_settings(AppSettings::default(), None);
+    app.books = bible.as_ref().and_then(|bible| load_books(bible).ok()).unwrap_or_default();
+    app.db = bible;
+    app.status = None;
+    app
+}
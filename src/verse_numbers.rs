@@ -0,0 +1,127 @@
+use std::fmt;
+
+use rusqlite::{Connection, Error as RusqliteError};
+
+// -------------------------------
+// Leading Verse Numbers in Text
+// -------------------------------
+
+/// Verses sampled when checking a module for leading verse numbers.
+const DETECTION_SAMPLE: usize = 200;
+
+/// Share of sampled verses that must start with their own number for the module to count
+/// as numbering its text, in percent. A few verses legitimately start with a number.
+const DETECTION_THRESHOLD_PERCENT: usize = 80;
+
+/// Whether leading verse numbers are stripped from verse text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeadingNumbers {
+    /// Strip them when the module is detected to store them.
+    #[default]
+    Auto,
+    Strip,
+    Keep,
+}
+
+impl LeadingNumbers {
+    pub const ALL: [LeadingNumbers; 3] = [LeadingNumbers::Auto, LeadingNumbers::Strip, LeadingNumbers::Keep];
+
+    /// Stable name used in the settings file.
+    pub fn key(&self) -> &'static str {
+        match self {
+            LeadingNumbers::Auto => "auto",
+            LeadingNumbers::Strip => "strip",
+            LeadingNumbers::Keep => "keep",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<LeadingNumbers> {
+        LeadingNumbers::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    /// Whether to strip, given what detection found for the open module.
+    pub fn strips(&self, detected: bool) -> bool {
+        match self {
+            LeadingNumbers::Auto => detected,
+            LeadingNumbers::Strip => true,
+            LeadingNumbers::Keep => false,
+        }
+    }
+}
+
+impl fmt::Display for LeadingNumbers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            LeadingNumbers::Auto => "Verse numbers in text: detect",
+            LeadingNumbers::Strip => "Verse numbers in text: strip",
+            LeadingNumbers::Keep => "Verse numbers in text: keep",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// The verse text after its own leading verse number ("1 In the beginning" for verse 1),
+/// or None when the text doesn't start with that number followed by whitespace.
+fn after_own_number(verse: u32, text: &str) -> Option<&str> {
+    let number = verse.to_string();
+    let rest = text.trim_start().strip_prefix(number.as_str())?;
+    rest.starts_with(char::is_whitespace).then(|| rest.trim_start())
+}
+
+/// Whether sampled `(verse number, text)` pairs mostly start with their own verse number.
+pub fn numbers_leading(samples: &[(u32, &str)]) -> bool {
+    if samples.is_empty() {
+        return false;
+    }
+    let numbered = samples.iter().filter(|(verse, text)| after_own_number(*verse, text).is_some()).count();
+    numbered * 100 >= samples.len() * DETECTION_THRESHOLD_PERCENT
+}
+
+/// Check a module for verse texts starting with their own verse number, sampling verses
+/// spread through the module rather than only the first chapter.
+pub fn detect_leading_numbers(conn: &Connection) -> Result<bool, RusqliteError> {
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM verses", [], |row| row.get(0))?;
+    let step = (total as usize / DETECTION_SAMPLE).max(1);
+    let mut stmt = conn.prepare("SELECT CAST(verse AS INTEGER), text FROM verses WHERE rowid % ? = 0 LIMIT ?")?;
+    let samples: Vec<(u32, String)> = stmt
+        .query_map([step as i64, DETECTION_SAMPLE as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let samples: Vec<(u32, &str)> = samples.iter().map(|(verse, text)| (*verse, text.as_str())).collect();
+    Ok(numbers_leading(&samples))
+}
+
+/// The verse text without its leading verse number; text without one is returned as is.
+pub fn strip_leading_number(verse: u32, text: &str) -> &str {
+    after_own_number(verse, text).unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_leading_needs_most_samples_numbered() {
+        let numbered = [(1, "1 In the beginning"), (2, "2 And the earth"), (3, "3 And God said"), (4, "4 And God saw")];
+        assert!(numbers_leading(&numbered));
+        // One verse of five legitimately starting with a number still counts as numbered.
+        let mut mostly = numbered.to_vec();
+        mostly.push((5, "And God called"));
+        assert!(numbers_leading(&mostly));
+        let plain = [(1, "In the beginning"), (12, "12 thousand were sealed"), (3, "And God said")];
+        assert!(!numbers_leading(&plain));
+        // Another verse's number doesn't count.
+        assert!(!numbers_leading(&[(2, "1 In the beginning"), (3, "10 And the earth")]));
+        assert!(!numbers_leading(&[]));
+    }
+
+    #[test]
+    fn strip_leading_number_only_strips_the_verse_own_number() {
+        assert_eq!(strip_leading_number(1, "1 In the beginning"), "In the beginning");
+        assert_eq!(strip_leading_number(16, "  16\tFor God so loved"), "For God so loved");
+        assert_eq!(strip_leading_number(1, "12 thousand"), "12 thousand");
+        assert_eq!(strip_leading_number(1, "1,000 men"), "1,000 men");
+        assert_eq!(strip_leading_number(2, "1 In the beginning"), "1 In the beginning");
+        assert_eq!(strip_leading_number(1, "In the beginning"), "In the beginning");
+    }
+}
@@ -0,0 +1,96 @@
+// -------------------------------
+// Versification Mapping
+// -------------------------------
+
+/// A run of verses a translation numbers differently from the canonical (KJV) scheme:
+/// verses `first..=last` of `chapter` are canonical verses `to_first..` of `to_chapter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerseShift {
+    pub book: u32,
+    pub chapter: u32,
+    pub first: u32,
+    pub last: u32,
+    pub to_chapter: u32,
+    pub to_first: u32,
+}
+
+/// A family of translations sharing a numbering scheme, recognized by a verse that only
+/// exists in that scheme. Book numbers follow the MyBible convention (Genesis is 10).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Versification {
+    pub name: &'static str,
+    /// `(book, chapter, verse)` present in this scheme but not in the canonical one.
+    pub marker: (u32, u32, u32),
+    pub shifts: &'static [VerseShift],
+}
+
+const fn shift(book: u32, chapter: u32, first: u32, last: u32, to_chapter: u32, to_first: u32) -> VerseShift {
+    VerseShift { book, chapter, first, last, to_chapter, to_first }
+}
+
+/// Known numbering schemes differing from the KJV, and how they map onto it.
+pub const VERSIFICATIONS: [Versification; 2] = [
+    // Hebrew chapter divisions, followed by many modern European translations.
+    Versification {
+        name: "Hebrew",
+        marker: (460, 3, 19),
+        shifts: &[
+            shift(10, 32, 1, 1, 31, 55),   // Genesis 32:1 = 31:55
+            shift(10, 32, 2, 33, 32, 1),   // Genesis 32:2-33 = 32:1-32
+            shift(40, 17, 1, 15, 16, 36),  // Numbers 17:1-15 = 16:36-50
+            shift(40, 17, 16, 28, 17, 1),  // Numbers 17:16-28 = 17:1-13
+            shift(50, 13, 1, 1, 12, 32),   // Deuteronomy 13:1 = 12:32
+            shift(50, 13, 2, 19, 13, 1),   // Deuteronomy 13:2-19 = 13:1-18
+            shift(110, 5, 1, 14, 4, 21),   // 1 Kings 5:1-14 = 4:21-34
+            shift(110, 5, 15, 32, 5, 1),   // 1 Kings 5:15-32 = 5:1-18
+            shift(290, 3, 1, 5, 2, 28),    // Joel 3:1-5 = 2:28-32
+            shift(290, 4, 1, 21, 3, 1),    // Joel 4:1-21 = 3:1-21
+            shift(460, 3, 19, 24, 4, 1),   // Malachi 3:19-24 = 4:1-6
+        ],
+    },
+    // Verses split differently in several modern English translations.
+    Versification {
+        name: "Modern English",
+        marker: (710, 1, 15),
+        shifts: &[
+            shift(710, 1, 15, 15, 1, 14),  // 3 John 1:15 is part of 1:14
+            shift(730, 12, 18, 18, 13, 1), // Revelation 12:18 is part of 13:1
+        ],
+    },
+];
+
+/// The canonical `(chapter, verse)` of a verse numbered under `schemes`; verses no
+/// scheme shifts keep their numbers.
+pub fn to_canonical(schemes: &[Versification], book: u32, chapter: u32, verse: u32) -> (u32, u32) {
+    schemes
+        .iter()
+        .flat_map(|scheme| scheme.shifts.iter())
+        .find(|s| s.book == book && s.chapter == chapter && (s.first..=s.last).contains(&verse))
+        .map(|s| (s.to_chapter, s.to_first + (verse - s.first)))
+        .unwrap_or((chapter, verse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hebrew_numbering_maps_onto_the_kjv() {
+        let hebrew = [VERSIFICATIONS[0]];
+        assert_eq!(to_canonical(&hebrew, 460, 3, 19), (4, 1));
+        assert_eq!(to_canonical(&hebrew, 460, 3, 24), (4, 6));
+        assert_eq!(to_canonical(&hebrew, 290, 3, 1), (2, 28));
+        assert_eq!(to_canonical(&hebrew, 290, 4, 21), (3, 21));
+        assert_eq!(to_canonical(&hebrew, 10, 32, 1), (31, 55));
+        assert_eq!(to_canonical(&hebrew, 460, 3, 18), (3, 18));
+        assert_eq!(to_canonical(&[], 460, 3, 19), (3, 19));
+    }
+
+    #[test]
+    fn split_verses_map_onto_the_verse_they_belong_to() {
+        let modern = [VERSIFICATIONS[1]];
+        assert_eq!(to_canonical(&modern, 710, 1, 15), (1, 14));
+        assert_eq!(to_canonical(&modern, 730, 12, 18), (13, 1));
+        assert_eq!(to_canonical(&modern, 730, 12, 17), (12, 17));
+    }
+}